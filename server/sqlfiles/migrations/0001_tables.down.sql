@@ -0,0 +1,30 @@
+DROP TABLE IF EXISTS deletecardtypevotes;
+DROP TABLE IF EXISTS deletecardvotes;
+DROP TABLE IF EXISTS collectormoderators;
+DROP TABLE IF EXISTS cardtypevotes;
+DROP TABLE IF EXISTS cardvotes;
+DROP TABLE IF EXISTS achievementunlocks;
+DROP TABLE IF EXISTS achievementconditions;
+DROP TABLE IF EXISTS achievements;
+DROP TABLE IF EXISTS achievementtypes;
+DROP TABLE IF EXISTS packtimes;
+DROP TABLE IF EXISTS packstats;
+DROP TABLE IF EXISTS tradesuggestions;
+DROP TABLE IF EXISTS tradecards;
+DROP TABLE IF EXISTS trades;
+DROP TABLE IF EXISTS cardunlocks;
+DROP TABLE IF EXISTS cardeffects;
+DROP TABLE IF EXISTS cardframes;
+DROP TABLE IF EXISTS deletecards;
+DROP TABLE IF EXISTS cards;
+DROP TABLE IF EXISTS deletecardtypes;
+DROP TABLE IF EXISTS cardtypes;
+DROP TABLE IF EXISTS collectorsettings;
+DROP TABLE IF EXISTS collectorfavorites;
+DROP TABLE IF EXISTS notifications;
+DROP TABLE IF EXISTS passwordresetkeys;
+DROP TABLE IF EXISTS verificationkeys;
+DROP TABLE IF EXISTS friends;
+DROP TABLE IF EXISTS refreshtokens;
+DROP TABLE IF EXISTS collectors;
+DROP TABLE IF EXISTS users;
@@ -0,0 +1,11 @@
+-- Revert image hash columns for MediaManager integration
+
+ALTER TABLE cards
+DROP COLUMN IF EXISTS cimage;
+
+ALTER TABLE collectors
+DROP COLUMN IF EXISTS coimage,
+DROP COLUMN IF EXISTS cobanner;
+
+ALTER TABLE users
+DROP COLUMN IF EXISTS uprofileimage;
@@ -0,0 +1,11 @@
+-- Revert rarity tiers
+
+ALTER TABLE cards
+	DROP FOREIGN KEY fk_cards_raid,
+	DROP COLUMN IF EXISTS raid;
+
+ALTER TABLE cardtypes
+	DROP FOREIGN KEY fk_cardtypes_raid,
+	DROP COLUMN IF EXISTS raid;
+
+DROP TABLE IF EXISTS rarities;
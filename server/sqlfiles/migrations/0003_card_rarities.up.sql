@@ -0,0 +1,23 @@
+-- Rarity tiers with drop weights, assignable to card types and cards
+-- A card uses its own rarity, then the rarity of its card type
+
+CREATE TABLE IF NOT EXISTS rarities (
+	raid VARCHAR(13) NOT NULL,
+	coid VARCHAR(13) NOT NULL,
+	raname TINYTEXT NOT NULL,
+	raweight INT NOT NULL,
+	ratime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	PRIMARY KEY (raid),
+	FOREIGN KEY (coid) REFERENCES collectors(coid)
+	ON DELETE CASCADE
+) ENGINE = InnoDB;
+
+ALTER TABLE cardtypes
+	ADD COLUMN IF NOT EXISTS raid VARCHAR(13) NULL,
+	ADD CONSTRAINT fk_cardtypes_raid FOREIGN KEY (raid) REFERENCES rarities(raid)
+	ON DELETE SET NULL;
+
+ALTER TABLE cards
+	ADD COLUMN IF NOT EXISTS raid VARCHAR(13) NULL,
+	ADD CONSTRAINT fk_cards_raid FOREIGN KEY (raid) REFERENCES rarities(raid)
+	ON DELETE SET NULL;
@@ -0,0 +1,4 @@
+-- Revert pity counter
+
+ALTER TABLE packtimes
+DROP COLUMN IF EXISTS ptpity;
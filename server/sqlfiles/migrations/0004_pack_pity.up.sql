@@ -0,0 +1,4 @@
+-- Packs opened in a row without a card that counts for pity
+
+ALTER TABLE packtimes
+ADD COLUMN IF NOT EXISTS ptpity INT NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+-- Revert pack idempotency keys
+
+DROP TABLE IF EXISTS packopencards;
+DROP TABLE IF EXISTS packopens;
@@ -0,0 +1,26 @@
+-- Pack openings done with an Idempotency-Key, so a retried request returns the original pack
+
+CREATE TABLE IF NOT EXISTS packopens (
+	poid INT NOT NULL AUTO_INCREMENT,
+	uid VARCHAR(13) NOT NULL,
+	coid VARCHAR(13) NOT NULL,
+	pokey VARCHAR(255) NOT NULL,
+	popitycount INT NOT NULL,
+	popitythreshold INT NOT NULL,
+	poguaranteed INT NOT NULL,
+	potime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	PRIMARY KEY (poid),
+	UNIQUE (uid, coid, pokey),
+	FOREIGN KEY (uid) REFERENCES users(uid)
+	ON DELETE CASCADE,
+	FOREIGN KEY (coid) REFERENCES collectors(coid)
+	ON DELETE CASCADE
+) ENGINE = InnoDB;
+
+CREATE TABLE IF NOT EXISTS packopencards (
+	poid INT NOT NULL,
+	cuid VARCHAR(13) NOT NULL,
+	PRIMARY KEY (poid, cuid),
+	FOREIGN KEY (poid) REFERENCES packopens(poid)
+	ON DELETE CASCADE
+) ENGINE = InnoDB;
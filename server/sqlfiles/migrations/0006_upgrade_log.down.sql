@@ -0,0 +1,3 @@
+-- Revert upgrade log
+
+DROP TABLE IF EXISTS upgradelog;
@@ -0,0 +1,24 @@
+-- Audit trail of card upgrades, used to tune upgrade odds
+-- Consumed and created cards are not foreign keys since they can be deleted later on
+
+CREATE TABLE IF NOT EXISTS upgradelog (
+	ulid INT NOT NULL AUTO_INCREMENT,
+	uid VARCHAR(13),
+	cid VARCHAR(13),
+	ulcardone VARCHAR(13) NOT NULL,
+	ulcardtwo VARCHAR(13) NOT NULL,
+	ullevel INT NOT NULL,
+	ulqualityone INT NOT NULL,
+	ulqualitytwo INT NOT NULL,
+	ulchance FLOAT NOT NULL,
+	ulsuccess INT NOT NULL,
+	ulresult VARCHAR(13) NOT NULL,
+	ulresultlevel INT NOT NULL,
+	ulresultquality INT NOT NULL,
+	ultime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	PRIMARY KEY (ulid),
+	FOREIGN KEY (uid) REFERENCES users(uid)
+	ON DELETE SET NULL,
+	FOREIGN KEY (cid) REFERENCES cards(cid)
+	ON DELETE SET NULL
+) ENGINE = InnoDB;
@@ -0,0 +1,5 @@
+-- Revert trade offers and history
+
+DROP TABLE IF EXISTS tradehistory;
+DROP TABLE IF EXISTS tradeoffercards;
+DROP TABLE IF EXISTS tradeoffers;
@@ -0,0 +1,59 @@
+-- Trade offers are immutable snapshots of a trade, created when a user confirms
+-- A counter-offer supersedes the open offer and references it as toparent
+
+CREATE TABLE IF NOT EXISTS tradeoffers (
+	toid VARCHAR(13) NOT NULL,
+	tid VARCHAR(13) NOT NULL,
+	coid VARCHAR(13) NOT NULL,
+	uidfrom VARCHAR(13) NOT NULL,
+	uidto VARCHAR(13) NOT NULL,
+	toparent VARCHAR(13),
+	tostatus INT NOT NULL,
+	tocreated DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	toexpires DATETIME NOT NULL,
+	toresolved DATETIME,
+	PRIMARY KEY (toid),
+	FOREIGN KEY (tid) REFERENCES trades(tid)
+	ON DELETE CASCADE,
+	FOREIGN KEY (coid) REFERENCES collectors(coid)
+	ON DELETE CASCADE,
+	FOREIGN KEY (uidfrom) REFERENCES users(uid)
+	ON DELETE CASCADE,
+	FOREIGN KEY (uidto) REFERENCES users(uid)
+	ON DELETE CASCADE,
+	FOREIGN KEY (toparent) REFERENCES tradeoffers(toid)
+	ON DELETE SET NULL
+) ENGINE = InnoDB;
+
+-- Cards are not foreign keys, an offer stays as it was even if a card is gone
+CREATE TABLE IF NOT EXISTS tradeoffercards (
+	toid VARCHAR(13) NOT NULL,
+	cuid VARCHAR(13) NOT NULL,
+	uidfrom VARCHAR(13) NOT NULL,
+	PRIMARY KEY (toid, cuid),
+	FOREIGN KEY (toid) REFERENCES tradeoffers(toid)
+	ON DELETE CASCADE
+) ENGINE = InnoDB;
+
+-- Cards moved by completed trades
+CREATE TABLE IF NOT EXISTS tradehistory (
+	thid INT NOT NULL AUTO_INCREMENT,
+	toid VARCHAR(13) NOT NULL,
+	coid VARCHAR(13) NOT NULL,
+	cuid VARCHAR(13) NOT NULL,
+	cid VARCHAR(13),
+	uidfrom VARCHAR(13),
+	uidto VARCHAR(13),
+	thlevel INT NOT NULL,
+	thquality INT NOT NULL,
+	thtime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	PRIMARY KEY (thid),
+	FOREIGN KEY (coid) REFERENCES collectors(coid)
+	ON DELETE CASCADE,
+	FOREIGN KEY (cid) REFERENCES cards(cid)
+	ON DELETE SET NULL,
+	FOREIGN KEY (uidfrom) REFERENCES users(uid)
+	ON DELETE SET NULL,
+	FOREIGN KEY (uidto) REFERENCES users(uid)
+	ON DELETE SET NULL
+) ENGINE = InnoDB;
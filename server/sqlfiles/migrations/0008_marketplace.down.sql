@@ -0,0 +1,4 @@
+-- Revert the marketplace
+
+DROP TABLE IF EXISTS marketlistings;
+DROP TABLE IF EXISTS balances;
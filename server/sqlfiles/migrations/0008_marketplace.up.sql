@@ -0,0 +1,37 @@
+-- Currency balance of a user in a collector, bdaily is the last claimed daily reward
+
+CREATE TABLE IF NOT EXISTS balances (
+	uid VARCHAR(13) NOT NULL,
+	coid VARCHAR(13) NOT NULL,
+	bamount INT NOT NULL DEFAULT 0,
+	bdaily DATETIME,
+	PRIMARY KEY (uid, coid),
+	FOREIGN KEY (uid) REFERENCES users(uid)
+	ON DELETE CASCADE,
+	FOREIGN KEY (coid) REFERENCES collectors(coid)
+	ON DELETE CASCADE
+) ENGINE = InnoDB;
+
+-- Cards listed on the market of a collector, a card can only have one open listing
+CREATE TABLE IF NOT EXISTS marketlistings (
+	mlid VARCHAR(13) NOT NULL,
+	cuid VARCHAR(13) NOT NULL,
+	coid VARCHAR(13) NOT NULL,
+	uid VARCHAR(13) NOT NULL,
+	mlprice INT NOT NULL,
+	mlstatus INT NOT NULL,
+	uidbuyer VARCHAR(13),
+	mltime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	mlresolved DATETIME,
+	PRIMARY KEY (mlid),
+	INDEX (coid, mlstatus),
+	INDEX (cuid, mlstatus),
+	FOREIGN KEY (cuid) REFERENCES cardunlocks(cuid)
+	ON DELETE CASCADE,
+	FOREIGN KEY (coid) REFERENCES collectors(coid)
+	ON DELETE CASCADE,
+	FOREIGN KEY (uid) REFERENCES users(uid)
+	ON DELETE CASCADE,
+	FOREIGN KEY (uidbuyer) REFERENCES users(uid)
+	ON DELETE SET NULL
+) ENGINE = InnoDB;
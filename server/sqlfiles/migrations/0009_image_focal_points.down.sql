@@ -0,0 +1,3 @@
+-- Revert image focal points
+
+DROP TABLE IF EXISTS imagefocalpoints;
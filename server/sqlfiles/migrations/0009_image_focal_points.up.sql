@@ -0,0 +1,9 @@
+-- Focal point of an uploaded image, relative coordinates (0..1) the smart_crop effect crops around
+-- Keyed by the image hash, so it applies to every entity using the image
+
+CREATE TABLE IF NOT EXISTS imagefocalpoints (
+	ifpimage VARCHAR(64) NOT NULL,
+	ifpx FLOAT NOT NULL,
+	ifpy FLOAT NOT NULL,
+	PRIMARY KEY (ifpimage)
+) ENGINE = InnoDB;
@@ -0,0 +1,5 @@
+-- Revert the unique open listing key
+
+ALTER TABLE marketlistings
+	DROP INDEX mlopencuid,
+	DROP COLUMN mlopencuid;
@@ -0,0 +1,7 @@
+-- A card can only have one open listing
+-- mlopencuid is the card of open listings (mlstatus 0) and NULL otherwise, its unique key
+-- makes sure a card has at most one open listing while closed ones are kept as history
+
+ALTER TABLE marketlistings
+	ADD COLUMN mlopencuid VARCHAR(13) AS (IF(mlstatus = 0, cuid, NULL)) STORED,
+	ADD UNIQUE KEY (mlopencuid);
@@ -0,0 +1,12 @@
+-- Revert keeping listings of dismantled cards, their history is lost
+
+DELETE FROM marketlistings
+	WHERE cuid IS NULL;
+
+ALTER TABLE marketlistings
+	DROP FOREIGN KEY fk_marketlistings_cuid;
+
+ALTER TABLE marketlistings
+	MODIFY cuid VARCHAR(13) NOT NULL,
+	ADD CONSTRAINT marketlistings_ibfk_1 FOREIGN KEY (cuid) REFERENCES cardunlocks(cuid)
+	ON DELETE CASCADE;
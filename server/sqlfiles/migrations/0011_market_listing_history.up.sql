@@ -0,0 +1,10 @@
+-- Keep closed listings when their card is dismantled, cuid becomes NULL instead
+-- Open listings always have a card, dismantling refuses listed cards
+
+ALTER TABLE marketlistings
+	DROP FOREIGN KEY marketlistings_ibfk_1;
+
+ALTER TABLE marketlistings
+	MODIFY cuid VARCHAR(13),
+	ADD CONSTRAINT fk_marketlistings_cuid FOREIGN KEY (cuid) REFERENCES cardunlocks(cuid)
+	ON DELETE SET NULL;
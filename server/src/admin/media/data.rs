@@ -0,0 +1,35 @@
+use serde::{Serialize, Deserialize};
+use rocketjson::JsonBody;
+use validator::Validate;
+
+use crate::media::CacheStats;
+use crate::media::gc::GcReport;
+use crate::media::manager::ReloadReport;
+use crate::media::pool::PoolStats;
+
+#[derive(Debug, Serialize)]
+pub struct AdminMediaStatsResponse {
+    pub cache: CacheStats,
+    pub generation: PoolStats
+}
+
+#[derive(Debug, Deserialize, Validate, JsonBody)]
+#[serde(rename_all="camelCase")]
+pub struct AdminMediaGcRequest {
+    pub dry_run: bool
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminMediaGcResponse {
+    pub report: GcReport
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminMediaRegenerateResponse {
+    pub images: usize
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminMediaReloadResponse {
+    pub report: ReloadReport
+}
@@ -0,0 +1,80 @@
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+use rocket::State;
+use rocket::http::Status;
+use std::sync::Arc;
+
+use super::data::{AdminMediaStatsResponse, AdminMediaGcRequest, AdminMediaGcResponse, AdminMediaRegenerateResponse, AdminMediaReloadResponse};
+use crate::config::Config;
+use crate::sql::Sql;
+use crate::media::{MediaManager, MediaGc, ManagerError};
+use crate::shared::crypto::JwtToken;
+use crate::shared::user;
+
+#[get("/admin/media/stats")]
+pub async fn admin_media_stats_route(sql: &State<Sql>, media_manager: &State<MediaManager>, token: JwtToken) -> ApiResponseErr<AdminMediaStatsResponse> {
+    let user_id = token.id;
+
+    if rjtry!(user::sql::get_user_rank(sql, &user_id).await) != user::data::UserRanking::Admin {
+        return ApiResponseErr::api_err(Status::Forbidden, String::from("Missing admin permissions"))
+    }
+
+    ApiResponseErr::ok(Status::Ok, AdminMediaStatsResponse {
+        cache: media_manager.cache_stats(),
+        generation: media_manager.generation_stats()
+    })
+}
+
+#[post("/admin/media/gc", data="<data>")]
+pub async fn admin_media_gc_route(data: AdminMediaGcRequest, sql: &State<Sql>, media_gc: &State<Arc<MediaGc>>, token: JwtToken) -> ApiResponseErr<AdminMediaGcResponse> {
+    let user_id = token.id;
+
+    if rjtry!(user::sql::get_user_rank(sql, &user_id).await) != user::data::UserRanking::Admin {
+        return ApiResponseErr::api_err(Status::Forbidden, String::from("Missing admin permissions"))
+    }
+
+    let report = rjtry!(media_gc.run(sql, data.dry_run).await);
+
+    ApiResponseErr::ok(Status::Ok, AdminMediaGcResponse {
+        report
+    })
+}
+
+///regenerates all cached variants of a media type in the background, e.g. after its config changed
+///images are listed from the cache, ones without a cached variant are left to their first request
+#[post("/admin/media/<media_type>/regenerate")]
+pub async fn admin_media_regenerate_route(media_type: String, sql: &State<Sql>, media_manager: &State<MediaManager>, token: JwtToken) -> ApiResponseErr<AdminMediaRegenerateResponse> {
+    let user_id = token.id;
+
+    if rjtry!(user::sql::get_user_rank(sql, &user_id).await) != user::data::UserRanking::Admin {
+        return ApiResponseErr::api_err(Status::Forbidden, String::from("Missing admin permissions"))
+    }
+
+    let images = match media_manager.regenerate_media_type(&media_type).await {
+        Ok(images) => images,
+        Err(ManagerError::UnknownMediaType(_)) => return ApiResponseErr::api_err(Status::NotFound, String::from("Unknown media type")),
+        Err(err) => return ApiResponseErr::api_err(Status::InternalServerError, err.to_string())
+    };
+
+    ApiResponseErr::ok(Status::Accepted, AdminMediaRegenerateResponse {
+        images
+    })
+}
+
+///re-reads the media type configs, a broken config is reported and the loaded ones stay in place
+#[post("/admin/media/reload")]
+pub async fn admin_media_reload_route(sql: &State<Sql>, config: &State<Config>, media_manager: &State<MediaManager>, token: JwtToken) -> ApiResponseErr<AdminMediaReloadResponse> {
+    let user_id = token.id;
+
+    if rjtry!(user::sql::get_user_rank(sql, &user_id).await) != user::data::UserRanking::Admin {
+        return ApiResponseErr::api_err(Status::Forbidden, String::from("Missing admin permissions"))
+    }
+
+    let report = match media_manager.reload_media_types(&config.media_types_dir) {
+        Ok(report) => report,
+        Err(err) => return ApiResponseErr::api_err(Status::UnprocessableEntity, err.to_string())
+    };
+
+    ApiResponseErr::ok(Status::Ok, AdminMediaReloadResponse {
+        report
+    })
+}
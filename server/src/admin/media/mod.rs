@@ -0,0 +1,4 @@
+mod data;
+mod logic;
+
+pub use logic::{admin_media_stats_route, admin_media_gc_route, admin_media_regenerate_route, admin_media_reload_route};
@@ -1,23 +1,24 @@
 use rocket::State;
-use rocket::http::{Status, ContentType};
+use rocket::http::Status;
 
 use crate::sql::Sql;
-use crate::media::MediaManager;
+use crate::media::{MediaManager, MediaResponse, MediaError, RenderContext};
+use crate::media::sql as media_sql;
 use crate::shared::Id;
 use crate::shared::card;
 
 //NOTE: this collides with /card/unlocked/<card_unlocked_id>
 #[get("/card/<card_id>/card-image", rank=1)]
-pub async fn card_image_get_route(card_id: Id, sql: &State<Sql>, media_manager: &State<MediaManager>) -> Result<(ContentType, Vec<u8>), Status> {
+pub async fn card_image_get_route(card_id: Id, sql: &State<Sql>, media_manager: &State<MediaManager>) -> Result<MediaResponse, MediaError> {
     //NOTE: check card_id to avoid path traversal attacks or similar
     let (card_id, fallback_card_id): (Id, Option<Id>) = match card::sql::get_card(sql, None, &card_id).await {
         Ok(Some(card)) => (card.card_info.id, card.update_card.as_ref().map(|boxed| boxed.card_info.id.clone())),
         Ok(None) => match card::sql::get_card_delete_request(sql, &card_id).await {
             Ok(Some(card_id)) => (card_id, None),
-            Ok(None) => return Err(Status::NotFound),
-            Err(_) => return Err(Status::InternalServerError)
+            Ok(None) => return Err(Status::NotFound.into()),
+            Err(_) => return Err(Status::InternalServerError.into())
         },
-        Err(_) => return Err(Status::InternalServerError)
+        Err(_) => return Err(Status::InternalServerError.into())
     };
 
 
@@ -28,22 +29,25 @@ pub async fn card_image_get_route(card_id: Id, sql: &State<Sql>, media_manager:
             Some(fallback_card_id) => match card::sql::get_card_image(sql, &fallback_card_id).await {
                 Ok(Some(hash)) => hash,
                 Ok(None) => String::from("card-image-default"),
-                Err(_) => return Err(Status::InternalServerError)
+                Err(_) => return Err(Status::InternalServerError.into())
             },
             None => String::from("card-image-default")
         },
-        Err(_) => return Err(Status::InternalServerError)
+        Err(_) => return Err(Status::InternalServerError.into())
     };
 
-    // Get image through MediaManager with "card" media type
-    let (bytes, format) = media_manager
-        .get_image("card", &image_hash, None)
-        .await
-        .map_err(|_| Status::NotFound)?;
+    // Crops are centered on the focal point of the image if it has one
+    let focal_point = match media_sql::get_focal_point(sql, &image_hash).await {
+        Ok(focal_point) => focal_point,
+        Err(_) => return Err(Status::InternalServerError.into())
+    };
+    let context = RenderContext::default().with_focal_point(focal_point);
 
-    // Parse Content-Type based on format
-    let content_type = ContentType::parse_flexible(format.mime_type())
-        .unwrap_or(ContentType::Binary);
+    // Get image through MediaManager with "card" media type
+    let image = media_manager
+        .get_composite_image("card", &image_hash, None, &context, None)
+        .await?;
 
-    Ok((content_type, bytes))
+    // The URL stays the same when the image changes, clients revalidate with the ETag
+    Ok(MediaResponse::revalidate(image))
 }
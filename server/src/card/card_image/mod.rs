@@ -1,7 +1,9 @@
 mod default;
 mod get;
 mod set;
+mod unlocked;
 
 pub use default::card_image_default_route;
 pub use set::card_image_set_route;
 pub use get::card_image_get_route;
+pub use unlocked::{card_image_unlocked_route, card_image_unlocked_default_route};
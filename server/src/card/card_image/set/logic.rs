@@ -4,6 +4,7 @@ use rocket::State;
 use rocket::form::Form;
 
 use crate::sql::Sql;
+use crate::config::Config;
 use crate::media::MediaManager;
 use crate::shared::Id;
 use crate::shared::crypto::JwtToken;
@@ -18,6 +19,7 @@ pub async fn card_image_set_route(
     card_id: Id,
     mut data: Form<CardImageSetRequest<'_>>,
     sql: &State<Sql>,
+    config: &State<Config>,
     media_manager: &State<MediaManager>,
     token: JwtToken
 ) -> ApiResponseErr<CardImageSetResponse> {
@@ -29,8 +31,9 @@ pub async fn card_image_set_route(
     }
 
     // Upload image to MediaManager (returns hash-based ID)
-    let image_hash = match upload_image_with_media_manager(&mut data.file, media_manager).await {
+    let image_hash = match upload_image_with_media_manager(&mut data.file, "card", config, media_manager).await {
         Ok(hash) => hash,
+        Err(err) if err.status().class().is_client_error() => return ApiResponseErr::api_err(err.status(), err.to_string()),
         Err(_) => return ApiResponseErr::api_err(Status::InternalServerError, String::from("Error uploading image"))
     };
 
@@ -0,0 +1,84 @@
+use std::path::{Path, PathBuf};
+use rocket::State;
+use rocket::http::{Accept, Status};
+
+use crate::sql::Sql;
+use crate::config::Config;
+use crate::media::{MediaManager, MediaResponse, MediaError, RenderContext};
+use crate::media::sql as media_sql;
+use crate::shared::Id;
+use crate::shared::card;
+
+//NOTE: rank 0 so this wins over the generic /media/<media_type>/<image_id> route (rank 1)
+#[get("/media/unlocked/<card_unlocked_id>", rank=0)]
+pub async fn card_image_unlocked_default_route(card_unlocked_id: Id, accept: Option<&Accept>, sql: &State<Sql>, config: &State<Config>, media_manager: &State<MediaManager>) -> Result<MediaResponse, MediaError> {
+    card_image_unlocked(card_unlocked_id, None, accept, sql, config, media_manager).await
+}
+
+//NOTE: rank 1 wins over the generic variant route (rank 2) but not over the
+//info route (rank 0), so "info" is never taken as a variant
+#[get("/media/unlocked/<card_unlocked_id>/<variant>", rank=1)]
+pub async fn card_image_unlocked_route(card_unlocked_id: Id, variant: String, accept: Option<&Accept>, sql: &State<Sql>, config: &State<Config>, media_manager: &State<MediaManager>) -> Result<MediaResponse, MediaError> {
+    card_image_unlocked(card_unlocked_id, Some(variant), accept, sql, config, media_manager).await
+}
+
+async fn card_image_unlocked(card_unlocked_id: Id, variant: Option<String>, accept: Option<&Accept>, sql: &State<Sql>, config: &State<Config>, media_manager: &State<MediaManager>) -> Result<MediaResponse, MediaError> {
+    let unlocked_card = match card::sql::get_unlocked_card(sql, &card_unlocked_id, None).await {
+        Ok(Some(card)) => card,
+        Ok(None) => return Err(Status::NotFound.into()),
+        Err(_) => return Err(Status::InternalServerError.into())
+    };
+
+    let card_id = &unlocked_card.card.card_info.id;
+
+    let image_hash = match card::sql::get_card_image(sql, card_id).await {
+        Ok(Some(hash)) => hash,
+        Ok(None) => String::from("card-image-default"),
+        Err(_) => return Err(Status::InternalServerError.into())
+    };
+
+    let focal_point = match media_sql::get_focal_point(sql, &image_hash).await {
+        Ok(focal_point) => focal_point,
+        Err(_) => return Err(Status::InternalServerError.into())
+    };
+
+    let frame_path = match &unlocked_card.card_frame {
+        Some(frame) => Path::new(&config.frame_fs_base).join(format!("Frame_{}_Front.png", frame.name)),
+        None => Path::new(&config.card_fs_base).join("card-frame-front-default")
+    };
+
+    let effect_path = unlocked_card.card_effect.as_ref()
+        .map(|effect| Path::new(&config.effect_fs_base).join(format!("Effect{}.png", effect.id)));
+
+    //NOTE: the card id covers the name, a card update creates a new card
+    //the opacity is in 1/1000 so float formatting doesn't end up in the cache key
+    let tag = format!("{}-f{}-e{}-o{}-l{}",
+        card_id,
+        unlocked_card.card_frame.as_ref().map_or(0, |frame| frame.id),
+        unlocked_card.card_effect.as_ref().map_or(0, |effect| effect.id),
+        unlocked_card.card_effect.as_ref().map_or(0, |effect| (effect.opacity * 1000.0).round() as i32),
+        unlocked_card.level);
+
+    let context = RenderContext::new(tag)
+        .with("frame", existing_path(frame_path))
+        .with("effect", effect_path.and_then(existing_path))
+        .with("effectOpacity", unlocked_card.card_effect.as_ref().map(|effect| effect.opacity))
+        .with("name", Some(unlocked_card.card.card_info.name.clone()))
+        .with_focal_point(focal_point);
+
+    let image = media_manager
+        .get_composite_image("unlocked", &image_hash, variant.as_deref(), &context, accept)
+        .await?;
+
+    //NOTE: upgrades change the image behind the same url, the etag covers frame, effect, opacity and level
+    Ok(MediaResponse::revalidate(image))
+}
+
+///missing layers are skipped instead of failing the whole image
+fn existing_path(path: PathBuf) -> Option<String> {
+    if path.is_file() {
+        path.to_str().map(String::from)
+    } else {
+        None
+    }
+}
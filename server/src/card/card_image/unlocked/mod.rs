@@ -0,0 +1,3 @@
+mod logic;
+
+pub use logic::{card_image_unlocked_route, card_image_unlocked_default_route};
@@ -1,6 +1,8 @@
 pub mod request;
 mod config;
 mod index;
+mod rarity;
 
 pub use config::card_type_config_route;
 pub use index::card_type_index_route;
+pub use rarity::card_type_rarity_route;
@@ -0,0 +1,38 @@
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+use rocket::State;
+use rocket::http::Status;
+
+use super::sql;
+use super::data::{CardTypeRarityRequest, CardTypeRarityResponse};
+use crate::shared::Id;
+use crate::shared::card;
+use crate::shared::rarity::{self, CardPoolCache};
+use crate::sql::Sql;
+use crate::{verify_collector_owner_moderator, verify_user};
+use crate::shared::crypto::JwtToken;
+
+#[post("/card-type/<card_type_id>/rarity", data="<data>")]
+pub async fn card_type_rarity_route(card_type_id: Id, data: CardTypeRarityRequest, sql: &State<Sql>, card_pool_cache: &State<CardPoolCache>, token: JwtToken) -> ApiResponseErr<CardTypeRarityResponse> {
+    let user_id = &token.id;
+
+    verify_user!(sql, user_id, true);
+
+    let collector_id = match card::sql::get_card_type_collector_id(sql, &card_type_id).await {
+        Ok(collector_id) => collector_id,
+        Err(sqlx::Error::RowNotFound) => return ApiResponseErr::api_err(Status::NotFound, String::from("Card type not found")),
+        Err(_) => return ApiResponseErr::api_err(Status::InternalServerError, String::from("Database Error"))
+    };
+    verify_collector_owner_moderator!(sql, &collector_id, user_id);
+
+    if let Some(ref rarity_id) = data.rarity_id {
+        if !rjtry!(rarity::sql::rarity_exists(sql, &collector_id, rarity_id).await) {
+            return ApiResponseErr::api_err(Status::NotFound, String::from("Rarity not found"));
+        }
+    }
+
+    rjtry!(sql::set_card_type_rarity(sql, &card_type_id, data.rarity_id.as_ref()).await);
+
+    card_pool_cache.invalidate(&collector_id);
+
+    ApiResponseErr::ok(Status::Ok, CardTypeRarityResponse { message: String::from("Card type rarity updated") })
+}
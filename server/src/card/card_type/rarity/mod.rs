@@ -0,0 +1,5 @@
+mod logic;
+mod sql;
+mod data;
+
+pub use logic::card_type_rarity_route;
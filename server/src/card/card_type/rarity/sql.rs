@@ -0,0 +1,15 @@
+use crate::sql::Sql;
+use crate::shared::Id;
+
+pub async fn set_card_type_rarity(sql: &Sql, card_type_id: &Id, rarity_id: Option<&Id>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE cardtypes
+         SET raid=?
+         WHERE ctid=?;")
+        .bind(rarity_id)
+        .bind(card_type_id)
+        .execute(sql.pool())
+        .await?;
+
+    Ok(())
+}
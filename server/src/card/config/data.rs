@@ -4,5 +4,35 @@ use serde::Serialize;
 #[serde(rename_all="camelCase")]
 pub struct CardConfigResponse {
     pub name_length_min: u32,
-    pub name_length_max: u32
+    pub name_length_max: u32,
+    ///Only set when requested for a collector
+    pub upgrade: Option<UpgradeConfig>
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct UpgradeConfig {
+    pub base_chance: u32,
+    pub quality_multiplier: u32,
+    pub level_decay: u32,
+    pub success_quality_min: i32,
+    pub success_quality_max: i32,
+    pub failure_quality_bonus: i32,
+    pub levels: Vec<UpgradeLevelChances>
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct UpgradeLevelChances {
+    pub level: i32,
+    pub chances: Vec<UpgradeChance>
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct UpgradeChance {
+    ///Sum of the qualities of both cards
+    pub quality_sum: i32,
+    ///Percent
+    pub chance: f32
 }
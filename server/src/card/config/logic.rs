@@ -1,14 +1,53 @@
-use rocketjson::ApiResponseErr;
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
 use rocket::State;
 use rocket::http::Status;
 
-use super::data::CardConfigResponse;
+use super::data::{CardConfigResponse, UpgradeConfig, UpgradeLevelChances, UpgradeChance};
 use crate::config::Config;
+use crate::sql::Sql;
+use crate::shared::Id;
+use crate::shared::card::upgrade::UpgradeRules;
+use crate::verify_collector;
+
+#[get("/card/config?<collector_id>")]
+pub async fn card_config_route(collector_id: Option<Id>, sql: &State<Sql>, config: &State<Config>) -> ApiResponseErr<CardConfigResponse> {
+    let upgrade = match collector_id {
+        Some(collector_id) => {
+            verify_collector!(sql, &collector_id);
+
+            let upgrade_rules = rjtry!(UpgradeRules::load(sql, &collector_id, config).await);
+            Some(upgrade_config(&upgrade_rules, config.upgrade_config_levels))
+        },
+        None => None
+    };
 
-#[get("/card/config")]
-pub async fn card_config_route(config: &State<Config>) -> ApiResponseErr<CardConfigResponse> {
     ApiResponseErr::ok(Status::Ok, CardConfigResponse {
         name_length_max: config.card_name_len_max,
         name_length_min: config.card_name_len_min,
+        upgrade
     })
 }
+
+fn upgrade_config(upgrade_rules: &UpgradeRules, levels: u32) -> UpgradeConfig {
+    let levels = (0..levels as i32)
+        .map(|level| UpgradeLevelChances {
+            level,
+            chances: (upgrade_rules.quality_min * 2..=upgrade_rules.quality_max * 2)
+                .map(|quality_sum| UpgradeChance {
+                    quality_sum,
+                    chance: upgrade_rules.chance(level, quality_sum, 0)
+                })
+                .collect()
+        })
+        .collect();
+
+    UpgradeConfig {
+        base_chance: upgrade_rules.base_chance,
+        quality_multiplier: upgrade_rules.quality_multiplier,
+        level_decay: upgrade_rules.level_decay,
+        success_quality_min: upgrade_rules.success_quality_min,
+        success_quality_max: upgrade_rules.success_quality_max,
+        failure_quality_bonus: upgrade_rules.failure_quality_bonus,
+        levels
+    }
+}
@@ -0,0 +1,25 @@
+use serde::{Serialize, Deserialize};
+use rocketjson::JsonBody;
+use validator::Validate;
+
+use crate::shared::Id;
+
+#[derive(Debug, Deserialize, Validate, JsonBody)]
+#[serde(rename_all="camelCase")]
+pub struct CraftRequest {
+    pub card_id: Id
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct CraftResponse {
+    pub card_unlocked_id: Id,
+    pub cost: u32,
+    pub balance: i32
+}
+
+pub enum CraftResult {
+    //balance after crafting
+    Crafted(i32),
+    InsufficientBalance(i32)
+}
@@ -0,0 +1,54 @@
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+use rocket::http::Status;
+use rocket::State;
+use rand::Rng;
+
+use super::data::{CraftRequest, CraftResponse, CraftResult};
+use super::sql;
+use crate::shared::crypto::JwtToken;
+use crate::sql::Sql;
+use crate::config::Config;
+use crate::shared::Id;
+use crate::shared::card::{self, data::{CardState, UnlockedCardCreateData}};
+use crate::shared::card::dust::DustRules;
+use crate::shared::collector::{get_collector_setting, CollectorSetting};
+use crate::verify_user;
+
+#[post("/card/craft", data="<data>")]
+pub async fn craft_route(sql: &State<Sql>, token: JwtToken, data: CraftRequest, config: &State<Config>) -> ApiResponseErr<CraftResponse> {
+    let user_id = token.id;
+
+    verify_user!(sql, &user_id, true);
+
+    let card = match rjtry!(card::sql::get_card(sql, None, &data.card_id).await) {
+        Some(card) if card.card_info.state == CardState::Created => card,
+        _ => return ApiResponseErr::api_err(Status::NotFound, format!("Card not found: {}", &data.card_id))
+    };
+
+    let collector_id = card.collector_id;
+    let dust_rules = rjtry!(DustRules::load(sql, &collector_id, config).await);
+
+    //crafted cards roll their quality like cards from a pack
+    let quality_min = rjtry!(get_collector_setting(sql, &collector_id, CollectorSetting::PackQualityMin, config.pack_quality_min).await);
+    let quality_max = rjtry!(get_collector_setting(sql, &collector_id, CollectorSetting::PackQualityMax, config.pack_quality_max).await);
+
+    let card_create_data = UnlockedCardCreateData {
+        card_id: data.card_id,
+        frame_id: None,
+        quality: rand::rng().random_range(quality_min.min(quality_max)..=quality_max.max(quality_min)),
+        level: 0
+    };
+
+    let card_unlocked_id = Id::new(config.id_length);
+    let balance = match rjtry!(sql::craft_card(sql, &user_id, &collector_id, &card_unlocked_id, &card_create_data, dust_rules.craft_cost).await) {
+        CraftResult::Crafted(balance) => balance,
+        CraftResult::InsufficientBalance(balance) =>
+            return ApiResponseErr::api_err(Status::Conflict, format!("Not enough dust: {} of {}", balance, dust_rules.craft_cost))
+    };
+
+    ApiResponseErr::ok(Status::Ok, CraftResponse {
+        card_unlocked_id,
+        cost: dust_rules.craft_cost,
+        balance
+    })
+}
@@ -0,0 +1,5 @@
+mod logic;
+mod data;
+mod sql;
+
+pub use logic::craft_route;
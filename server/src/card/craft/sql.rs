@@ -0,0 +1,24 @@
+use crate::sql::Sql;
+use crate::shared::{Id, card, market};
+use crate::shared::card::data::UnlockedCardCreateData;
+use super::data::CraftResult;
+
+//spends the dust and creates the card in one transaction
+pub async fn craft_card(sql: &Sql, user_id: &Id, collector_id: &Id, card_unlocked_id: &Id, card: &UnlockedCardCreateData, cost: u32) -> Result<CraftResult, sqlx::Error> {
+    market::sql::ensure_balance(sql, user_id, collector_id).await?;
+
+    let mut transaction = sql.pool().begin().await?;
+
+    let balance = market::sql::lock_balance(&mut transaction, user_id, collector_id).await?;
+
+    if (balance.bamount as i64) < cost as i64 {
+        return Ok(CraftResult::InsufficientBalance(balance.bamount));
+    }
+
+    market::sql::add_balance(&mut transaction, user_id, collector_id, -(cost as i32)).await?;
+    card::sql::add_card_transaction(&mut transaction, user_id, card_unlocked_id, collector_id, card).await?;
+
+    transaction.commit().await?;
+
+    Ok(CraftResult::Crafted(balance.bamount - cost as i32))
+}
@@ -0,0 +1,35 @@
+use serde::{Serialize, Deserialize};
+use rocketjson::JsonBody;
+use validator::{Validate, ValidationError, ValidateArgs};
+use std::borrow::Cow;
+
+use crate::shared::Id;
+use crate::config;
+
+#[derive(Debug, Deserialize, Validate, JsonBody)]
+#[serde(rename_all="camelCase")]
+#[validate(context = config::Config)]
+pub struct DismantleRequest {
+    #[validate(custom(function="validate_card_unlocked_ids", use_context))]
+    pub card_unlocked_ids: Vec<Id>
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct DismantleResponse {
+    pub collector_id: Id,
+    pub dust: u64,
+    pub balance: i32
+}
+
+fn validate_card_unlocked_ids(card_unlocked_ids: &[Id], config: &config::Config) -> Result<(), ValidationError> {
+	if card_unlocked_ids.is_empty() || card_unlocked_ids.len() > config.dismantle_card_limit as usize {
+        let mut err = ValidationError::new("Card amount not in valid range");
+        err.add_param(Cow::from("min"), &1);
+        err.add_param(Cow::from("max"), &config.dismantle_card_limit);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
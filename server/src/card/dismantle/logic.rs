@@ -0,0 +1,74 @@
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+use rocket::http::Status;
+use rocket::State;
+use std::collections::HashSet;
+
+use super::data::{DismantleRequest, DismantleResponse};
+use super::sql;
+use crate::shared::crypto::JwtToken;
+use crate::sql::Sql;
+use crate::config::Config;
+use crate::shared::Id;
+use crate::shared::{card, market};
+use crate::shared::card::dust::DustRules;
+use crate::verify_user;
+
+#[post("/card/dismantle", data="<data>")]
+pub async fn dismantle_route(sql: &State<Sql>, token: JwtToken, data: DismantleRequest, config: &State<Config>) -> ApiResponseErr<DismantleResponse> {
+    let user_id = token.id;
+
+    verify_user!(sql, &user_id, true);
+
+    if data.card_unlocked_ids.iter().collect::<HashSet<_>>().len() != data.card_unlocked_ids.len() {
+        return ApiResponseErr::api_err(Status::BadRequest, String::from("Cards can only be dismantled once"));
+    }
+
+    let cards = rjtry!(card::sql::get_unlocked_cards(sql, data.card_unlocked_ids.clone(), Some(&user_id)).await);
+    if let Some(card_unlocked_id) = data.card_unlocked_ids.iter().find(|id| !cards.iter().any(|card| &card.id == *id)) {
+        return ApiResponseErr::api_err(Status::NotFound, format!("Card not found: {}", card_unlocked_id));
+    }
+
+    let collector_id: Id = cards[0].card.collector_id.clone();
+    if let Some(card) = cards.iter().find(|card| card.card.collector_id != collector_id) {
+        return ApiResponseErr::api_err(Status::BadRequest, format!("Cards are not from the same collector: {} {}", cards[0].id, card.id));
+    }
+
+    let dust_rules = rjtry!(DustRules::load(sql, &collector_id, config).await);
+    let dust: u64 = cards
+        .iter()
+        .map(|card| dust_rules.dismantle_value(card.level, card.quality))
+        .fold(0, u64::saturating_add);
+
+    //the balance needs a row to lock
+    rjtry!(market::sql::ensure_balance(sql, &user_id, &collector_id).await);
+
+    let mut transaction = rjtry!(sql.pool().begin().await);
+
+    if !rjtry!(sql::lock_dismantle_cards(&mut transaction, &user_id, &data.card_unlocked_ids).await) {
+        return ApiResponseErr::api_err(Status::Conflict, String::from("Card was already used"));
+    }
+
+    if rjtry!(sql::dismantle_cards_locked(&mut transaction, &data.card_unlocked_ids).await) {
+        return ApiResponseErr::api_err(Status::Conflict, String::from("Card is in a trade or listed on the market"));
+    }
+
+    //checked before anything is changed, the balance column is a signed INT
+    let balance = rjtry!(market::sql::lock_balance(&mut transaction, &user_id, &collector_id).await);
+    let amount = match i32::try_from(dust).ok().filter(|amount| balance.bamount.checked_add(*amount).is_some()) {
+        Some(amount) => amount,
+        None => return ApiResponseErr::api_err(Status::Conflict, format!("Dismantling would exceed the maximum balance of {}", i32::MAX))
+    };
+
+    rjtry!(sql::delete_dismantle_cards(&mut transaction, &data.card_unlocked_ids).await);
+    rjtry!(market::sql::add_balance(&mut transaction, &user_id, &collector_id, amount).await);
+
+    rjtry!(transaction.commit().await);
+
+    let balance = rjtry!(market::sql::get_balance(sql, &user_id, &collector_id).await);
+
+    ApiResponseErr::ok(Status::Ok, DismantleResponse {
+        collector_id,
+        dust,
+        balance: balance.bamount
+    })
+}
@@ -0,0 +1,65 @@
+use sqlx::MySqlConnection;
+
+use crate::shared::Id;
+use crate::shared::market::data::ListingStatus;
+
+fn placeholders(amount: usize) -> String {
+    vec!["?"; amount].join(",")
+}
+
+///Locks the cards until the transaction ends, returns false if one of them
+///does not belong to the user anymore
+pub async fn lock_dismantle_cards(transaction: &mut MySqlConnection, user_id: &Id, card_unlocked_ids: &[Id]) -> Result<bool, sqlx::Error> {
+    let query = format!(
+        "SELECT cuid
+         FROM cardunlocks
+         WHERE uid=?
+         AND cuid IN ({})
+         FOR UPDATE;", placeholders(card_unlocked_ids.len()));
+
+    let mut stmt = sqlx::query_as(&query).bind(user_id);
+    for card_unlocked_id in card_unlocked_ids {
+        stmt = stmt.bind(card_unlocked_id);
+    }
+
+    let cards: Vec<(Id, )> = stmt.fetch_all(transaction).await?;
+
+    Ok(cards.len() == card_unlocked_ids.len())
+}
+
+///Has to be called after `lock_dismantle_cards`
+pub async fn dismantle_cards_locked(transaction: &mut MySqlConnection, card_unlocked_ids: &[Id]) -> Result<bool, sqlx::Error> {
+    let query = format!(
+        "SELECT
+         (SELECT COUNT(*) FROM tradecards WHERE cuid IN ({0})) +
+         (SELECT COUNT(*) FROM marketlistings WHERE mlstatus=? AND cuid IN ({0}));", placeholders(card_unlocked_ids.len()));
+
+    let mut stmt = sqlx::query_as(&query);
+    for card_unlocked_id in card_unlocked_ids {
+        stmt = stmt.bind(card_unlocked_id);
+    }
+    stmt = stmt.bind(ListingStatus::Open as i32);
+    for card_unlocked_id in card_unlocked_ids {
+        stmt = stmt.bind(card_unlocked_id);
+    }
+
+    let (count, ): (i64, ) = stmt.fetch_one(transaction).await?;
+
+    Ok(count != 0)
+}
+
+//closed market listings of the cards are kept, their cuid is set to NULL
+pub async fn delete_dismantle_cards(transaction: &mut MySqlConnection, card_unlocked_ids: &[Id]) -> Result<(), sqlx::Error> {
+    let query = format!(
+        "DELETE FROM cardunlocks
+         WHERE cuid IN ({});", placeholders(card_unlocked_ids.len()));
+
+    let mut stmt = sqlx::query(&query);
+    for card_unlocked_id in card_unlocked_ids {
+        stmt = stmt.bind(card_unlocked_id);
+    }
+
+    stmt.execute(transaction).await?;
+
+    Ok(())
+}
@@ -1,5 +1,7 @@
 pub mod unlocked;
 pub mod upgrade;
+pub mod dismantle;
+pub mod craft;
 pub mod card_image;
 pub mod card_type;
 pub mod request;
@@ -7,3 +9,4 @@ pub mod config;
 pub mod index;
 pub mod frame;
 pub mod get;
+pub mod rarity;
@@ -0,0 +1,18 @@
+use rocketjson::JsonBody;
+use validator::Validate;
+use serde::{Serialize, Deserialize};
+
+use crate::shared::Id;
+
+#[derive(Debug, Deserialize, Validate, JsonBody)]
+#[serde(rename_all = "camelCase")]
+pub struct CardRarityRequest {
+    /// None removes the rarity
+    pub rarity_id: Option<Id>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CardRarityResponse {
+    pub message: String
+}
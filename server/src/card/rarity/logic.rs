@@ -0,0 +1,38 @@
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+use rocket::State;
+use rocket::http::Status;
+
+use super::sql;
+use super::data::{CardRarityRequest, CardRarityResponse};
+use crate::shared::Id;
+use crate::shared::card;
+use crate::shared::rarity::{self, CardPoolCache};
+use crate::sql::Sql;
+use crate::{verify_collector_owner_moderator, verify_user};
+use crate::shared::crypto::JwtToken;
+
+#[post("/card/<card_id>/rarity", data="<data>")]
+pub async fn card_rarity_route(card_id: Id, data: CardRarityRequest, sql: &State<Sql>, card_pool_cache: &State<CardPoolCache>, token: JwtToken) -> ApiResponseErr<CardRarityResponse> {
+    let user_id = &token.id;
+
+    verify_user!(sql, user_id, true);
+
+    if !rjtry!(card::sql::card_exists(sql, &card_id).await) {
+        return ApiResponseErr::api_err(Status::NotFound, String::from("Card not found"));
+    }
+
+    let collector_id = rjtry!(card::sql::get_card_collector_id(sql, &card_id).await);
+    verify_collector_owner_moderator!(sql, &collector_id, user_id);
+
+    if let Some(ref rarity_id) = data.rarity_id {
+        if !rjtry!(rarity::sql::rarity_exists(sql, &collector_id, rarity_id).await) {
+            return ApiResponseErr::api_err(Status::NotFound, String::from("Rarity not found"));
+        }
+    }
+
+    rjtry!(sql::set_card_rarity(sql, &card_id, data.rarity_id.as_ref()).await);
+
+    card_pool_cache.invalidate(&collector_id);
+
+    ApiResponseErr::ok(Status::Ok, CardRarityResponse { message: String::from("Card rarity updated") })
+}
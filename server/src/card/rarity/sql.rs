@@ -0,0 +1,15 @@
+use crate::sql::Sql;
+use crate::shared::Id;
+
+pub async fn set_card_rarity(sql: &Sql, card_id: &Id, rarity_id: Option<&Id>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE cards
+         SET raid=?
+         WHERE cid=?;")
+        .bind(rarity_id)
+        .bind(card_id)
+        .execute(sql.pool())
+        .await?;
+
+    Ok(())
+}
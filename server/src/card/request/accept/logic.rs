@@ -8,13 +8,14 @@ use super::sql;
 use super::data::CardRequestAcceptResponse;
 use crate::shared::Id;
 use crate::shared::card;
+use crate::shared::rarity::CardPoolCache;
 use crate::sql::Sql;
 use crate::config::Config;
 use crate::{verify_collector_owner_moderator, verify_user};
 use crate::shared::crypto::JwtToken;
 
 #[post("/card/request/<card_id>/accept")]
-pub async fn card_request_accept_route(card_id: Id, sql: &State<Sql>, token: JwtToken, config: &State<Config>) -> ApiResponseErr<CardRequestAcceptResponse> {
+pub async fn card_request_accept_route(card_id: Id, sql: &State<Sql>, token: JwtToken, config: &State<Config>, card_pool_cache: &State<CardPoolCache>) -> ApiResponseErr<CardRequestAcceptResponse> {
     let user_id = &token.id;
 
     verify_user!(sql, user_id, true);
@@ -25,6 +26,7 @@ pub async fn card_request_accept_route(card_id: Id, sql: &State<Sql>, token: Jwt
             verify_collector_owner_moderator!(sql, &collector_id, user_id);
 
             rjtry!(sql::card_delete_request_accept(sql, &card_id, &delete_card_id).await);
+            card_pool_cache.invalidate(&collector_id);
         },
         None => {
             let collector_id = rjtry!(card::sql::get_card_collector_id(sql, &card_id).await);
@@ -56,6 +58,7 @@ pub async fn card_request_accept_route(card_id: Id, sql: &State<Sql>, token: Jwt
                 }
                 None => rjtry!(sql::card_request_accept(sql, &card_id).await),
             }
+            card_pool_cache.invalidate(&collector_id);
         }
     }
 
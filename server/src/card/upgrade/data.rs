@@ -20,6 +20,8 @@ pub struct UpgradeRequest {
 
 pub struct UpgradeCardsResult {
     pub success: bool,
+    ///Percent
+    pub chance: f32,
     pub create_card_data: UnlockedCardCreateData
 }
 
@@ -4,11 +4,13 @@ use rocket::State;
 use rand::Rng;
 
 use super::data::{UpgradeResponse, UpgradeRequest, UpgradeCardsResult};
+use super::sql;
 use crate::shared::crypto::JwtToken;
 use crate::sql::Sql;
 use crate::shared::card::{self, data::{UnlockedCard, UnlockedCardCreateData, CardFrame}};
 use crate::config::Config;
-use crate::shared::{Id, collector, collector::CollectorSetting};
+use crate::shared::Id;
+use crate::shared::card::upgrade::UpgradeRules;
 use crate::verify_user;
 
 #[post("/card/upgrade", data="<data>")]
@@ -45,16 +47,32 @@ pub async fn upgrade_route(sql: &State<Sql>, token: JwtToken, data: UpgradeReque
                                            );
     }
 
-    let pack_quality_min = rjtry!(collector::get_collector_setting(sql, &card_one.card.collector_id, CollectorSetting::PackQualityMin, config.pack_quality_min).await);
-    let pack_quality_max = rjtry!(collector::get_collector_setting(sql, &card_one.card.collector_id, CollectorSetting::PackQualityMax, config.pack_quality_max).await);
+    let upgrade_rules = rjtry!(UpgradeRules::load(sql, &card_one.card.collector_id, config).await);
 
-    let UpgradeCardsResult { create_card_data: new_card_data, success } = upgrade_cards(&card_one, &card_two, pack_quality_min, pack_quality_max);
+    let UpgradeCardsResult { create_card_data: new_card_data, success, chance } = upgrade_cards(&card_one, &card_two, &upgrade_rules);
+
+    let mut transaction = rjtry!(sql.pool().begin().await);
+
+    if !rjtry!(sql::lock_upgrade_cards(&mut transaction, &user_id, &card_one.id, &card_two.id).await) {
+        return ApiResponseErr::api_err(Status::Conflict, format!("Card was already used: {} {}", card_one.id, card_two.id));
+    }
+
+    if rjtry!(sql::upgrade_cards_in_trade(&mut transaction, &card_one.id, &card_two.id).await) {
+        return ApiResponseErr::api_err(Status::Conflict, format!("Card is in a trade: {} {}", card_one.id, card_two.id));
+    }
+
+    if rjtry!(sql::upgrade_cards_listed(&mut transaction, &card_one.id, &card_two.id).await) {
+        return ApiResponseErr::api_err(Status::Conflict, format!("Card is listed on the market: {} {}", card_one.id, card_two.id));
+    }
 
     let new_card_uuid = Id::new(config.id_length);
-    rjtry!(card::sql::add_card(sql, &user_id, &new_card_uuid, &card_one.card.collector_id, &new_card_data).await);
+    rjtry!(card::sql::add_card_transaction(&mut transaction, &user_id, &new_card_uuid, &card_one.card.collector_id, &new_card_data).await);
+
+    rjtry!(sql::delete_upgrade_cards(&mut transaction, &card_one.id, &card_two.id).await);
+
+    rjtry!(sql::add_upgrade_log(&mut transaction, &user_id, &card_one, &card_two, chance, success, &new_card_uuid, &new_card_data).await);
 
-    rjtry!(card::sql::delete_card(sql, &card_one.id).await);
-    rjtry!(card::sql::delete_card(sql, &card_two.id).await);
+    rjtry!(transaction.commit().await);
 
     ApiResponseErr::ok(Status::Ok, UpgradeResponse {
         success,
@@ -62,14 +80,12 @@ pub async fn upgrade_route(sql: &State<Sql>, token: JwtToken, data: UpgradeReque
     })
 }
 
-fn upgrade_cards(card_one: &UnlockedCard, card_two: &UnlockedCard, pack_quality_min: i32, pack_quality_max: i32) -> UpgradeCardsResult {
-    let upgrade_chance = ((card_one.quality + card_two.quality) * 10) as f32;
+fn upgrade_cards(card_one: &UnlockedCard, card_two: &UnlockedCard, upgrade_rules: &UpgradeRules) -> UpgradeCardsResult {
+    let upgrade_chance = upgrade_rules.chance(card_one.level, card_one.quality, card_two.quality);
 
     let mut rng = rand::rng();
 
-    let success = rng.random_range(0f32..=100f32) <= upgrade_chance;
-
-    //TODO: log success
+    let success = rng.random_range(0f32..100f32) < upgrade_chance;
 
     let new_level: i32;
     let new_quality: i32;
@@ -77,13 +93,11 @@ fn upgrade_cards(card_one: &UnlockedCard, card_two: &UnlockedCard, pack_quality_
     match success {
          true => {
             new_level = card_one.level + 1;
-			new_quality = rng.random_range(pack_quality_min..=pack_quality_max);
+            new_quality = upgrade_rules.success_quality(&mut rng);
          },
          false => {
             new_level = card_one.level;
-		    new_quality = (((card_one.quality as f32 + card_two.quality as f32) / 2f32)
-                            .round() as i32 + 1)
-                            .clamp(pack_quality_min, pack_quality_max);
+            new_quality = upgrade_rules.failure_quality(card_one.quality, card_two.quality);
          }
     }
 
@@ -100,6 +114,7 @@ fn upgrade_cards(card_one: &UnlockedCard, card_two: &UnlockedCard, pack_quality_
 
     UpgradeCardsResult {
         create_card_data,
-        success
+        success,
+        chance: upgrade_chance
     }
 }
@@ -1,4 +1,5 @@
 mod logic;
 mod data;
+mod sql;
 
 pub use logic::upgrade_route;
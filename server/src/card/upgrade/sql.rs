@@ -0,0 +1,89 @@
+use sqlx::MySqlConnection;
+
+use crate::shared::Id;
+use crate::shared::card::data::{UnlockedCard, UnlockedCardCreateData};
+use crate::shared::market::data::ListingStatus;
+
+///Locks both cards until the transaction ends, returns false if one of them
+///does not belong to the user anymore, e.g. because a parallel upgrade consumed it
+pub async fn lock_upgrade_cards(transaction: &mut MySqlConnection, user_id: &Id, card_one: &Id, card_two: &Id) -> Result<bool, sqlx::Error> {
+    let cards: Vec<(Id, )> = sqlx::query_as(
+        "SELECT cuid
+         FROM cardunlocks
+         WHERE uid=?
+         AND cuid IN (?, ?)
+         FOR UPDATE;")
+        .bind(user_id)
+        .bind(card_one)
+        .bind(card_two)
+        .fetch_all(transaction)
+        .await?;
+
+    Ok(cards.len() == 2)
+}
+
+///Has to be called after `lock_upgrade_cards`, adding a card to a trade needs the
+///lock on the card so this can not change until the transaction ends
+pub async fn upgrade_cards_in_trade(transaction: &mut MySqlConnection, card_one: &Id, card_two: &Id) -> Result<bool, sqlx::Error> {
+    let (count, ): (i64, ) = sqlx::query_as(
+        "SELECT COUNT(*)
+         FROM tradecards
+         WHERE cuid IN (?, ?);")
+        .bind(card_one)
+        .bind(card_two)
+        .fetch_one(transaction)
+        .await?;
+
+    Ok(count != 0)
+}
+
+///Has to be called after `lock_upgrade_cards`, listing a card locks it as well
+pub async fn upgrade_cards_listed(transaction: &mut MySqlConnection, card_one: &Id, card_two: &Id) -> Result<bool, sqlx::Error> {
+    let (count, ): (i64, ) = sqlx::query_as(
+        "SELECT COUNT(*)
+         FROM marketlistings
+         WHERE cuid IN (?, ?) AND mlstatus=?;")
+        .bind(card_one)
+        .bind(card_two)
+        .bind(ListingStatus::Open as i32)
+        .fetch_one(transaction)
+        .await?;
+
+    Ok(count != 0)
+}
+
+pub async fn delete_upgrade_cards(transaction: &mut MySqlConnection, card_one: &Id, card_two: &Id) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM cardunlocks
+         WHERE cuid IN (?, ?);")
+        .bind(card_one)
+        .bind(card_two)
+        .execute(transaction)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn add_upgrade_log(transaction: &mut MySqlConnection, user_id: &Id, card_one: &UnlockedCard, card_two: &UnlockedCard, chance: f32, success: bool, result_id: &Id, result: &UnlockedCardCreateData) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO upgradelog
+         (uid, cid, ulcardone, ulcardtwo, ullevel, ulqualityone, ulqualitytwo, ulchance, ulsuccess, ulresult, ulresultlevel, ulresultquality)
+         VALUES
+         (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);")
+        .bind(user_id)
+        .bind(&card_one.card.card_info.id)
+        .bind(&card_one.id)
+        .bind(&card_two.id)
+        .bind(card_one.level)
+        .bind(card_one.quality)
+        .bind(card_two.quality)
+        .bind(chance)
+        .bind(success as i32)
+        .bind(result_id)
+        .bind(result.level)
+        .bind(result.quality)
+        .execute(transaction)
+        .await?;
+
+    Ok(())
+}
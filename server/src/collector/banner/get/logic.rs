@@ -1,8 +1,8 @@
 use rocket::State;
-use rocket::http::{Status, ContentType};
+use rocket::http::Status;
 
 use crate::sql::Sql;
-use crate::media::MediaManager;
+use crate::media::{MediaManager, MediaResponse, MediaError};
 use crate::shared::Id;
 use crate::shared::collector::sql as collector_sql;
 
@@ -11,12 +11,12 @@ pub async fn collector_banner_get_route(
     collector_id: Id,
     sql: &State<Sql>,
     media_manager: &State<MediaManager>
-) -> Result<(ContentType, Vec<u8>), Status> {
+) -> Result<MediaResponse, MediaError> {
     // Check if collector exists
     match collector_sql::collector_exists(sql, &collector_id).await {
         Ok(true) => (),
-        Ok(false) => return Err(Status::NotFound),
-        Err(_) => return Err(Status::InternalServerError)
+        Ok(false) => return Err(Status::NotFound.into()),
+        Err(_) => return Err(Status::InternalServerError.into())
     }
 
     // Get banner hash from database
@@ -25,18 +25,14 @@ pub async fn collector_banner_get_route(
         Ok(None) => {
             String::from("collector-banner-default")
         },
-        Err(_) => return Err(Status::InternalServerError)
+        Err(_) => return Err(Status::InternalServerError.into())
     };
 
     // Get banner through MediaManager with "banner" media type
-    let (bytes, format) = media_manager
+    let image = media_manager
         .get_image("banner", &banner_hash, None)
-        .await
-        .map_err(|_| Status::NotFound)?;
+        .await?;
 
-    // Parse Content-Type based on format
-    let content_type = ContentType::parse_flexible(format.mime_type())
-        .unwrap_or(ContentType::Binary);
-
-    Ok((content_type, bytes))
+    // The URL stays the same when the image changes, clients revalidate with the ETag
+    Ok(MediaResponse::revalidate(image))
 }
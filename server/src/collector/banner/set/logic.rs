@@ -4,6 +4,7 @@ use rocket::State;
 use rocket::form::Form;
 
 use crate::sql::Sql;
+use crate::config::Config;
 use crate::media::MediaManager;
 use crate::shared::Id;
 use crate::shared::crypto::JwtToken;
@@ -17,6 +18,7 @@ pub async fn collector_banner_set_route(
     collector_id: Id,
     mut data: Form<CollectorBannerSetRequest<'_>>,
     sql: &State<Sql>,
+    config: &State<Config>,
     media_manager: &State<MediaManager>,
     token: JwtToken
 ) -> ApiResponseErr<CollectorBannerSetResponse> {
@@ -26,8 +28,9 @@ pub async fn collector_banner_set_route(
     verify_collector_owner_moderator!(sql, &collector_id, &user_id);
 
     // Upload banner to MediaManager (returns hash-based ID)
-    let banner_hash = match upload_image_with_media_manager(&mut data.file, media_manager).await {
+    let banner_hash = match upload_image_with_media_manager(&mut data.file, "banner", config, media_manager).await {
         Ok(hash) => hash,
+        Err(err) if err.status().class().is_client_error() => return ApiResponseErr::api_err(err.status(), err.to_string()),
         Err(_) => return ApiResponseErr::api_err(Status::InternalServerError, String::from("Error uploading banner"))
     };
 
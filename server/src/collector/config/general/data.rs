@@ -10,6 +10,28 @@ pub struct CollectorConfigResponse {
     pub pack_amount: FieldRange,
     pub pack_quality_min: FieldRange,
     pub pack_quality_max: FieldRange,
+    pub pack_level_one_chance: FieldRange,
+    pub pack_level_two_chance: FieldRange,
+    pub pack_pity_threshold: FieldRange,
+    pub pack_pity_level: FieldRange,
+    pub pack_pity_rarity_weight: FieldRange,
+    pub upgrade_base_chance: FieldRange,
+    pub upgrade_quality_multiplier: FieldRange,
+    pub upgrade_level_decay: FieldRange,
+    pub upgrade_success_quality_min: FieldRange,
+    pub upgrade_success_quality_max: FieldRange,
+    pub upgrade_failure_quality_bonus: FieldRange,
+    pub market_daily_reward: FieldRange,
+    pub market_price: FieldRange,
+    pub market_listing_limit: u32,
+    pub dismantle_base_dust: FieldRange,
+    pub dismantle_level_dust: FieldRange,
+    pub dismantle_quality_dust: FieldRange,
+    pub dismantle_card_limit: u32,
+    pub craft_cost: FieldRange,
+    pub rarity_name: FieldRange,
+    pub rarity_weight: FieldRange,
+    pub rarity_limit: u32,
 }
 
 #[derive(Debug, Serialize)]
@@ -33,5 +33,84 @@ pub async fn get_collector_general_config_route(config: &State<Config>) -> ApiRe
             min: config.pack_quality_max_min,
             max: config.pack_quality_max_max,
         },
+        pack_level_one_chance: FieldRange {
+            min: config.pack_level_one_chance_min as i32,
+            max: config.pack_level_one_chance_max as i32,
+        },
+        pack_level_two_chance: FieldRange {
+            min: config.pack_level_two_chance_min as i32,
+            max: config.pack_level_two_chance_max as i32,
+        },
+        pack_pity_threshold: FieldRange {
+            min: config.pack_pity_threshold_min as i32,
+            max: config.pack_pity_threshold_max as i32,
+        },
+        pack_pity_level: FieldRange {
+            min: config.pack_pity_level_min as i32,
+            max: config.pack_pity_level_max as i32,
+        },
+        pack_pity_rarity_weight: FieldRange {
+            min: config.pack_pity_rarity_weight_min as i32,
+            max: config.pack_pity_rarity_weight_max as i32,
+        },
+        upgrade_base_chance: FieldRange {
+            min: config.upgrade_base_chance_min as i32,
+            max: config.upgrade_base_chance_max as i32,
+        },
+        upgrade_quality_multiplier: FieldRange {
+            min: config.upgrade_quality_multiplier_min as i32,
+            max: config.upgrade_quality_multiplier_max as i32,
+        },
+        upgrade_level_decay: FieldRange {
+            min: config.upgrade_level_decay_min as i32,
+            max: config.upgrade_level_decay_max as i32,
+        },
+        upgrade_success_quality_min: FieldRange {
+            min: config.upgrade_success_quality_min_min,
+            max: config.upgrade_success_quality_max_max,
+        },
+        upgrade_success_quality_max: FieldRange {
+            min: config.upgrade_success_quality_min_min,
+            max: config.upgrade_success_quality_max_max,
+        },
+        upgrade_failure_quality_bonus: FieldRange {
+            min: config.upgrade_failure_quality_bonus_min,
+            max: config.upgrade_failure_quality_bonus_max,
+        },
+        market_daily_reward: FieldRange {
+            min: config.market_daily_reward_min as i32,
+            max: config.market_daily_reward_max as i32,
+        },
+        market_price: FieldRange {
+            min: config.market_price_min as i32,
+            max: config.market_price_max as i32,
+        },
+        market_listing_limit: config.market_listing_limit,
+        dismantle_base_dust: FieldRange {
+            min: config.dismantle_base_dust_min as i32,
+            max: config.dismantle_base_dust_max as i32,
+        },
+        dismantle_level_dust: FieldRange {
+            min: config.dismantle_level_dust_min as i32,
+            max: config.dismantle_level_dust_max as i32,
+        },
+        dismantle_quality_dust: FieldRange {
+            min: config.dismantle_quality_dust_min as i32,
+            max: config.dismantle_quality_dust_max as i32,
+        },
+        dismantle_card_limit: config.dismantle_card_limit,
+        craft_cost: FieldRange {
+            min: config.craft_cost_min as i32,
+            max: config.craft_cost_max as i32,
+        },
+        rarity_name: FieldRange {
+            min: config.rarity_name_len_min as i32,
+            max: config.rarity_name_len_max as i32,
+        },
+        rarity_weight: FieldRange {
+            min: config.rarity_weight_min as i32,
+            max: config.rarity_weight_max as i32,
+        },
+        rarity_limit: config.collector_rarity_limit,
     })
 }
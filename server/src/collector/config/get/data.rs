@@ -6,5 +6,21 @@ pub struct CollectorConfigResponse {
     pub pack_cooldown: u32,
     pub pack_amount: u32,
     pub pack_quality_min: i32,
-    pub pack_quality_max: i32
+    pub pack_quality_max: i32,
+    pub pack_level_one_chance: u32,
+    pub pack_level_two_chance: u32,
+    pub pack_pity_threshold: u32,
+    pub pack_pity_level: u32,
+    pub pack_pity_rarity_weight: u32,
+    pub upgrade_base_chance: u32,
+    pub upgrade_quality_multiplier: u32,
+    pub upgrade_level_decay: u32,
+    pub upgrade_success_quality_min: i32,
+    pub upgrade_success_quality_max: i32,
+    pub upgrade_failure_quality_bonus: i32,
+    pub market_daily_reward: u32,
+    pub dismantle_base_dust: u32,
+    pub dismantle_level_dust: u32,
+    pub dismantle_quality_dust: u32,
+    pub craft_cost: u32
 }
@@ -16,11 +16,43 @@ pub async fn get_collector_config_route(collector_id: Id, sql: &State<Sql>, conf
     let pack_cooldown = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::PackCooldown, config.pack_cooldown).await);
     let pack_quality_min = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::PackQualityMin, config.pack_quality_min).await);
     let pack_quality_max = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::PackQualityMax, config.pack_quality_max).await);
+    let pack_level_one_chance = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::PackLevelOneChance, config.pack_level_one_chance).await);
+    let pack_level_two_chance = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::PackLevelTwoChance, config.pack_level_two_chance).await);
+    let pack_pity_threshold = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::PackPityThreshold, config.pack_pity_threshold).await);
+    let pack_pity_level = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::PackPityLevel, config.pack_pity_level).await);
+    let pack_pity_rarity_weight = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::PackPityRarityWeight, config.pack_pity_rarity_weight).await);
+    let upgrade_base_chance = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::UpgradeBaseChance, config.upgrade_base_chance).await);
+    let upgrade_quality_multiplier = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::UpgradeQualityMultiplier, config.upgrade_quality_multiplier).await);
+    let upgrade_level_decay = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::UpgradeLevelDecay, config.upgrade_level_decay).await);
+    let upgrade_failure_quality_bonus = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::UpgradeFailureQualityBonus, config.upgrade_failure_quality_bonus).await);
+    let upgrade_success_quality_min = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::UpgradeSuccessQualityMin, pack_quality_min).await);
+    let upgrade_success_quality_max = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::UpgradeSuccessQualityMax, pack_quality_max).await);
+    let market_daily_reward = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::MarketDailyReward, config.market_daily_reward).await);
+    let dismantle_base_dust = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::DismantleBaseDust, config.dismantle_base_dust).await);
+    let dismantle_level_dust = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::DismantleLevelDust, config.dismantle_level_dust).await);
+    let dismantle_quality_dust = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::DismantleQualityDust, config.dismantle_quality_dust).await);
+    let craft_cost = rjtry!(collector::get_collector_setting(sql, &collector_id, CollectorSetting::CraftCost, config.craft_cost).await);
 
     ApiResponseErr::ok(Status::Ok, CollectorConfigResponse {
         pack_amount,
         pack_cooldown,
         pack_quality_min,
-        pack_quality_max ,
+        pack_quality_max,
+        pack_level_one_chance,
+        pack_level_two_chance,
+        pack_pity_threshold,
+        pack_pity_level,
+        pack_pity_rarity_weight,
+        upgrade_base_chance,
+        upgrade_quality_multiplier,
+        upgrade_level_decay,
+        upgrade_success_quality_min,
+        upgrade_success_quality_max,
+        upgrade_failure_quality_bonus,
+        market_daily_reward,
+        dismantle_base_dust,
+        dismantle_level_dust,
+        dismantle_quality_dust,
+        craft_cost,
     })
 }
@@ -21,7 +21,39 @@ pub struct CollectorConfigRequest {
     #[validate(custom(function="validate_pack_quality_min", use_context))]
     pub pack_quality_min: Option<i32>,
     #[validate(custom(function="validate_pack_quality_max", use_context))]
-    pub pack_quality_max: Option<i32>
+    pub pack_quality_max: Option<i32>,
+    #[validate(custom(function="validate_pack_level_one_chance", use_context))]
+    pub pack_level_one_chance: Option<u32>,
+    #[validate(custom(function="validate_pack_level_two_chance", use_context))]
+    pub pack_level_two_chance: Option<u32>,
+    #[validate(custom(function="validate_pack_pity_threshold", use_context))]
+    pub pack_pity_threshold: Option<u32>,
+    #[validate(custom(function="validate_pack_pity_level", use_context))]
+    pub pack_pity_level: Option<u32>,
+    #[validate(custom(function="validate_pack_pity_rarity_weight", use_context))]
+    pub pack_pity_rarity_weight: Option<u32>,
+    #[validate(custom(function="validate_upgrade_base_chance", use_context))]
+    pub upgrade_base_chance: Option<u32>,
+    #[validate(custom(function="validate_upgrade_quality_multiplier", use_context))]
+    pub upgrade_quality_multiplier: Option<u32>,
+    #[validate(custom(function="validate_upgrade_level_decay", use_context))]
+    pub upgrade_level_decay: Option<u32>,
+    #[validate(custom(function="validate_upgrade_success_quality_min", use_context))]
+    pub upgrade_success_quality_min: Option<i32>,
+    #[validate(custom(function="validate_upgrade_success_quality_max", use_context))]
+    pub upgrade_success_quality_max: Option<i32>,
+    #[validate(custom(function="validate_upgrade_failure_quality_bonus", use_context))]
+    pub upgrade_failure_quality_bonus: Option<i32>,
+    #[validate(custom(function="validate_market_daily_reward", use_context))]
+    pub market_daily_reward: Option<u32>,
+    #[validate(custom(function="validate_dismantle_base_dust", use_context))]
+    pub dismantle_base_dust: Option<u32>,
+    #[validate(custom(function="validate_dismantle_level_dust", use_context))]
+    pub dismantle_level_dust: Option<u32>,
+    #[validate(custom(function="validate_dismantle_quality_dust", use_context))]
+    pub dismantle_quality_dust: Option<u32>,
+    #[validate(custom(function="validate_craft_cost", use_context))]
+    pub craft_cost: Option<u32>
 }
 
 fn validate_pack_cooldown(pack_cooldown: u32, config: &config::Config) -> Result<(), ValidationError> {
@@ -71,3 +103,195 @@ fn validate_pack_quality_max(pack_quality_max: i32, config: &config::Config) ->
 
     Ok(())
 }
+
+fn validate_pack_level_one_chance(pack_level_one_chance: u32, config: &config::Config) -> Result<(), ValidationError> {
+	if pack_level_one_chance < config.pack_level_one_chance_min || pack_level_one_chance > config.pack_level_one_chance_max {
+        let mut err = ValidationError::new("Pack level one chance not in valid range");
+        err.add_param(Cow::from("min"), &config.pack_level_one_chance_min);
+        err.add_param(Cow::from("max"), &config.pack_level_one_chance_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn validate_pack_level_two_chance(pack_level_two_chance: u32, config: &config::Config) -> Result<(), ValidationError> {
+	if pack_level_two_chance < config.pack_level_two_chance_min || pack_level_two_chance > config.pack_level_two_chance_max {
+        let mut err = ValidationError::new("Pack level two chance not in valid range");
+        err.add_param(Cow::from("min"), &config.pack_level_two_chance_min);
+        err.add_param(Cow::from("max"), &config.pack_level_two_chance_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn validate_pack_pity_threshold(pack_pity_threshold: u32, config: &config::Config) -> Result<(), ValidationError> {
+	if pack_pity_threshold < config.pack_pity_threshold_min || pack_pity_threshold > config.pack_pity_threshold_max {
+        let mut err = ValidationError::new("Pack pity threshold not in valid range");
+        err.add_param(Cow::from("min"), &config.pack_pity_threshold_min);
+        err.add_param(Cow::from("max"), &config.pack_pity_threshold_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn validate_pack_pity_level(pack_pity_level: u32, config: &config::Config) -> Result<(), ValidationError> {
+	if pack_pity_level < config.pack_pity_level_min || pack_pity_level > config.pack_pity_level_max {
+        let mut err = ValidationError::new("Pack pity level not in valid range");
+        err.add_param(Cow::from("min"), &config.pack_pity_level_min);
+        err.add_param(Cow::from("max"), &config.pack_pity_level_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn validate_pack_pity_rarity_weight(pack_pity_rarity_weight: u32, config: &config::Config) -> Result<(), ValidationError> {
+	if pack_pity_rarity_weight < config.pack_pity_rarity_weight_min || pack_pity_rarity_weight > config.pack_pity_rarity_weight_max {
+        let mut err = ValidationError::new("Pack pity rarity weight not in valid range");
+        err.add_param(Cow::from("min"), &config.pack_pity_rarity_weight_min);
+        err.add_param(Cow::from("max"), &config.pack_pity_rarity_weight_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn validate_upgrade_base_chance(upgrade_base_chance: u32, config: &config::Config) -> Result<(), ValidationError> {
+	if upgrade_base_chance < config.upgrade_base_chance_min || upgrade_base_chance > config.upgrade_base_chance_max {
+        let mut err = ValidationError::new("Upgrade base chance not in valid range");
+        err.add_param(Cow::from("min"), &config.upgrade_base_chance_min);
+        err.add_param(Cow::from("max"), &config.upgrade_base_chance_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn validate_upgrade_quality_multiplier(upgrade_quality_multiplier: u32, config: &config::Config) -> Result<(), ValidationError> {
+	if upgrade_quality_multiplier < config.upgrade_quality_multiplier_min || upgrade_quality_multiplier > config.upgrade_quality_multiplier_max {
+        let mut err = ValidationError::new("Upgrade quality multiplier not in valid range");
+        err.add_param(Cow::from("min"), &config.upgrade_quality_multiplier_min);
+        err.add_param(Cow::from("max"), &config.upgrade_quality_multiplier_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn validate_upgrade_level_decay(upgrade_level_decay: u32, config: &config::Config) -> Result<(), ValidationError> {
+	if upgrade_level_decay < config.upgrade_level_decay_min || upgrade_level_decay > config.upgrade_level_decay_max {
+        let mut err = ValidationError::new("Upgrade level decay not in valid range");
+        err.add_param(Cow::from("min"), &config.upgrade_level_decay_min);
+        err.add_param(Cow::from("max"), &config.upgrade_level_decay_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn validate_upgrade_success_quality_min(upgrade_success_quality_min: i32, config: &config::Config) -> Result<(), ValidationError> {
+	if upgrade_success_quality_min < config.upgrade_success_quality_min_min || upgrade_success_quality_min > config.upgrade_success_quality_max_max {
+        let mut err = ValidationError::new("Upgrade min success quality not in valid range");
+        err.add_param(Cow::from("min"), &config.upgrade_success_quality_min_min);
+        err.add_param(Cow::from("max"), &config.upgrade_success_quality_max_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn validate_upgrade_success_quality_max(upgrade_success_quality_max: i32, config: &config::Config) -> Result<(), ValidationError> {
+	if upgrade_success_quality_max < config.upgrade_success_quality_min_min || upgrade_success_quality_max > config.upgrade_success_quality_max_max {
+        let mut err = ValidationError::new("Upgrade max success quality not in valid range");
+        err.add_param(Cow::from("min"), &config.upgrade_success_quality_min_min);
+        err.add_param(Cow::from("max"), &config.upgrade_success_quality_max_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn validate_upgrade_failure_quality_bonus(upgrade_failure_quality_bonus: i32, config: &config::Config) -> Result<(), ValidationError> {
+	if upgrade_failure_quality_bonus < config.upgrade_failure_quality_bonus_min || upgrade_failure_quality_bonus > config.upgrade_failure_quality_bonus_max {
+        let mut err = ValidationError::new("Upgrade failure quality bonus not in valid range");
+        err.add_param(Cow::from("min"), &config.upgrade_failure_quality_bonus_min);
+        err.add_param(Cow::from("max"), &config.upgrade_failure_quality_bonus_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn validate_market_daily_reward(market_daily_reward: u32, config: &config::Config) -> Result<(), ValidationError> {
+	if market_daily_reward < config.market_daily_reward_min || market_daily_reward > config.market_daily_reward_max {
+        let mut err = ValidationError::new("Market daily reward not in valid range");
+        err.add_param(Cow::from("min"), &config.market_daily_reward_min);
+        err.add_param(Cow::from("max"), &config.market_daily_reward_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn validate_dismantle_base_dust(dismantle_base_dust: u32, config: &config::Config) -> Result<(), ValidationError> {
+	if dismantle_base_dust < config.dismantle_base_dust_min || dismantle_base_dust > config.dismantle_base_dust_max {
+        let mut err = ValidationError::new("Dismantle base dust not in valid range");
+        err.add_param(Cow::from("min"), &config.dismantle_base_dust_min);
+        err.add_param(Cow::from("max"), &config.dismantle_base_dust_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn validate_dismantle_level_dust(dismantle_level_dust: u32, config: &config::Config) -> Result<(), ValidationError> {
+	if dismantle_level_dust < config.dismantle_level_dust_min || dismantle_level_dust > config.dismantle_level_dust_max {
+        let mut err = ValidationError::new("Dismantle level dust not in valid range");
+        err.add_param(Cow::from("min"), &config.dismantle_level_dust_min);
+        err.add_param(Cow::from("max"), &config.dismantle_level_dust_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn validate_dismantle_quality_dust(dismantle_quality_dust: u32, config: &config::Config) -> Result<(), ValidationError> {
+	if dismantle_quality_dust < config.dismantle_quality_dust_min || dismantle_quality_dust > config.dismantle_quality_dust_max {
+        let mut err = ValidationError::new("Dismantle quality dust not in valid range");
+        err.add_param(Cow::from("min"), &config.dismantle_quality_dust_min);
+        err.add_param(Cow::from("max"), &config.dismantle_quality_dust_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn validate_craft_cost(craft_cost: u32, config: &config::Config) -> Result<(), ValidationError> {
+	if craft_cost < config.craft_cost_min || craft_cost > config.craft_cost_max {
+        let mut err = ValidationError::new("Craft cost not in valid range");
+        err.add_param(Cow::from("min"), &config.craft_cost_min);
+        err.add_param(Cow::from("max"), &config.craft_cost_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
@@ -29,6 +29,54 @@ pub async fn set_collector_config_route(collector_id: Id, data: CollectorConfigR
     if let Some(pack_quality_max) = data.pack_quality_max {
         rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::PackQualityMax, &pack_quality_max.to_string()).await);
     }
+    if let Some(pack_level_one_chance) = data.pack_level_one_chance {
+        rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::PackLevelOneChance, &pack_level_one_chance.to_string()).await);
+    }
+    if let Some(pack_level_two_chance) = data.pack_level_two_chance {
+        rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::PackLevelTwoChance, &pack_level_two_chance.to_string()).await);
+    }
+    if let Some(pack_pity_threshold) = data.pack_pity_threshold {
+        rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::PackPityThreshold, &pack_pity_threshold.to_string()).await);
+    }
+    if let Some(pack_pity_level) = data.pack_pity_level {
+        rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::PackPityLevel, &pack_pity_level.to_string()).await);
+    }
+    if let Some(pack_pity_rarity_weight) = data.pack_pity_rarity_weight {
+        rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::PackPityRarityWeight, &pack_pity_rarity_weight.to_string()).await);
+    }
+    if let Some(upgrade_base_chance) = data.upgrade_base_chance {
+        rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::UpgradeBaseChance, &upgrade_base_chance.to_string()).await);
+    }
+    if let Some(upgrade_quality_multiplier) = data.upgrade_quality_multiplier {
+        rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::UpgradeQualityMultiplier, &upgrade_quality_multiplier.to_string()).await);
+    }
+    if let Some(upgrade_level_decay) = data.upgrade_level_decay {
+        rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::UpgradeLevelDecay, &upgrade_level_decay.to_string()).await);
+    }
+    if let Some(upgrade_success_quality_min) = data.upgrade_success_quality_min {
+        rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::UpgradeSuccessQualityMin, &upgrade_success_quality_min.to_string()).await);
+    }
+    if let Some(upgrade_success_quality_max) = data.upgrade_success_quality_max {
+        rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::UpgradeSuccessQualityMax, &upgrade_success_quality_max.to_string()).await);
+    }
+    if let Some(upgrade_failure_quality_bonus) = data.upgrade_failure_quality_bonus {
+        rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::UpgradeFailureQualityBonus, &upgrade_failure_quality_bonus.to_string()).await);
+    }
+    if let Some(market_daily_reward) = data.market_daily_reward {
+        rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::MarketDailyReward, &market_daily_reward.to_string()).await);
+    }
+    if let Some(dismantle_base_dust) = data.dismantle_base_dust {
+        rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::DismantleBaseDust, &dismantle_base_dust.to_string()).await);
+    }
+    if let Some(dismantle_level_dust) = data.dismantle_level_dust {
+        rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::DismantleLevelDust, &dismantle_level_dust.to_string()).await);
+    }
+    if let Some(dismantle_quality_dust) = data.dismantle_quality_dust {
+        rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::DismantleQualityDust, &dismantle_quality_dust.to_string()).await);
+    }
+    if let Some(craft_cost) = data.craft_cost {
+        rjtry!(sql::set_collector_setting(sql, &collector_id, CollectorSetting::CraftCost, &craft_cost.to_string()).await);
+    }
 
     ApiResponseErr::ok(Status::Ok, CollectorConfigResponse {
         message: String::from("Updated collector config")
@@ -7,3 +7,4 @@ pub mod get;
 pub mod banner;
 pub mod update;
 pub mod moderator;
+pub mod rarity;
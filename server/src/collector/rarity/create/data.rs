@@ -0,0 +1,51 @@
+use rocketjson::JsonBody;
+use validator::{Validate, ValidationError, ValidateArgs};
+use serde::{Serialize, Deserialize};
+use std::borrow::Cow;
+use regex::Regex;
+
+use crate::shared::Id;
+use crate::config;
+
+#[derive(Debug, Deserialize, Validate, JsonBody)]
+#[validate(context = config::Config)]
+pub struct CollectorRarityCreateRequest {
+    #[validate(custom(function="validate_rarity_name", use_context))]
+    pub name: String,
+    #[validate(custom(function="validate_rarity_weight", use_context))]
+    pub weight: u32
+}
+
+#[derive(Debug, Serialize)]
+pub struct CollectorRarityCreateResponse {
+    pub id: Id
+}
+
+fn validate_rarity_name(name: &str, config: &config::Config) -> Result<(), ValidationError> {
+	if name.len() < config.rarity_name_len_min as usize || name.len() > config.rarity_name_len_max as usize {
+        let mut err = ValidationError::new("rarity name does not fit the length constraints");
+        err.add_param(Cow::from("min"), &config.rarity_name_len_min);
+        err.add_param(Cow::from("max"), &config.rarity_name_len_max);
+
+        return Err(err);
+    }
+    let re = Regex::new("^[a-zA-Z0-9_]+( [a-zA-Z0-9_]+)*$").unwrap();
+
+    if !re.is_match(name) {
+        return Err(ValidationError::new("rarity can only contain letters, numbers, _ and whitespaces in between words"));
+    }
+
+    Ok(())
+}
+
+fn validate_rarity_weight(weight: u32, config: &config::Config) -> Result<(), ValidationError> {
+	if weight < config.rarity_weight_min || weight > config.rarity_weight_max {
+        let mut err = ValidationError::new("Rarity weight not in valid range");
+        err.add_param(Cow::from("min"), &config.rarity_weight_min);
+        err.add_param(Cow::from("max"), &config.rarity_weight_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
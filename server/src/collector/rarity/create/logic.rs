@@ -0,0 +1,32 @@
+use rocket::{State, http::Status};
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+
+use crate::sql::Sql;
+use crate::shared::Id;
+use crate::shared::crypto::JwtToken;
+use crate::shared::rarity::CardPoolCache;
+use crate::{verify_collector, verify_user, verify_collector_owner_moderator};
+use crate::config::Config;
+
+use super::sql;
+use super::data::{CollectorRarityCreateRequest, CollectorRarityCreateResponse};
+
+#[post("/collector/<collector_id>/rarity/create", data="<data>")]
+pub async fn collector_rarity_create_route(token: JwtToken, sql: &State<Sql>, config: &State<Config>, card_pool_cache: &State<CardPoolCache>, collector_id: Id, data: CollectorRarityCreateRequest) -> ApiResponseErr<CollectorRarityCreateResponse> {
+    verify_collector!(sql, &collector_id);
+    verify_user!(sql, &token.id, true);
+    verify_collector_owner_moderator!(sql, &collector_id, &token.id);
+
+    if rjtry!(sql::rarity_count(sql, &collector_id).await) >= config.collector_rarity_limit as i64 {
+        return ApiResponseErr::api_err(Status::Conflict, String::from("Rarity limit reached"));
+    }
+
+    let rarity_id = Id::new(config.id_length);
+    rjtry!(sql::create_rarity(sql, &rarity_id, &collector_id, &data.name, data.weight).await);
+
+    card_pool_cache.invalidate(&collector_id);
+
+    ApiResponseErr::ok(Status::Ok, CollectorRarityCreateResponse {
+        id: rarity_id
+    })
+}
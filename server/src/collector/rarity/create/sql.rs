@@ -0,0 +1,30 @@
+use crate::sql::Sql;
+use crate::shared::Id;
+
+pub async fn create_rarity(sql: &Sql, rarity_id: &Id, collector_id: &Id, name: &str, weight: u32) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO rarities
+         (raid, coid, raname, raweight)
+         VALUES
+         (?, ?, ?, ?);")
+        .bind(rarity_id)
+        .bind(collector_id)
+        .bind(name)
+        .bind(weight)
+        .execute(sql.pool())
+        .await?;
+
+    Ok(())
+}
+
+pub async fn rarity_count(sql: &Sql, collector_id: &Id) -> Result<i64, sqlx::Error> {
+    let (count, ): (i64, ) = sqlx::query_as(
+        "SELECT COUNT(*)
+         FROM rarities
+         WHERE coid=?;")
+        .bind(collector_id)
+        .fetch_one(sql.pool())
+        .await?;
+
+    Ok(count)
+}
@@ -0,0 +1,17 @@
+use rocketjson::JsonBody;
+use validator::Validate;
+use serde::{Serialize, Deserialize};
+
+use crate::shared::Id;
+
+#[derive(Debug, Deserialize, Validate, JsonBody)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectorRarityDeleteRequest {
+    pub rarity_id: Id,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectorRarityDeleteResponse {
+    pub message: String
+}
@@ -0,0 +1,31 @@
+use rocket::{State, http::Status};
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+
+use crate::sql::Sql;
+use crate::shared::Id;
+use crate::shared::crypto::JwtToken;
+use crate::shared::rarity::{self, CardPoolCache};
+use crate::{verify_collector, verify_user, verify_collector_owner_moderator};
+
+use super::sql;
+use super::data::{CollectorRarityDeleteRequest, CollectorRarityDeleteResponse};
+
+#[post("/collector/<collector_id>/rarity/delete", data="<data>")]
+pub async fn collector_rarity_delete_route(token: JwtToken, sql: &State<Sql>, card_pool_cache: &State<CardPoolCache>, collector_id: Id, data: CollectorRarityDeleteRequest) -> ApiResponseErr<CollectorRarityDeleteResponse> {
+    verify_collector!(sql, &collector_id);
+    verify_user!(sql, &token.id, true);
+    verify_collector_owner_moderator!(sql, &collector_id, &token.id);
+
+    if !rjtry!(rarity::sql::rarity_exists(sql, &collector_id, &data.rarity_id).await) {
+        return ApiResponseErr::api_err(Status::NotFound, String::from("Rarity not found"));
+    }
+
+    //cards and card types fall back to the default weight
+    rjtry!(sql::delete_rarity(sql, &collector_id, &data.rarity_id).await);
+
+    card_pool_cache.invalidate(&collector_id);
+
+    ApiResponseErr::ok(Status::Ok, CollectorRarityDeleteResponse {
+        message: String::from("Rarity deleted")
+    })
+}
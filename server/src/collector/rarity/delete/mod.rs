@@ -0,0 +1,5 @@
+mod logic;
+mod sql;
+mod data;
+
+pub use logic::collector_rarity_delete_route;
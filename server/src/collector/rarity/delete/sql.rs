@@ -0,0 +1,15 @@
+use crate::sql::Sql;
+use crate::shared::Id;
+
+pub async fn delete_rarity(sql: &Sql, collector_id: &Id, rarity_id: &Id) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM rarities
+         WHERE coid=?
+         AND raid=?;")
+        .bind(collector_id)
+        .bind(rarity_id)
+        .execute(sql.pool())
+        .await?;
+
+    Ok(())
+}
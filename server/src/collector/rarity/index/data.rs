@@ -0,0 +1,9 @@
+use serde::Serialize;
+
+use crate::shared::rarity::Rarity;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectorRarityIndexResponse {
+    pub rarities: Vec<Rarity>,
+}
@@ -0,0 +1,20 @@
+use rocket::{State, http::Status};
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+
+use crate::sql::Sql;
+use crate::shared::Id;
+use crate::shared::rarity;
+use crate::verify_collector;
+
+use super::data::CollectorRarityIndexResponse;
+
+#[get("/collector/<collector_id>/rarity")]
+pub async fn collector_rarity_index_route(sql: &State<Sql>, collector_id: Id) -> ApiResponseErr<CollectorRarityIndexResponse> {
+    verify_collector!(sql, &collector_id);
+
+    let rarities = rjtry!(rarity::sql::get_rarities(sql, &collector_id).await);
+
+    ApiResponseErr::ok(Status::Ok, CollectorRarityIndexResponse {
+        rarities
+    })
+}
@@ -0,0 +1,4 @@
+mod logic;
+mod data;
+
+pub use logic::collector_rarity_index_route;
@@ -1,4 +1,8 @@
 use figment::{Figment, providers::{Format, Json, Serialized}};
+use rocket::data::{Limits, ToByteUnit};
+
+use crate::migration::MigrationMode;
+use crate::media::storage::StorageBackend;
 
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
 pub struct Config {
@@ -47,6 +51,8 @@ pub struct Config {
     pub flex_cards_amount: u32,
     pub card_type_page_amount: u32,
     pub card_page_amount: u32,
+    pub trade_history_page_amount: u32,
+    pub market_page_amount: u32,
 
     pub max_friends: u32,
     pub max_trades: u32,
@@ -65,10 +71,89 @@ pub struct Config {
     pub pack_quality_max: i32,
     pub pack_quality_max_min: i32,
     pub pack_quality_max_max: i32,
+    //per mille
+    pub pack_level_one_chance: u32,
+    pub pack_level_one_chance_min: u32,
+    pub pack_level_one_chance_max: u32,
+    pub pack_level_two_chance: u32,
+    pub pack_level_two_chance_min: u32,
+    pub pack_level_two_chance_max: u32,
+    //packs without a pity card until one is guaranteed, 0 (default) disables pity
+    pub pack_pity_threshold: u32,
+    pub pack_pity_threshold_min: u32,
+    pub pack_pity_threshold_max: u32,
+    //cards with at least this level count for pity, 0 disables the level check
+    pub pack_pity_level: u32,
+    pub pack_pity_level_min: u32,
+    pub pack_pity_level_max: u32,
+    //cards with a rarity weight of at most this count for pity, 0 disables the rarity check
+    pub pack_pity_rarity_weight: u32,
+    pub pack_pity_rarity_weight_min: u32,
+    pub pack_pity_rarity_weight_max: u32,
+    //seconds
+    pub pack_card_pool_ttl: u32,
+    //seconds a pack stays retrievable with its idempotency key
+    pub pack_idempotency_key_duration: u32,
+
+    pub rarity_name_len_min: u32,
+    pub rarity_name_len_max: u32,
+    pub rarity_weight_min: u32,
+    pub rarity_weight_max: u32,
+    //weight of cards without a rarity
+    pub rarity_default_weight: u32,
+    pub collector_rarity_limit: u32,
+
+    //upgrade chance in percent: base + (quality one + quality two) * multiplier - level * decay
+    pub upgrade_base_chance: u32,
+    pub upgrade_base_chance_min: u32,
+    pub upgrade_base_chance_max: u32,
+    pub upgrade_quality_multiplier: u32,
+    pub upgrade_quality_multiplier_min: u32,
+    pub upgrade_quality_multiplier_max: u32,
+    pub upgrade_level_decay: u32,
+    pub upgrade_level_decay_min: u32,
+    pub upgrade_level_decay_max: u32,
+    //bounds of the success quality settings, they default to the pack quality range
+    pub upgrade_success_quality_min_min: i32,
+    pub upgrade_success_quality_max_max: i32,
+    pub upgrade_failure_quality_bonus: i32,
+    pub upgrade_failure_quality_bonus_min: i32,
+    pub upgrade_failure_quality_bonus_max: i32,
+    //levels listed in the upgrade chances of the card config
+    pub upgrade_config_levels: u32,
 
     //seconds
     pub trade_cooldown: u32,
     pub trade_card_limit: u32,
+    //seconds until an open trade offer expires
+    pub trade_offer_duration: u32,
+
+    //currency granted once per cooldown
+    pub market_daily_reward: u32,
+    pub market_daily_reward_min: u32,
+    pub market_daily_reward_max: u32,
+    //seconds
+    pub market_daily_cooldown: u32,
+    pub market_price_min: u32,
+    pub market_price_max: u32,
+    //open listings per user and collector
+    pub market_listing_limit: u32,
+
+    //dust for dismantling a card: base + level * level dust + quality * quality dust
+    pub dismantle_base_dust: u32,
+    pub dismantle_base_dust_min: u32,
+    pub dismantle_base_dust_max: u32,
+    pub dismantle_level_dust: u32,
+    pub dismantle_level_dust_min: u32,
+    pub dismantle_level_dust_max: u32,
+    pub dismantle_quality_dust: u32,
+    pub dismantle_quality_dust_min: u32,
+    pub dismantle_quality_dust_max: u32,
+    //cards per dismantle request
+    pub dismantle_card_limit: u32,
+    pub craft_cost: u32,
+    pub craft_cost_min: u32,
+    pub craft_cost_max: u32,
 
     //seconds
     pub pack_data_span: u32,
@@ -85,11 +170,41 @@ pub struct Config {
     pub user_fs_base: String,
     pub collector_fs_base: String,
     pub card_fs_base: String,
+    pub frame_fs_base: String,
+    pub effect_fs_base: String,
 
     // Media Manager paths
     pub media_types_dir: String,
     pub media_cache_dir: String,
     pub media_storage_dir: String,
+    /// Where originals are stored, media_storage_dir is used by "filesystem"
+    pub media_storage_backend: StorageBackend,
+    /// Endpoint of the "s3" backend, e.g. http://localhost:9000 for MinIO
+    pub media_s3_endpoint: String,
+    pub media_s3_region: String,
+    pub media_s3_bucket: String,
+    pub media_s3_access_key: String,
+    pub media_s3_secret_key: String,
+    /// Prepended to all object keys
+    pub media_s3_prefix: String,
+    /// Byte budget of the in-memory cache tier, 0 disables it
+    pub media_memory_cache_bytes: u32,
+    /// Size budget of the file system cache, 0 for no limit
+    pub media_cache_max_bytes: u64,
+    /// Seconds between background GC runs, 0 disables them
+    pub media_gc_interval: u32,
+    /// Seconds before an unreferenced file counts as orphaned
+    pub media_gc_grace: u32,
+    /// Background runs only report what they would remove
+    pub media_gc_dry_run: bool,
+    /// Concurrent variant generations, 0 for the number of CPUs
+    pub media_generation_concurrency: u32,
+    /// Generations waiting for a slot before requests get 503
+    pub media_generation_queue: u32,
+    /// Rocket's `limits.file` and `limits.data-form` must allow at least this much
+    pub image_upload_max_bytes: u32,
+    /// Width times height, protects against decompression bombs
+    pub image_upload_max_pixels: u32,
 
     pub log_file: String,
 
@@ -97,7 +212,8 @@ pub struct Config {
     pub email_password: String,
     pub smtp_server: String,
 
-    pub db_init_files: Vec<String>
+    pub db_migrations_dir: String,
+    pub db_migration_mode: MigrationMode
 }
 
 impl Default for Config {
@@ -150,6 +266,8 @@ impl Default for Config {
             flex_cards_amount: 9,
             card_type_page_amount: 10,
             card_page_amount: 10,
+            trade_history_page_amount: 10,
+            market_page_amount: 20,
 
             max_friends: 999,
             max_trades: 5,
@@ -166,9 +284,72 @@ impl Default for Config {
             pack_quality_max: 5,
             pack_quality_max_min: 5,
             pack_quality_max_max: 10,
+            pack_level_one_chance: 50,
+            pack_level_one_chance_min: 0,
+            pack_level_one_chance_max: 1000,
+            pack_level_two_chance: 5,
+            pack_level_two_chance_min: 0,
+            pack_level_two_chance_max: 1000,
+            pack_pity_threshold: 0,
+            pack_pity_threshold_min: 0,
+            pack_pity_threshold_max: 1000,
+            pack_pity_level: 1,
+            pack_pity_level_min: 0,
+            pack_pity_level_max: 2,
+            pack_pity_rarity_weight: 0,
+            pack_pity_rarity_weight_min: 0,
+            pack_pity_rarity_weight_max: 1000000,
+            pack_card_pool_ttl: 60,
+            pack_idempotency_key_duration: 60 * 60 * 24,
+
+            rarity_name_len_min: 1,
+            rarity_name_len_max: 20,
+            rarity_weight_min: 1,
+            rarity_weight_max: 1000000,
+            rarity_default_weight: 100,
+            collector_rarity_limit: 20,
+
+            upgrade_base_chance: 0,
+            upgrade_base_chance_min: 0,
+            upgrade_base_chance_max: 100,
+            upgrade_quality_multiplier: 10,
+            upgrade_quality_multiplier_min: 0,
+            upgrade_quality_multiplier_max: 100,
+            upgrade_level_decay: 0,
+            upgrade_level_decay_min: 0,
+            upgrade_level_decay_max: 100,
+            upgrade_success_quality_min_min: -5,
+            upgrade_success_quality_max_max: 10,
+            upgrade_failure_quality_bonus: 1,
+            upgrade_failure_quality_bonus_min: -10,
+            upgrade_failure_quality_bonus_max: 10,
+            upgrade_config_levels: 5,
 
             trade_cooldown: 60,
             trade_card_limit: 5,
+            trade_offer_duration: 259200,
+
+            market_daily_reward: 100,
+            market_daily_reward_min: 0,
+            market_daily_reward_max: 100000,
+            market_daily_cooldown: 60 * 60 * 24,
+            market_price_min: 1,
+            market_price_max: 1000000,
+            market_listing_limit: 20,
+
+            dismantle_base_dust: 5,
+            dismantle_base_dust_min: 0,
+            dismantle_base_dust_max: 10000,
+            dismantle_level_dust: 20,
+            dismantle_level_dust_min: 0,
+            dismantle_level_dust_max: 10000,
+            dismantle_quality_dust: 2,
+            dismantle_quality_dust_min: 0,
+            dismantle_quality_dust_max: 10000,
+            dismantle_card_limit: 50,
+            craft_cost: 100,
+            craft_cost_min: 1,
+            craft_cost_max: 1000000,
 
             pack_data_span: 60,
             pack_data_amount: 30,
@@ -184,11 +365,29 @@ impl Default for Config {
             user_fs_base: String::from("static/user"),
             collector_fs_base: String::from("static/collector"),
             card_fs_base: String::from("static/card"),
+            frame_fs_base: String::from("static/frame"),
+            effect_fs_base: String::from("static/effect"),
 
             // Media Manager defaults
             media_types_dir: String::from("media-types"),
             media_cache_dir: String::from("media/cache"),
             media_storage_dir: String::from("media/originals"),
+            media_storage_backend: StorageBackend::Filesystem,
+            media_s3_endpoint: String::new(),
+            media_s3_region: String::from("us-east-1"),
+            media_s3_bucket: String::new(),
+            media_s3_access_key: String::new(),
+            media_s3_secret_key: String::new(),
+            media_s3_prefix: String::from("originals/"),
+            media_memory_cache_bytes: 64 * 1024 * 1024,
+            media_cache_max_bytes: 4 * 1024 * 1024 * 1024,
+            media_gc_interval: 60 * 60,
+            media_gc_grace: 24 * 60 * 60,
+            media_gc_dry_run: false,
+            media_generation_concurrency: 0,
+            media_generation_queue: 64,
+            image_upload_max_bytes: 10 * 1024 * 1024,
+            image_upload_max_pixels: 40_000_000,
 
             log_file: String::from("./log-file.log"),
 
@@ -196,21 +395,20 @@ impl Default for Config {
             email_password: String::from("EMAIL_PASSWORD"),
             smtp_server: String::from("smtp.gmail.com"),
 
-            db_init_files: vec![
-                String::from("./sqlfiles/tables.sql"),
-                String::from("./sqlfiles/add_image_hashes.sql"),
-                /* String::from("./sqlfiles/cardtypes.sql"),
-                String::from("./sqlfiles/cards.sql"),
-                String::from("./sqlfiles/cardframes.sql"),
-                String::from("./sqlfiles/cardeffects.sql"),
-                String::from("./sqlfiles/achievements.sql") */
-            ]
+            db_migrations_dir: String::from("./sqlfiles/migrations"),
+            db_migration_mode: MigrationMode::Apply
         }
     }
 }
 
 pub fn get_figment() -> Result<Figment, figment::Error> {
+    //NOTE: rocket rejects uploads over its limits (1MiB per file by default) before image_upload_max_bytes is checked
+    let limits = Limits::default()
+        .limit("file", 16.mebibytes())
+        .limit("data-form", 16.mebibytes());
+
     Ok(Figment::from(rocket::Config::default())
+        .merge(Serialized::default("limits", limits))
         .merge(Serialized::defaults(Config::default()))
         .merge(Json::file("Config.json")))
 }
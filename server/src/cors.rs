@@ -21,7 +21,7 @@ pub struct CORS;
             ));
             response.set_header(Header::new(
                 "Access-Control-Allow-Headers",
-                "content-type, authorization",
+                "content-type, authorization, idempotency-key",
             ));
         }
 
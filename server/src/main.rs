@@ -22,10 +22,12 @@ mod admission;
 mod pack;
 mod friend;
 mod trade;
+mod market;
 mod admin;
 mod collector;
 mod scripts;
 mod media;
+mod migration;
 
 #[get("/")]
 fn index() -> &'static str {
@@ -39,6 +41,20 @@ async fn rocket() -> _ {
 
     let config: config::Config = config_figment.extract().expect("Initializing config failed");
 
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(command) = media::command::MediaCommand::from_args(&args) {
+        let result = match command {
+            Ok(command) => command.run(&config).await,
+            Err(usage) => Err(usage)
+        };
+
+        if let Err(err) = result {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
+
     println!("Connecting to database...");
     let sql = sql::Sql(MySqlPoolOptions::new()
         .max_connections(5)
@@ -46,16 +62,39 @@ async fn rocket() -> _ {
         .await.expect("Creating DB pool failed"));
 
     //TODO: paths not relative to start path
+    println!("Loading migrations...");
+    let migrations = migration::load_migrations(&config.db_migrations_dir).expect("Failed loading migrations");
+    let migration_runner = migration::MigrationRunner::new(migrations);
+
+    if let Some(command) = migration::MigrateCommand::from_args(&args) {
+        let result = match command {
+            Ok(command) => command.run(&sql, &migration_runner).await.map_err(|e| e.to_string()),
+            Err(usage) => Err(usage)
+        };
+
+        if let Err(err) = result {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
+
     println!("Setting up database...");
-    for file in config.db_init_files.iter() {
-        println!("-{}", file);
-        sql::setup_db(&sql, file).await.expect("Failed setting up database");
+    match config.db_migration_mode {
+        migration::MigrationMode::Apply => {
+            for version in migration_runner.apply(&sql).await.expect("Failed migrating database") {
+                println!("-applied migration {}", version);
+            }
+        },
+        migration::MigrationMode::Verify => {
+            migration_runner.verify(&sql).await.expect("Database schema is not up to date");
+        }
     }
 
     // Initialize Media Manager
     println!("Initializing Media Manager...");
     use std::sync::Arc;
-    use media::{EffectRegistry, FilesystemCache, ImageStorage, MediaManager};
+    use media::{EffectRegistry, FilesystemCache, ImageCache, MediaManager, MemoryCache, TieredCache, MediaGc, GcOptions};
 
     let effect_registry = EffectRegistry::new();
     println!("- Registered {} effects", effect_registry.effect_ids().len());
@@ -64,14 +103,39 @@ async fn rocket() -> _ {
         .expect("Failed to load media type configurations");
     println!("- Loaded {} media types", media_types.len());
 
-    let cache = Arc::new(FilesystemCache::new(&config.media_cache_dir));
-    let storage = Arc::new(ImageStorage::new(&config.media_storage_dir));
+    let filesystem_cache = Arc::new(FilesystemCache::new(&config.media_cache_dir));
+    let cache: Arc<dyn ImageCache> = if config.media_memory_cache_bytes > 0 {
+        println!("- Memory cache tier with {} bytes", config.media_memory_cache_bytes);
+        let memory_cache = MemoryCache::new(config.media_memory_cache_bytes as usize);
+        Arc::new(TieredCache::new(memory_cache, filesystem_cache.clone()))
+    } else {
+        filesystem_cache.clone()
+    };
+    let storage = media::storage::open_store(config.media_storage_backend, &config)
+        .expect("Failed to create image storage");
+    println!("- Originals stored in {:?} backend", config.media_storage_backend);
 
     storage.init().await.expect("Failed to initialize image storage");
 
-    let media_manager = MediaManager::new(effect_registry, media_types, cache, storage);
+    let media_gc = Arc::new(MediaGc::new(filesystem_cache, storage.clone(), GcOptions {
+        max_cache_bytes: config.media_cache_max_bytes,
+        grace: std::time::Duration::from_secs(config.media_gc_grace as u64),
+    }));
+
+    if config.media_gc_interval > 0 {
+        println!("- Media GC every {} seconds{}", config.media_gc_interval, if config.media_gc_dry_run { " (dry run)" } else { "" });
+        media::gc::spawn(media_gc.clone(), sql.clone(), std::time::Duration::from_secs(config.media_gc_interval as u64), config.media_gc_dry_run);
+    }
+
+    let media_manager = MediaManager::new(effect_registry, media_types, cache, storage)
+        .with_generation_limits(config.media_generation_concurrency as usize, config.media_generation_queue as usize);
     println!("Media Manager initialized successfully");
 
+    let card_pool_cache = shared::rarity::CardPoolCache::new(
+        std::time::Duration::from_secs(config.pack_card_pool_ttl as u64),
+        config.rarity_default_weight
+    );
+
     /*
     let allowed_origins = AllowedOrigins::all();
 
@@ -144,6 +208,8 @@ async fn rocket() -> _ {
 
             card::unlocked::card_unlocked_route,
             card::upgrade::upgrade_route,
+            card::dismantle::dismantle_route,
+            card::craft::craft_route,
             card::request::create::create::card_request_create_route,
             card::request::create::update::card_request_update_route,
             card::request::create::delete::card_request_delete_route,
@@ -154,8 +220,11 @@ async fn rocket() -> _ {
             card::card_image::card_image_default_route,
             card::card_image::card_image_get_route,
             card::card_image::card_image_set_route,
+            card::card_image::card_image_unlocked_route,
+            card::card_image::card_image_unlocked_default_route,
             card::card_type::card_type_config_route,
             card::card_type::card_type_index_route,
+            card::card_type::card_type_rarity_route,
             card::card_type::request::create::create::card_type_request_create_route,
             card::card_type::request::create::update::card_type_request_update_route,
             card::card_type::request::create::delete::card_type_request_delete_route,
@@ -168,6 +237,7 @@ async fn rocket() -> _ {
             card::frame::default::card_frame_front_default_route,
             card::frame::default::card_frame_back_default_route,
             card::get::card_route,
+            card::rarity::card_rarity_route,
 
             trade::info::trade_route,
             trade::confirm::trade_confirm_route,
@@ -176,10 +246,26 @@ async fn rocket() -> _ {
             trade::suggestion::add::trade_suggestion_add_route,
             trade::suggestion::remove::trade_suggestion_remove_route,
             trade::time::trade_time_route,
+            trade::offer::decline::trade_offer_decline_route,
+            trade::offer::cancel::trade_offer_cancel_route,
+            trade::history::trade_history_route,
+            market::balance::market_balance_route,
+            market::daily::market_daily_route,
+            market::listing::index::market_listing_index_route,
+            market::listing::create::market_listing_create_route,
+            market::listing::buy::market_listing_buy_route,
+            market::listing::cancel::market_listing_cancel_route,
 
             admin::log::admin_log_route,
+            admin::media::admin_media_stats_route,
+            admin::media::admin_media_gc_route,
+            admin::media::admin_media_regenerate_route,
+            admin::media::admin_media_reload_route,
             admin::give::card::give_card_route,
 
+            media::focal_point::media_focal_point_set_route,
+            media::focal_point::media_focal_point_delete_route,
+
             collector::create::create_collector_route,
             collector::update::update_collector_route,
             collector::config::general::get_collector_general_config_route,
@@ -199,6 +285,9 @@ async fn rocket() -> _ {
             collector::moderator::index::collector_moderator_index_route,
             collector::moderator::add::collector_moderator_add_route,
             collector::moderator::remove::collector_moderator_remove_route,
+            collector::rarity::index::collector_rarity_index_route,
+            collector::rarity::create::collector_rarity_create_route,
+            collector::rarity::delete::collector_rarity_delete_route,
         ])
         .mount(format!("/{}", &config.card_image_base), FileServer::from(relative!("static/card")))
         .mount(format!("/{}", &config.frame_image_base), FileServer::from(relative!("static/frame")))
@@ -211,4 +300,6 @@ async fn rocket() -> _ {
         .attach(cors::CORS)
         .manage(sql)
         .manage(media_manager)
+        .manage(media_gc)
+        .manage(card_pool_cache)
 }
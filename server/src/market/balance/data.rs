@@ -0,0 +1,9 @@
+use serde::Serialize;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct MarketBalanceResponse {
+    pub balance: i32,
+    pub daily_time: DateTime<Utc>
+}
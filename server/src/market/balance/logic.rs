@@ -0,0 +1,26 @@
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+use rocket::http::Status;
+use rocket::State;
+
+use super::data::MarketBalanceResponse;
+use crate::sql::Sql;
+use crate::config::Config;
+use crate::shared::Id;
+use crate::shared::crypto::JwtToken;
+use crate::shared::{market, util};
+use crate::{verify_user, verify_collector};
+
+#[get("/market/<collector_id>/balance")]
+pub async fn market_balance_route(collector_id: Id, sql: &State<Sql>, config: &State<Config>, token: JwtToken) -> ApiResponseErr<MarketBalanceResponse> {
+    let user_id = token.id;
+
+    verify_user!(sql, &user_id, true);
+    verify_collector!(sql, &collector_id);
+
+    let balance = rjtry!(market::sql::get_balance(sql, &user_id, &collector_id).await);
+
+    ApiResponseErr::ok(Status::Ok, MarketBalanceResponse {
+        balance: balance.bamount,
+        daily_time: util::time_from_db(balance.bdaily, config.market_daily_cooldown)
+    })
+}
@@ -0,0 +1,4 @@
+mod logic;
+mod data;
+
+pub use logic::market_balance_route;
@@ -0,0 +1,16 @@
+use serde::Serialize;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct MarketDailyResponse {
+    pub reward: u32,
+    pub balance: i32,
+    pub daily_time: DateTime<Utc>
+}
+
+pub enum DailyClaimResult {
+    //balance after the reward
+    Claimed(i32),
+    Wait(DateTime<Utc>)
+}
@@ -0,0 +1,32 @@
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+use rocket::http::Status;
+use rocket::State;
+use chrono::{Utc, Duration};
+
+use super::data::{MarketDailyResponse, DailyClaimResult};
+use super::sql;
+use crate::sql::Sql;
+use crate::config::Config;
+use crate::shared::Id;
+use crate::shared::crypto::JwtToken;
+use crate::shared::collector::{get_collector_setting, CollectorSetting};
+use crate::{verify_user, verify_collector};
+
+#[post("/market/<collector_id>/daily")]
+pub async fn market_daily_route(collector_id: Id, sql: &State<Sql>, config: &State<Config>, token: JwtToken) -> ApiResponseErr<MarketDailyResponse> {
+    let user_id = token.id;
+
+    verify_user!(sql, &user_id, true);
+    verify_collector!(sql, &collector_id);
+
+    let reward = rjtry!(get_collector_setting(sql, &collector_id, CollectorSetting::MarketDailyReward, config.market_daily_reward).await);
+
+    match rjtry!(sql::claim_daily(sql, &user_id, &collector_id, reward, config.market_daily_cooldown).await) {
+        DailyClaimResult::Wait(next_time) => ApiResponseErr::api_err(Status::Conflict, format!("Wait until: {}", next_time)),
+        DailyClaimResult::Claimed(balance) => ApiResponseErr::ok(Status::Ok, MarketDailyResponse {
+            reward,
+            balance,
+            daily_time: Utc::now() + Duration::seconds(config.market_daily_cooldown as i64)
+        })
+    }
+}
@@ -0,0 +1,36 @@
+use chrono::{Utc, Duration};
+
+use crate::sql::Sql;
+use crate::shared::Id;
+use crate::shared::market;
+use super::data::DailyClaimResult;
+
+pub async fn claim_daily(sql: &Sql, user_id: &Id, collector_id: &Id, reward: u32, cooldown: u32) -> Result<DailyClaimResult, sqlx::Error> {
+    market::sql::ensure_balance(sql, user_id, collector_id).await?;
+
+    let mut transaction = sql.pool().begin().await?;
+
+    let balance = market::sql::lock_balance(&mut transaction, user_id, collector_id).await?;
+
+    if let Some(last_daily) = balance.bdaily {
+        let next_time = last_daily + Duration::seconds(cooldown as i64);
+        if Utc::now() < next_time {
+            return Ok(DailyClaimResult::Wait(next_time));
+        }
+    }
+
+    sqlx::query(
+        "UPDATE balances
+         SET bamount=bamount+?, bdaily=?
+         WHERE uid=? AND coid=?;")
+        .bind(reward)
+        .bind(Utc::now())
+        .bind(user_id)
+        .bind(collector_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    transaction.commit().await?;
+
+    Ok(DailyClaimResult::Claimed(balance.bamount + reward as i32))
+}
@@ -0,0 +1,32 @@
+use serde::Serialize;
+use sqlx::FromRow;
+
+use crate::shared::Id;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct MarketListingBuyResponse {
+    pub message: String,
+    pub card_unlocked_id: Id,
+    pub balance: i32
+}
+
+#[derive(Debug, FromRow)]
+pub struct ListingDb {
+    //NULL once the card of a closed listing was dismantled
+    pub cuid: Option<Id>,
+    pub coid: Id,
+    pub uid: Id,
+    pub mlprice: i32,
+    pub mlstatus: i32
+}
+
+pub enum ListingBuyResult {
+    //balance after buying
+    Bought(i32),
+    Closed,
+    OwnListing,
+    //the seller does not own the card anymore
+    CardChanged,
+    InsufficientBalance(i32)
+}
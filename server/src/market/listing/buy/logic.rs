@@ -0,0 +1,62 @@
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+use rocket::http::Status;
+use rocket::State;
+use chrono::Utc;
+
+use super::data::{MarketListingBuyResponse, ListingBuyResult};
+use super::sql;
+use crate::sql::Sql;
+use crate::shared::Id;
+use crate::shared::crypto::JwtToken;
+use crate::shared::{market, notification};
+use crate::shared::market::data::ListingStatus;
+use crate::verify_user;
+
+#[post("/market/listing/<listing_id>/buy")]
+pub async fn market_listing_buy_route(listing_id: Id, sql: &State<Sql>, token: JwtToken) -> ApiResponseErr<MarketListingBuyResponse> {
+    let JwtToken { id: user_id, username } = token;
+
+    verify_user!(sql, &user_id, true);
+
+    let listing = match rjtry!(sql::get_listing(sql, &listing_id).await) {
+        Some(listing) => listing,
+        None => return ApiResponseErr::api_err(Status::NotFound, format!("No listing with id {} found", &listing_id))
+    };
+
+    let card_unlocked_id = match &listing.cuid {
+        Some(card_unlocked_id) if listing.mlstatus == ListingStatus::Open as i32 => card_unlocked_id.clone(),
+        _ => return ApiResponseErr::api_err(Status::Conflict, format!("The listing {} is not open anymore", &listing_id))
+    };
+
+    if listing.uid == user_id {
+        return ApiResponseErr::api_err(Status::Conflict, String::from("Can not buy your own listing"));
+    }
+
+    //the buyer needs a balance row to lock
+    rjtry!(market::sql::ensure_balance(sql, &user_id, &listing.coid).await);
+
+    let balance = match rjtry!(sql::buy_listing(sql, &listing_id, &user_id).await) {
+        ListingBuyResult::Bought(balance) => balance,
+        ListingBuyResult::Closed =>
+            return ApiResponseErr::api_err(Status::Conflict, format!("The listing {} is not open anymore", &listing_id)),
+        ListingBuyResult::OwnListing =>
+            return ApiResponseErr::api_err(Status::Conflict, String::from("Can not buy your own listing")),
+        ListingBuyResult::CardChanged =>
+            return ApiResponseErr::api_err(Status::Conflict, format!("The card {} is not available anymore", &card_unlocked_id)),
+        ListingBuyResult::InsufficientBalance(balance) =>
+            return ApiResponseErr::api_err(Status::Conflict, format!("Not enough balance: {} of {}", balance, listing.mlprice)),
+    };
+
+    rjtry!(notification::sql::add_notification(sql, &listing.uid, Some(&listing.coid), &notification::data::NotificationCreateData {
+        title: String::from("Card Sold"),
+        message: format!("{} bought your card for {}", &username, listing.mlprice),
+        url: format!("market/{}", &listing.coid),
+        time: Utc::now()
+    }).await);
+
+    ApiResponseErr::ok(Status::Ok, MarketListingBuyResponse {
+        message: format!("Bought card {} for {}", &card_unlocked_id, listing.mlprice),
+        card_unlocked_id,
+        balance
+    })
+}
@@ -0,0 +1,115 @@
+use chrono::Utc;
+
+use crate::sql::Sql;
+use crate::shared::Id;
+use crate::shared::market;
+use crate::shared::market::data::ListingStatus;
+use super::data::{ListingDb, ListingBuyResult};
+
+pub async fn get_listing(sql: &Sql, listing_id: &Id) -> Result<Option<ListingDb>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT cuid, coid, uid, mlprice, mlstatus
+         FROM marketlistings
+         WHERE mlid=?;")
+        .bind(listing_id)
+        .fetch_optional(sql.pool())
+        .await
+}
+
+//moves the card to the buyer and the price to the seller, all checks are repeated under lock
+pub async fn buy_listing(sql: &Sql, listing_id: &Id, user_id: &Id) -> Result<ListingBuyResult, sqlx::Error> {
+    let mut transaction = sql.pool().begin().await?;
+
+    let listing: ListingDb = match sqlx::query_as(
+        "SELECT cuid, coid, uid, mlprice, mlstatus
+         FROM marketlistings
+         WHERE mlid=?
+         FOR UPDATE;")
+        .bind(listing_id)
+        .fetch_optional(&mut *transaction)
+        .await? {
+        Some(listing) => listing,
+        None => return Ok(ListingBuyResult::Closed)
+    };
+
+    let card_unlocked_id = match &listing.cuid {
+        Some(card_unlocked_id) if listing.mlstatus == ListingStatus::Open as i32 => card_unlocked_id,
+        _ => return Ok(ListingBuyResult::Closed)
+    };
+
+    if &listing.uid == user_id {
+        return Ok(ListingBuyResult::OwnListing);
+    }
+
+    let owned: Option<(Id, )> = sqlx::query_as(
+        "SELECT cuid
+         FROM cardunlocks
+         WHERE cuid=? AND uid=?
+         FOR UPDATE;")
+        .bind(card_unlocked_id)
+        .bind(&listing.uid)
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+    if owned.is_none() {
+        return Ok(ListingBuyResult::CardChanged);
+    }
+
+    //both balances are locked in the same order to not deadlock with a parallel buy in the other direction
+    let balances: Vec<(Id, i32)> = sqlx::query_as(
+        "SELECT uid, bamount
+         FROM balances
+         WHERE coid=? AND uid IN (?, ?)
+         ORDER BY uid
+         FOR UPDATE;")
+        .bind(&listing.coid)
+        .bind(user_id)
+        .bind(&listing.uid)
+        .fetch_all(&mut *transaction)
+        .await?;
+
+    let balance = balances
+        .iter()
+        .find(|(uid, _)| uid == user_id)
+        .map(|(_, amount)| *amount)
+        .unwrap_or(0);
+
+    if balance < listing.mlprice {
+        return Ok(ListingBuyResult::InsufficientBalance(balance));
+    }
+
+    market::sql::add_balance(&mut transaction, user_id, &listing.coid, -listing.mlprice).await?;
+    market::sql::add_balance(&mut transaction, &listing.uid, &listing.coid, listing.mlprice).await?;
+
+    sqlx::query(
+        "UPDATE cardunlocks
+         SET uid=?
+         WHERE cuid=?;")
+        .bind(user_id)
+        .bind(card_unlocked_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    //suggestions were made to the seller
+    sqlx::query(
+        "DELETE FROM tradesuggestions
+         WHERE cuid=?;")
+        .bind(card_unlocked_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query(
+        "UPDATE marketlistings
+         SET mlstatus=?, uidbuyer=?, mlresolved=?
+         WHERE mlid=?;")
+        .bind(ListingStatus::Sold as i32)
+        .bind(user_id)
+        .bind(Utc::now())
+        .bind(listing_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    transaction.commit().await?;
+
+    Ok(ListingBuyResult::Bought(balance - listing.mlprice))
+}
@@ -0,0 +1,6 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct MarketListingCancelResponse {
+    pub message: String
+}
@@ -0,0 +1,25 @@
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+use rocket::http::Status;
+use rocket::State;
+
+use super::data::MarketListingCancelResponse;
+use super::sql;
+use crate::sql::Sql;
+use crate::shared::Id;
+use crate::shared::crypto::JwtToken;
+use crate::verify_user;
+
+#[post("/market/listing/<listing_id>/cancel")]
+pub async fn market_listing_cancel_route(listing_id: Id, sql: &State<Sql>, token: JwtToken) -> ApiResponseErr<MarketListingCancelResponse> {
+    let user_id = token.id;
+
+    verify_user!(sql, &user_id, true);
+
+    if !rjtry!(sql::cancel_listing(sql, &listing_id, &user_id).await) {
+        return ApiResponseErr::api_err(Status::NotFound, format!("No open listing with id {} found", &listing_id));
+    }
+
+    ApiResponseErr::ok(Status::Ok, MarketListingCancelResponse {
+        message: format!("Cancelled listing {}", &listing_id)
+    })
+}
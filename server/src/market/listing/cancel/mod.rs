@@ -0,0 +1,5 @@
+mod logic;
+mod data;
+mod sql;
+
+pub use logic::market_listing_cancel_route;
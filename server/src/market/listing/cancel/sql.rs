@@ -0,0 +1,21 @@
+use chrono::Utc;
+
+use crate::sql::Sql;
+use crate::shared::Id;
+use crate::shared::market::data::ListingStatus;
+
+pub async fn cancel_listing(sql: &Sql, listing_id: &Id, user_id: &Id) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE marketlistings
+         SET mlstatus=?, mlresolved=?
+         WHERE mlid=? AND uid=? AND mlstatus=?;")
+        .bind(ListingStatus::Cancelled as i32)
+        .bind(Utc::now())
+        .bind(listing_id)
+        .bind(user_id)
+        .bind(ListingStatus::Open as i32)
+        .execute(sql.pool())
+        .await?;
+
+    Ok(result.rows_affected() != 0)
+}
@@ -0,0 +1,33 @@
+use rocketjson::JsonBody;
+use validator::{Validate, ValidationError, ValidateArgs};
+use serde::{Serialize, Deserialize};
+use std::borrow::Cow;
+
+use crate::shared::Id;
+use crate::config;
+
+#[derive(Debug, Deserialize, Validate, JsonBody)]
+#[serde(rename_all = "camelCase")]
+#[validate(context = config::Config)]
+pub struct MarketListingCreateRequest {
+    pub card_unlocked_id: Id,
+    #[validate(custom(function="validate_price", use_context))]
+    pub price: u32
+}
+
+#[derive(Debug, Serialize)]
+pub struct MarketListingCreateResponse {
+    pub id: Id
+}
+
+fn validate_price(price: u32, config: &config::Config) -> Result<(), ValidationError> {
+	if price < config.market_price_min || price > config.market_price_max {
+        let mut err = ValidationError::new("Price not in valid range");
+        err.add_param(Cow::from("min"), &config.market_price_min);
+        err.add_param(Cow::from("max"), &config.market_price_max);
+
+        return Err(err);
+    }
+
+    Ok(())
+}
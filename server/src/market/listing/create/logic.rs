@@ -0,0 +1,50 @@
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+use rocket::http::Status;
+use rocket::State;
+
+use super::data::{MarketListingCreateRequest, MarketListingCreateResponse};
+use super::sql;
+use crate::sql::Sql;
+use crate::config::Config;
+use crate::shared::Id;
+use crate::shared::crypto::JwtToken;
+use crate::shared::{card, trade, market};
+use crate::{verify_user, verify_collector};
+
+#[post("/market/<collector_id>/listing/create", data="<data>")]
+pub async fn market_listing_create_route(collector_id: Id, data: MarketListingCreateRequest, sql: &State<Sql>, config: &State<Config>, token: JwtToken) -> ApiResponseErr<MarketListingCreateResponse> {
+    let user_id = token.id;
+
+    verify_user!(sql, &user_id, true);
+    verify_collector!(sql, &collector_id);
+
+    if !rjtry!(card::sql::user_owns_card(sql, &user_id, &data.card_unlocked_id, Some(&collector_id)).await) {
+        return ApiResponseErr::api_err(Status::NotFound,
+                                       format!("The card with the id {} does not exist or is not owned by {} in the collector {}",
+                                               &data.card_unlocked_id,
+                                               &user_id,
+                                               &collector_id
+                                        ));
+    }
+
+    if rjtry!(trade::sql::card_in_trade(sql, &data.card_unlocked_id).await) {
+        return ApiResponseErr::api_err(Status::Conflict, format!("Card with id {} is in a trade", &data.card_unlocked_id));
+    }
+
+    if rjtry!(market::sql::card_listed(sql, &data.card_unlocked_id).await) {
+        return ApiResponseErr::api_err(Status::Conflict, format!("Card with id {} is already listed", &data.card_unlocked_id));
+    }
+
+    if rjtry!(sql::listing_count(sql, &user_id, &collector_id).await) >= config.market_listing_limit as i64 {
+        return ApiResponseErr::api_err(Status::Conflict, format!("Max listing limit of {} reached", config.market_listing_limit));
+    }
+
+    let listing_id = Id::new(config.id_length);
+    if !rjtry!(sql::create_listing(sql, &listing_id, &user_id, &collector_id, &data.card_unlocked_id, data.price).await) {
+        return ApiResponseErr::api_err(Status::Conflict, format!("Card with id {} changed, try again", &data.card_unlocked_id));
+    }
+
+    ApiResponseErr::ok(Status::Ok, MarketListingCreateResponse {
+        id: listing_id
+    })
+}
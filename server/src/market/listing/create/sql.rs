@@ -0,0 +1,76 @@
+use crate::sql::Sql;
+use crate::shared::Id;
+use crate::shared::market::data::ListingStatus;
+
+pub async fn listing_count(sql: &Sql, user_id: &Id, collector_id: &Id) -> Result<i64, sqlx::Error> {
+    let (count, ): (i64, ) = sqlx::query_as(
+        "SELECT COUNT(*)
+         FROM marketlistings
+         WHERE uid=? AND coid=? AND mlstatus=?;")
+        .bind(user_id)
+        .bind(collector_id)
+        .bind(ListingStatus::Open as i32)
+        .fetch_one(sql.pool())
+        .await?;
+
+    Ok(count)
+}
+
+//locks the card and checks again that it is owned and not in a trade or listed,
+//returns false if that changed since the checks of the route
+//the unique key on open listings (mlopencuid) backs this up in the database
+pub async fn create_listing(sql: &Sql, listing_id: &Id, user_id: &Id, collector_id: &Id, card_unlocked_id: &Id, price: u32) -> Result<bool, sqlx::Error> {
+    let mut transaction = sql.pool().begin().await?;
+
+    let owned: Option<(Id, )> = sqlx::query_as(
+        "SELECT cuid
+         FROM cardunlocks
+         WHERE cuid=? AND uid=?
+         FOR UPDATE;")
+        .bind(card_unlocked_id)
+        .bind(user_id)
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+    if owned.is_none() {
+        return Ok(false);
+    }
+
+    let (locked, ): (i64, ) = sqlx::query_as(
+        "SELECT
+         (SELECT COUNT(*) FROM tradecards WHERE cuid=?) +
+         (SELECT COUNT(*) FROM marketlistings WHERE cuid=? AND mlstatus=?);")
+        .bind(card_unlocked_id)
+        .bind(card_unlocked_id)
+        .bind(ListingStatus::Open as i32)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+    if locked != 0 {
+        return Ok(false);
+    }
+
+    let inserted = sqlx::query(
+        "INSERT INTO marketlistings
+         (mlid, cuid, coid, uid, mlprice, mlstatus)
+         VALUES
+         (?, ?, ?, ?, ?, ?);")
+        .bind(listing_id)
+        .bind(card_unlocked_id)
+        .bind(collector_id)
+        .bind(user_id)
+        .bind(price)
+        .bind(ListingStatus::Open as i32)
+        .execute(&mut *transaction)
+        .await;
+
+    match inserted {
+        Ok(_) => (),
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => return Ok(false),
+        Err(e) => return Err(e)
+    }
+
+    transaction.commit().await?;
+
+    Ok(true)
+}
@@ -0,0 +1,86 @@
+use serde::Serialize;
+use serde_repr::Serialize_repr;
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+
+use crate::shared::Id;
+use crate::shared::card::data::{UnlockedCardDb, UnlockedCard};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketListingIndexResponse {
+    pub page_size: u32,
+    pub page: u32,
+    pub listing_count: u32,
+    pub listings: Vec<MarketListing>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketListing {
+    pub id: Id,
+    pub user_id: Id,
+    pub username: String,
+    pub price: i32,
+    pub time: DateTime<Utc>,
+    pub card: UnlockedCard,
+}
+
+#[derive(Debug, FromRow)]
+pub struct MarketListingDb {
+    pub mlid: Id,
+    pub mluid: Id,
+    pub uusername: String,
+    pub mlprice: i32,
+    pub mltime: DateTime<Utc>,
+    #[sqlx(flatten)]
+    pub card: UnlockedCardDb,
+}
+
+impl From<MarketListingDb> for MarketListing {
+    fn from(listing: MarketListingDb) -> Self {
+        Self {
+            id: listing.mlid,
+            user_id: listing.mluid,
+            username: listing.uusername,
+            price: listing.mlprice,
+            time: listing.mltime,
+            card: UnlockedCard::from(listing.card),
+        }
+    }
+}
+
+pub struct MarketListingOptions {
+    pub collector_id: Id,
+    pub search: String,
+    pub sort_type: MarketSortType,
+    pub card_id: Option<Id>,
+    pub user_id: Option<Id>,
+    pub level: Option<i32>,
+    pub price_min: Option<u32>,
+    pub price_max: Option<u32>,
+    pub count: u32,
+    pub offset: u32,
+}
+
+#[derive(Debug, Serialize_repr)]
+#[repr(i32)]
+pub enum MarketSortType {
+    Recent = 0,
+    PriceLow = 1,
+    PriceHigh = 2,
+    Level = 3,
+    Name = 4
+}
+
+impl From<Option<i32>> for MarketSortType {
+    fn from(value: Option<i32>) -> Self {
+        match value {
+            Some(1) => Self::PriceLow,
+            Some(2) => Self::PriceHigh,
+            Some(3) => Self::Level,
+            Some(4) => Self::Name,
+            _ => Self::Recent
+        }
+    }
+}
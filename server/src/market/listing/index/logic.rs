@@ -0,0 +1,40 @@
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+use rocket::http::Status;
+use rocket::State;
+
+use super::data::{MarketListingIndexResponse, MarketListingOptions, MarketSortType, MarketListing};
+use super::sql;
+use crate::sql::Sql;
+use crate::config::Config;
+use crate::shared::Id;
+use crate::verify_collector;
+
+#[get("/market/<collector_id>/listing?<search>&<page>&<sort_type>&<card_id>&<user_id>&<level>&<price_min>&<price_max>")]
+pub async fn market_listing_index_route(collector_id: Id, search: Option<String>, page: Option<u32>, sort_type: Option<i32>, card_id: Option<Id>, user_id: Option<Id>, level: Option<i32>, price_min: Option<u32>, price_max: Option<u32>, sql: &State<Sql>, config: &State<Config>) -> ApiResponseErr<MarketListingIndexResponse> {
+    verify_collector!(sql, &collector_id);
+
+    let page = page.unwrap_or(0);
+
+    let options = MarketListingOptions {
+        collector_id,
+        search: search.unwrap_or_default(),
+        sort_type: MarketSortType::from(sort_type),
+        card_id,
+        user_id,
+        level,
+        price_min,
+        price_max,
+        count: config.market_page_amount,
+        offset: config.market_page_amount * page,
+    };
+
+    let listings = rjtry!(sql::get_listings(sql, &options).await);
+    let listing_count = rjtry!(sql::get_listing_count(sql, &options).await);
+
+    ApiResponseErr::ok(Status::Ok, MarketListingIndexResponse {
+        page_size: config.market_page_amount,
+        page,
+        listing_count,
+        listings: listings.into_iter().map(MarketListing::from).collect()
+    })
+}
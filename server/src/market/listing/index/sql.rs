@@ -0,0 +1,170 @@
+use crate::sql::Sql;
+use crate::shared::util;
+use crate::shared::market::data::ListingStatus;
+use super::data::{MarketListingDb, MarketListingOptions, MarketSortType};
+
+fn extra_conditions(options: &MarketListingOptions) -> String {
+    let mut extra_conditions: Vec<&str> = Vec::new();
+
+    if options.card_id.is_some() {
+        extra_conditions.push("cards.cid = ?");
+    }
+
+    if options.user_id.is_some() {
+        extra_conditions.push("marketlistings.uid = ?");
+    }
+
+    if options.level.is_some() {
+        extra_conditions.push("cardunlocks.culevel = ?");
+    }
+
+    if options.price_min.is_some() {
+        extra_conditions.push("marketlistings.mlprice >= ?");
+    }
+
+    if options.price_max.is_some() {
+        extra_conditions.push("marketlistings.mlprice <= ?");
+    }
+
+    if extra_conditions.is_empty() {
+        String::new()
+    } else {
+        format!("{} AND", extra_conditions.join(" AND "))
+    }
+}
+
+pub async fn get_listings(sql: &Sql, options: &MarketListingOptions) -> Result<Vec<MarketListingDb>, sqlx::Error> {
+    let search = util::escape_for_like(options.search.clone());
+
+    let order_by = match options.sort_type {
+        MarketSortType::Recent => "marketlistings.mltime DESC",
+        MarketSortType::PriceLow => "marketlistings.mlprice ASC, marketlistings.mltime DESC",
+        MarketSortType::PriceHigh => "marketlistings.mlprice DESC, marketlistings.mltime DESC",
+        MarketSortType::Level => "cardunlocks.culevel DESC, cardunlocks.cuquality DESC, marketlistings.mltime DESC",
+        MarketSortType::Name => "cards.cname ASC, marketlistings.mltime DESC",
+    };
+
+    let query = format!(
+        "SELECT
+         marketlistings.mlid,
+         marketlistings.uid AS mluid,
+         marketlistings.mlprice,
+         marketlistings.mltime,
+         users.uusername,
+         cardunlocks.cuid,
+         cardunlocks.uid AS cuuid,
+         cardunlocks.culevel,
+         cardunlocks.cuquality,
+         cardunlocks.cutime,
+         cards.cid,
+         cards.uid AS ccuid,
+         cards.cname,
+         cards.ctime,
+         cards.cstate,
+         cardtypes.ctid,
+         cardtypes.ctname,
+         cardtypes.uid AS ctuid,
+         cardtypes.coid,
+         cardtypes.ctstate,
+         cardtypes.cttime,
+         cardframes.cfid,
+         cardframes.cfname,
+         cardeffects.ceid,
+         cardeffects.ceopacity
+         FROM (marketlistings, users, cardunlocks, cards, cardtypes)
+         LEFT JOIN cardframes ON cardframes.cfid = cardunlocks.cfid
+         LEFT JOIN cardeffects ON cardeffects.ceid = cardunlocks.culevel
+         WHERE
+         {}
+         marketlistings.uid = users.uid
+         AND marketlistings.cuid = cardunlocks.cuid
+         AND cardunlocks.cid = cards.cid
+         AND cards.ctid = cardtypes.ctid
+         AND marketlistings.coid=?
+         AND marketlistings.mlstatus=?
+         AND (cards.cname LIKE CONCAT('%', ?, '%') OR cardtypes.ctname LIKE CONCAT('%', ?, '%'))
+         ORDER BY
+         {}
+         LIMIT ? OFFSET ?;",
+         extra_conditions(options),
+         order_by);
+
+    let mut stmt = sqlx::query_as(&query);
+
+    if let Some(card_id) = &options.card_id {
+        stmt = stmt.bind(card_id);
+    }
+
+    if let Some(user_id) = &options.user_id {
+        stmt = stmt.bind(user_id);
+    }
+
+    if let Some(level) = options.level {
+        stmt = stmt.bind(level);
+    }
+
+    if let Some(price_min) = options.price_min {
+        stmt = stmt.bind(price_min);
+    }
+
+    if let Some(price_max) = options.price_max {
+        stmt = stmt.bind(price_max);
+    }
+
+    stmt.bind(&options.collector_id)
+        .bind(ListingStatus::Open as i32)
+        .bind(&search)
+        .bind(&search)
+        .bind(options.count)
+        .bind(options.offset)
+        .fetch_all(sql.pool())
+        .await
+}
+
+pub async fn get_listing_count(sql: &Sql, options: &MarketListingOptions) -> Result<u32, sqlx::Error> {
+    let search = util::escape_for_like(options.search.clone());
+
+    let query = format!(
+        "SELECT COUNT(*)
+         FROM marketlistings, cardunlocks, cards, cardtypes
+         WHERE
+         {}
+         marketlistings.cuid = cardunlocks.cuid
+         AND cardunlocks.cid = cards.cid
+         AND cards.ctid = cardtypes.ctid
+         AND marketlistings.coid=?
+         AND marketlistings.mlstatus=?
+         AND (cards.cname LIKE CONCAT('%', ?, '%') OR cardtypes.ctname LIKE CONCAT('%', ?, '%'));",
+         extra_conditions(options));
+
+    let mut stmt = sqlx::query_as(&query);
+
+    if let Some(card_id) = &options.card_id {
+        stmt = stmt.bind(card_id);
+    }
+
+    if let Some(user_id) = &options.user_id {
+        stmt = stmt.bind(user_id);
+    }
+
+    if let Some(level) = options.level {
+        stmt = stmt.bind(level);
+    }
+
+    if let Some(price_min) = options.price_min {
+        stmt = stmt.bind(price_min);
+    }
+
+    if let Some(price_max) = options.price_max {
+        stmt = stmt.bind(price_max);
+    }
+
+    let (count, ): (i64, ) = stmt.bind(&options.collector_id)
+        .bind(ListingStatus::Open as i32)
+        .bind(&search)
+        .bind(&search)
+        .fetch_one(sql.pool())
+        .await?;
+
+    Ok(count as u32)
+}
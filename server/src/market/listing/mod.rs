@@ -0,0 +1,4 @@
+pub mod index;
+pub mod create;
+pub mod buy;
+pub mod cancel;
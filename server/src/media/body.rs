@@ -0,0 +1,198 @@
+use std::io::{self, SeekFrom};
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+
+/// Image bytes of a response, either in memory or streamed from disk
+#[derive(Debug)]
+pub enum MediaBody {
+    /// Freshly generated or held in memory
+    Memory(Vec<u8>),
+
+    /// Cached file with its length, streamed instead of buffered
+    File(File, u64),
+}
+
+impl MediaBody {
+    /// Length in bytes
+    pub fn len(&self) -> u64 {
+        match self {
+            MediaBody::Memory(bytes) => bytes.len() as u64,
+            MediaBody::File(_, len) => *len,
+        }
+    }
+}
+
+/// Result of parsing a Range header against a body length
+#[derive(Debug, PartialEq, Eq)]
+pub enum ByteRange {
+    /// No (usable) range, send the whole body
+    Full,
+
+    /// Inclusive start and end
+    Partial(u64, u64),
+
+    /// Range starts after the end of the body
+    Unsatisfiable,
+}
+
+impl ByteRange {
+    /// Parse a single `bytes=` range
+    ///
+    /// Malformed headers and multiple ranges are ignored (whole body), as
+    /// allowed by RFC 9110
+    pub fn parse(header: &str, len: u64) -> Self {
+        let spec = match header.trim().strip_prefix("bytes=") {
+            Some(spec) if !spec.contains(',') => spec.trim(),
+            _ => return ByteRange::Full,
+        };
+
+        let (start, end) = match spec.split_once('-') {
+            Some(parts) => parts,
+            None => return ByteRange::Full,
+        };
+
+        match (start.parse::<u64>(), end.parse::<u64>()) {
+            // bytes=-500, the last 500 bytes
+            (Err(_), Ok(suffix)) if start.is_empty() => {
+                if suffix == 0 || len == 0 {
+                    ByteRange::Unsatisfiable
+                } else {
+                    ByteRange::Partial(len.saturating_sub(suffix), len - 1)
+                }
+            }
+            // bytes=500-
+            (Ok(start), Err(_)) if end.is_empty() => {
+                if start >= len {
+                    ByteRange::Unsatisfiable
+                } else {
+                    ByteRange::Partial(start, len - 1)
+                }
+            }
+            // bytes=500-999
+            (Ok(start), Ok(end)) if start <= end => {
+                if start >= len {
+                    ByteRange::Unsatisfiable
+                } else {
+                    ByteRange::Partial(start, end.min(len - 1))
+                }
+            }
+            _ => ByteRange::Full,
+        }
+    }
+}
+
+/// Reader over `len` bytes of a file starting at `start`
+///
+/// The file has to be positioned at `start` already. Seeks are relative to
+/// the range, so Rocket sees a body of exactly `len` bytes.
+pub struct FileRange {
+    file: File,
+    start: u64,
+    len: u64,
+    pos: u64,
+}
+
+impl FileRange {
+    pub fn new(file: File, start: u64, len: u64) -> Self {
+        Self { file, start, len, pos: 0 }
+    }
+}
+
+impl AsyncRead for FileRange {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        let remaining = this.len - this.pos;
+        if remaining == 0 || buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+
+        let max = (buf.remaining() as u64).min(remaining) as usize;
+        let read = {
+            let mut limited = ReadBuf::new(buf.initialize_unfilled_to(max));
+            ready!(Pin::new(&mut this.file).poll_read(cx, &mut limited))?;
+            limited.filled().len()
+        };
+
+        buf.advance(read);
+        this.pos += read as u64;
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncSeek for FileRange {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        let this = self.get_mut();
+
+        let target = match position {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::Current(offset) => this.pos as i64 + offset,
+            SeekFrom::End(offset) => this.len as i64 + offset,
+        };
+
+        if target < 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "seek before start of range"));
+        }
+
+        let target = (target as u64).min(this.len);
+        Pin::new(&mut this.file).start_seek(SeekFrom::Start(this.start + target))
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+
+        let absolute = ready!(Pin::new(&mut this.file).poll_complete(cx))?;
+        this.pos = absolute.saturating_sub(this.start).min(this.len);
+
+        Poll::Ready(Ok(this.pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(ByteRange::parse("bytes=0-99", 1000), ByteRange::Partial(0, 99));
+        assert_eq!(ByteRange::parse("bytes=900-", 1000), ByteRange::Partial(900, 999));
+        assert_eq!(ByteRange::parse("bytes=-100", 1000), ByteRange::Partial(900, 999));
+        assert_eq!(ByteRange::parse("bytes=-2000", 1000), ByteRange::Partial(0, 999));
+        assert_eq!(ByteRange::parse("bytes=500-5000", 1000), ByteRange::Partial(500, 999));
+
+        assert_eq!(ByteRange::parse("bytes=1000-", 1000), ByteRange::Unsatisfiable);
+        assert_eq!(ByteRange::parse("bytes=-0", 1000), ByteRange::Unsatisfiable);
+
+        // Ignored
+        assert_eq!(ByteRange::parse("bytes=0-1,5-6", 1000), ByteRange::Full);
+        assert_eq!(ByteRange::parse("bytes=9-1", 1000), ByteRange::Full);
+        assert_eq!(ByteRange::parse("items=0-1", 1000), ByteRange::Full);
+        assert_eq!(ByteRange::parse("bytes=abc", 1000), ByteRange::Full);
+    }
+
+    #[tokio::test]
+    async fn test_file_range() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("data");
+        tokio::fs::write(&path, b"0123456789").await.unwrap();
+
+        let mut file = File::open(&path).await.unwrap();
+        file.seek(SeekFrom::Start(3)).await.unwrap();
+
+        let mut range = FileRange::new(file, 3, 4);
+        let mut read = Vec::new();
+        range.read_to_end(&mut read).await.unwrap();
+        assert_eq!(read, b"3456");
+
+        // Seeks are relative to the range
+        assert_eq!(range.seek(SeekFrom::Start(1)).await.unwrap(), 1);
+        let mut read = Vec::new();
+        range.read_to_end(&mut read).await.unwrap();
+        assert_eq!(read, b"456");
+    }
+}
@@ -1,7 +1,11 @@
 use std::path::{Path, PathBuf};
 use std::fs;
 use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+use serde::Serialize;
 use super::effect::ImageFormat;
+use super::body::MediaBody;
 
 /// Trait for image caching backends
 #[async_trait::async_trait]
@@ -9,6 +13,13 @@ pub trait ImageCache: Send + Sync {
     /// Get cached image bytes
     async fn get(&self, key: &CacheKey) -> Result<Option<Vec<u8>>, CacheError>;
 
+    /// Open a cached image for streaming
+    ///
+    /// Defaults to reading it into memory, backends on disk return the file
+    async fn open(&self, key: &CacheKey) -> Result<Option<MediaBody>, CacheError> {
+        Ok(self.get(key).await?.map(MediaBody::Memory))
+    }
+
     /// Store image bytes in cache
     async fn set(&self, key: &CacheKey, data: &[u8]) -> Result<(), CacheError>;
 
@@ -20,6 +31,28 @@ pub trait ImageCache: Send + Sync {
 
     /// Clear all cached images
     async fn clear(&self) -> Result<(), CacheError>;
+
+    /// Ids of all images with cached variants of a media type
+    async fn list_images(&self, media_type: &str) -> Result<Vec<String>, CacheError>;
+
+    /// Hit/miss counters of `get` and `open`
+    fn stats(&self) -> CacheStats;
+}
+
+/// Hit/miss counters of a cache
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub name: &'static str,
+    pub hits: u64,
+    pub misses: u64,
+
+    /// Bytes held, only for caches with a size budget
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+
+    /// Stats of composed caches, outermost first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tiers: Vec<CacheStats>,
 }
 
 /// Cache key that uniquely identifies a cached image variant
@@ -36,6 +69,12 @@ pub struct CacheKey {
 
     /// Output format
     pub format: ImageFormat,
+
+    /// Render context tag for composited images (e.g. frame, effect and level)
+    pub context: Option<String>,
+
+    /// Version of the variant config, changed configs get new keys
+    pub version: Option<String>,
 }
 
 impl CacheKey {
@@ -45,31 +84,77 @@ impl CacheKey {
             image_id,
             variant,
             format,
+            context: None,
+            version: None,
         }
     }
 
+    /// Set the render context tag
+    pub fn with_context(mut self, context: String) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Set the variant config version
+    pub fn with_version(mut self, version: String) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// File name of the variant, including the version and context tag if set
+    fn file_stem(&self) -> String {
+        let mut stem = self.variant.clone();
+        for part in [&self.version, &self.context].into_iter().flatten() {
+            stem.push('.');
+            stem.push_str(part);
+        }
+        stem
+    }
+
     /// Get the file path for this cache key
-    /// Format: {base_path}/{media_type}/{image_id}/{variant}.{ext}
+    /// Format: {base_path}/{media_type}/{image_id}/{variant}[.{version}][.{context}].{ext}
     pub fn to_path(&self, base_path: &Path) -> PathBuf {
         let extension = self.format.extension();
         base_path
             .join(&self.media_type)
             .join(&self.image_id)
-            .join(format!("{}.{}", self.variant, extension))
+            .join(format!("{}.{}", self.file_stem(), extension))
     }
 
     /// Create a string representation for use as a lookup key
     pub fn to_string_key(&self) -> String {
         format!(
-            "{}/{}/{}",
-            self.media_type, self.image_id, self.variant
+            "{}/{}/{}.{}",
+            self.media_type, self.image_id, self.file_stem(), self.format.extension()
         )
     }
 }
 
+/// Hits don't touch a file again within this interval
+const TOUCH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// A cached variant on disk
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    pub path: PathBuf,
+    pub image_id: String,
+    pub len: u64,
+
+    /// Modification time, bumped on hits
+    pub accessed: SystemTime,
+}
+
+/// Makes the temporary files of concurrent writes unique
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
 /// Filesystem-based image cache implementation
+///
+/// The modification time of a cached file is its last access time (atime is
+/// unreliable with noatime/relatime mounts), the GC evicts by it.
 pub struct FilesystemCache {
     base_path: PathBuf,
+    hits: AtomicU64,
+    misses: AtomicU64,
 }
 
 impl FilesystemCache {
@@ -77,9 +162,89 @@ impl FilesystemCache {
     pub fn new<P: Into<PathBuf>>(base_path: P) -> Self {
         Self {
             base_path: base_path.into(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
         }
     }
 
+    fn count(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Bump the modification time of a hit, at most once per `TOUCH_INTERVAL`
+    fn touch(path: PathBuf, modified: SystemTime) {
+        if !matches!(modified.elapsed(), Ok(age) if age >= TOUCH_INTERVAL) {
+            return;
+        }
+
+        tokio::task::spawn_blocking(move || {
+            //NOTE: a failed touch only makes the file an earlier eviction candidate
+            let _ = fs::File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(SystemTime::now()));
+        });
+    }
+
+    /// List all cached variants, layout `{media_type}/{image_id}/{file}`
+    pub fn entries(&self) -> Result<Vec<CacheEntry>, CacheError> {
+        let mut entries = Vec::new();
+
+        if !self.base_path.exists() {
+            return Ok(entries);
+        }
+
+        for media_type in fs::read_dir(&self.base_path)? {
+            let media_type = media_type?;
+            if !media_type.file_type()?.is_dir() {
+                continue;
+            }
+
+            for image in fs::read_dir(media_type.path())? {
+                let image = image?;
+                if !image.file_type()?.is_dir() {
+                    continue;
+                }
+
+                let image_id = image.file_name().to_string_lossy().into_owned();
+
+                for file in fs::read_dir(image.path())? {
+                    let file = file?;
+                    let metadata = file.metadata()?;
+                    if !metadata.is_file() {
+                        continue;
+                    }
+
+                    entries.push(CacheEntry {
+                        path: file.path(),
+                        image_id: image_id.clone(),
+                        len: metadata.len(),
+                        accessed: metadata.modified()?,
+                    });
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Remove a listed entry and its image directory once empty
+    pub fn remove_entry(&self, entry: &CacheEntry) -> Result<(), CacheError> {
+        match fs::remove_file(&entry.path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(CacheError::DeleteError(entry.path.clone(), e)),
+        }
+
+        //NOTE: fails if other variants are left, which is fine
+        if let Some(dir) = entry.path.parent() {
+            let _ = fs::remove_dir(dir);
+        }
+
+        Ok(())
+    }
+
     /// Ensure the directory structure exists for a cache key
     fn ensure_dir(&self, key: &CacheKey) -> Result<(), CacheError> {
         let path = key.to_path(&self.base_path);
@@ -96,19 +261,64 @@ impl ImageCache for FilesystemCache {
         let path = key.to_path(&self.base_path);
 
         match tokio::fs::read(&path).await {
-            Ok(data) => Ok(Some(data)),
-            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Ok(data) => {
+                self.count(true);
+                if let Ok(modified) = tokio::fs::metadata(&path).await.and_then(|m| m.modified()) {
+                    Self::touch(path, modified);
+                }
+                Ok(Some(data))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                self.count(false);
+                Ok(None)
+            }
             Err(e) => Err(CacheError::ReadError(path, e)),
         }
     }
 
+    async fn open(&self, key: &CacheKey) -> Result<Option<MediaBody>, CacheError> {
+        let path = key.to_path(&self.base_path);
+
+        let file = match tokio::fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                self.count(false);
+                return Ok(None);
+            }
+            Err(e) => return Err(CacheError::ReadError(path, e)),
+        };
+        self.count(true);
+
+        let metadata = file.metadata()
+            .await
+            .map_err(|e| CacheError::ReadError(path.clone(), e))?;
+
+        if let Ok(modified) = metadata.modified() {
+            Self::touch(path, modified);
+        }
+
+        Ok(Some(MediaBody::File(file, metadata.len())))
+    }
+
     async fn set(&self, key: &CacheKey, data: &[u8]) -> Result<(), CacheError> {
         self.ensure_dir(key)?;
 
         let path = key.to_path(&self.base_path);
-        tokio::fs::write(&path, data)
+
+        //NOTE: written to a temporary file first, readers streaming the old file never see a half-written one
+        let temp_path = path.with_file_name(format!(
+            "{}.{}.tmp",
+            key.file_stem(),
+            TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        tokio::fs::write(&temp_path, data)
             .await
-            .map_err(|e| CacheError::WriteError(path, e))?;
+            .map_err(|e| CacheError::WriteError(temp_path.clone(), e))?;
+
+        if let Err(e) = tokio::fs::rename(&temp_path, &path).await {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(CacheError::WriteError(path, e));
+        }
 
         Ok(())
     }
@@ -142,6 +352,35 @@ impl ImageCache for FilesystemCache {
 
         Ok(())
     }
+
+    async fn list_images(&self, media_type: &str) -> Result<Vec<String>, CacheError> {
+        let path = self.base_path.join(media_type);
+
+        let mut dir = match tokio::fs::read_dir(&path).await {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(CacheError::ReadError(path, e)),
+        };
+
+        let mut image_ids = Vec::new();
+        while let Some(entry) = dir.next_entry().await? {
+            if entry.file_type().await?.is_dir() {
+                image_ids.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+
+        Ok(image_ids)
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            name: "filesystem",
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            bytes: None,
+            tiers: Vec::new(),
+        }
+    }
 }
 
 /// Errors that can occur during caching operations
@@ -205,6 +444,38 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_cache_key_with_context() {
+        let key = CacheKey::new(
+            "unlocked".to_string(),
+            "abc123".to_string(),
+            "default".to_string(),
+            ImageFormat::WebP,
+        ).with_context("f1-e2-l2".to_string());
+
+        assert_eq!(
+            key.to_path(Path::new("/cache")),
+            PathBuf::from("/cache/unlocked/abc123/default.f1-e2-l2.webp")
+        );
+        assert_eq!(key.to_string_key(), "unlocked/abc123/default.f1-e2-l2.webp");
+    }
+
+    #[tokio::test]
+    async fn test_cache_key_with_version() {
+        let key = CacheKey::new(
+            "unlocked".to_string(),
+            "abc123".to_string(),
+            "default".to_string(),
+            ImageFormat::WebP,
+        ).with_version("0123456789abcdef".to_string());
+
+        assert_eq!(key.to_string_key(), "unlocked/abc123/default.0123456789abcdef.webp");
+        assert_eq!(
+            key.with_context("f1-e2-l2".to_string()).to_string_key(),
+            "unlocked/abc123/default.0123456789abcdef.f1-e2-l2.webp"
+        );
+    }
+
     #[tokio::test]
     async fn test_filesystem_cache() {
         let temp_dir = TempDir::new().unwrap();
@@ -232,11 +503,74 @@ mod tests {
         let retrieved = cache.get(&key).await.unwrap().unwrap();
         assert_eq!(retrieved, data);
 
+        // Open for streaming
+        match cache.open(&key).await.unwrap().unwrap() {
+            MediaBody::File(_, len) => assert_eq!(len, data.len() as u64),
+            MediaBody::Memory(_) => panic!("filesystem cache should stream from disk"),
+        }
+
         // Delete
         cache.delete(&key).await.unwrap();
         assert!(!cache.exists(&key).await.unwrap());
     }
 
+    #[tokio::test]
+    async fn test_set_replaces_file() {
+        use std::io::Read;
+
+        let temp_dir = TempDir::new().unwrap();
+        let cache = FilesystemCache::new(temp_dir.path());
+
+        let key = CacheKey::new(
+            "profile".to_string(),
+            "test123".to_string(),
+            "default".to_string(),
+            ImageFormat::Jpeg,
+        );
+
+        cache.set(&key, b"old").await.unwrap();
+        let mut reader = fs::File::open(key.to_path(temp_dir.path())).unwrap();
+
+        cache.set(&key, b"new data").await.unwrap();
+
+        // An open reader keeps the old file, new readers get the new one
+        let mut old = Vec::new();
+        reader.read_to_end(&mut old).unwrap();
+        assert_eq!(old, b"old");
+        assert_eq!(cache.get(&key).await.unwrap().unwrap(), b"new data");
+
+        // No temporary files left behind
+        assert_eq!(cache.entries().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = FilesystemCache::new(temp_dir.path());
+
+        let key = |image_id: &str, variant: &str| CacheKey::new(
+            "card".to_string(),
+            image_id.to_string(),
+            variant.to_string(),
+            ImageFormat::WebP,
+        );
+
+        cache.set(&key("a", "default"), b"aaaa").await.unwrap();
+        cache.set(&key("a", "thumbnail"), b"aa").await.unwrap();
+        cache.set(&key("b", "default"), b"b").await.unwrap();
+
+        let mut entries = cache.entries().unwrap();
+        entries.sort_by_key(|entry| entry.len);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].image_id, "b");
+        assert_eq!(entries.iter().map(|entry| entry.len).sum::<u64>(), 7);
+
+        // The image directory goes with its last variant
+        cache.remove_entry(&entries[0]).unwrap();
+        assert!(!temp_dir.path().join("card").join("b").exists());
+        assert_eq!(cache.entries().unwrap().len(), 2);
+    }
+
     #[tokio::test]
     async fn test_cache_clear() {
         let temp_dir = TempDir::new().unwrap();
@@ -253,6 +587,9 @@ mod tests {
             cache.set(&key, b"data").await.unwrap();
         }
 
+        assert_eq!(cache.list_images("profile").await.unwrap().len(), 3);
+        assert!(cache.list_images("card").await.unwrap().is_empty());
+
         // Clear cache
         cache.clear().await.unwrap();
 
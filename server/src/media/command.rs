@@ -0,0 +1,88 @@
+use crate::config::Config;
+use super::storage::{self, StorageBackend, StorageError};
+
+/// `media` subcommand of the server binary
+///
+/// `card_collector media copy-originals <from> <to>` copies all originals
+/// between storage backends ("filesystem" or "s3", configured as for the
+/// server) and verifies their hashes. Switch `media_storage_backend` once
+/// it reports no mismatches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MediaCommand {
+    CopyOriginals(StorageBackend, StorageBackend),
+}
+
+impl MediaCommand {
+    /// Parse the command from the process arguments, None if the server should start normally
+    pub fn from_args(args: &[String]) -> Option<Result<Self, String>> {
+        if args.get(1).map(String::as_str) != Some("media") {
+            return None;
+        }
+
+        let command = match args.get(2).map(String::as_str) {
+            Some("copy-originals") => match (args.get(3), args.get(4)) {
+                (Some(from), Some(to)) => Self::copy_originals(from, to),
+                _ => Err(String::from("Usage: media copy-originals <filesystem|s3> <filesystem|s3>")),
+            },
+            Some(other) => Err(format!("Unknown media command: {}", other)),
+            None => Err(String::from("Usage: media copy-originals <from> <to>")),
+        };
+
+        Some(command)
+    }
+
+    fn copy_originals(from: &str, to: &str) -> Result<Self, String> {
+        let (from, to) = (from.parse()?, to.parse()?);
+        if from == to {
+            return Err(String::from("Source and target backend are the same"));
+        }
+        Ok(MediaCommand::CopyOriginals(from, to))
+    }
+
+    pub async fn run(&self, config: &Config) -> Result<(), String> {
+        match self {
+            MediaCommand::CopyOriginals(from, to) => {
+                let error = |e: StorageError| e.to_string();
+
+                let source = storage::open_store(*from, config).map_err(error)?;
+                let target = storage::open_store(*to, config).map_err(error)?;
+                source.init().await.map_err(error)?;
+                target.init().await.map_err(error)?;
+
+                let report = storage::copy_originals(source.as_ref(), target.as_ref()).await.map_err(error)?;
+                println!("-copied {}, already present {}", report.copied, report.skipped);
+
+                if !report.mismatched.is_empty() {
+                    for image_id in &report.mismatched {
+                        println!("-mismatch {}", image_id);
+                    }
+                    return Err(format!("{} originals failed verification", report.mismatched.len()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_from_args() {
+        assert_eq!(MediaCommand::from_args(&args(&["card_collector"])), None);
+        assert_eq!(MediaCommand::from_args(&args(&["card_collector", "migrate"])), None);
+        assert_eq!(
+            MediaCommand::from_args(&args(&["card_collector", "media", "copy-originals", "filesystem", "s3"])),
+            Some(Ok(MediaCommand::CopyOriginals(StorageBackend::Filesystem, StorageBackend::S3)))
+        );
+        assert!(matches!(MediaCommand::from_args(&args(&["card_collector", "media", "copy-originals", "s3", "s3"])), Some(Err(_))));
+        assert!(matches!(MediaCommand::from_args(&args(&["card_collector", "media", "copy-originals", "filesystem", "ftp"])), Some(Err(_))));
+        assert!(matches!(MediaCommand::from_args(&args(&["card_collector", "media"])), Some(Err(_))));
+    }
+}
@@ -1,10 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use std::fs;
 use serde::{Deserialize, Serialize};
+use sha2::{Sha256, Digest};
 use rocket::serde::json::serde_json;
 use super::effect_registry::EffectRegistry;
-use super::effect::ImageFormat;
+use super::effect::{ImageFormat, EffectParams, EffectError};
+use super::context::IMAGE_VARIABLES;
 
 /// A single effect specification in the effect chain
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +39,11 @@ pub struct VariantConfig {
     /// Ordered list of effects to apply
     pub effects: Vec<EffectSpec>,
 
+    /// Candidate output formats picked by the Accept header, in order of preference
+    /// Empty means the format is fixed by the effect chain
+    #[serde(default)]
+    pub formats: Vec<ImageFormat>,
+
     /// Metadata about dimensions
     pub metadata: VariantMetadata,
 
@@ -45,6 +52,48 @@ pub struct VariantConfig {
     pub breakpoint: Option<u32>,
 }
 
+impl VariantConfig {
+    /// Short hash of the effect chain and candidate formats
+    ///
+    /// Changes whenever the config would produce different output, params are
+    /// sorted so the hash is stable across restarts
+    pub fn version(&self) -> String {
+        let effects: Vec<(&str, BTreeMap<&String, &serde_json::Value>)> = self.effects
+            .iter()
+            .map(|effect| (effect.id.as_str(), effect.params.iter().collect()))
+            .collect();
+
+        let canonical = serde_json::json!({
+            "effects": effects,
+            "formats": self.formats,
+        });
+
+        let hash = Sha256::digest(canonical.to_string().as_bytes());
+        format!("{:x}", hash)[..16].to_string()
+    }
+
+    /// Whether an effect takes a "$name" param resolved from a `RenderContext`
+    ///
+    /// References to `IMAGE_VARIABLES` don't count, the variant can be
+    /// generated without them
+    pub fn needs_context(&self) -> bool {
+        self.effects
+            .iter()
+            .flat_map(|effect| effect.params.values())
+            .filter_map(|value| value.as_str().and_then(|s| s.strip_prefix('$')))
+            .any(|name| !IMAGE_VARIABLES.contains(&name))
+    }
+
+    /// Whether an effect takes "$focalX"/"$focalY" from the focal point of the image
+    pub fn uses_focal_point(&self) -> bool {
+        self.effects
+            .iter()
+            .flat_map(|effect| effect.params.values())
+            .filter_map(|value| value.as_str().and_then(|s| s.strip_prefix('$')))
+            .any(|name| IMAGE_VARIABLES.contains(&name))
+    }
+}
+
 /// Configuration for a media type (e.g., "profile", "card", "banner")
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MediaTypeConfig {
@@ -72,7 +121,7 @@ impl MediaTypeConfig {
         let config: MediaTypeConfig = serde_json::from_str(&content)
             .map_err(|e| ConfigError::ParseError(path.as_ref().to_path_buf(), e))?;
 
-        config.validate()?;
+        config.validate().map_err(|e| ConfigError::InvalidFile(path.as_ref().to_path_buf(), Box::new(e)))?;
 
         Ok(config)
     }
@@ -107,14 +156,51 @@ impl MediaTypeConfig {
         Ok(())
     }
 
-    /// Validate that all effects used in this config are registered
+    /// Validate the effect chains against the registry
+    ///
+    /// Every effect must be registered and its params must match its schema
+    /// and pass `validate_params`. Params that are "$name" references are
+    /// only checked for being declared, their values come from the render
+    /// context. The metadata dimensions must match what the last resize in
+    /// the chain produces.
     pub fn validate_effects(&self, registry: &EffectRegistry) -> Result<(), ConfigError> {
         for (variant_name, variant) in &self.variants {
-            for effect_spec in &variant.effects {
-                if !registry.has(&effect_spec.id) {
+            let mut size = None;
+
+            for (index, effect_spec) in variant.effects.iter().enumerate() {
+                let effect = registry.get(&effect_spec.id).ok_or_else(|| ConfigError::ValidationError(format!(
+                    "Unknown effect '{}' in variant '{}'",
+                    effect_spec.id, variant_name
+                )))?;
+
+                let params = EffectParams::new(effect_spec.params.clone());
+                let result = params.check_schema(effect.params()).and_then(|_| {
+                    match effect.validate_params(&params.without_references()) {
+                        Err(EffectError::MissingParameter(name)) if params.is_reference(&name) => Ok(()),
+                        result => result,
+                    }
+                });
+
+                if let Err(error) = result {
+                    return Err(ConfigError::InvalidEffect {
+                        variant: variant_name.clone(),
+                        index,
+                        effect: effect_spec.id.clone(),
+                        error,
+                    });
+                }
+
+                if let Some(output_size) = effect.output_size(&params) {
+                    size = Some(output_size);
+                }
+            }
+
+            //NOTE: without a resize the output keeps the size of the upload, so there is nothing to compare
+            if let Some((width, height)) = size {
+                if (width, height) != (variant.metadata.width, variant.metadata.height) {
                     return Err(ConfigError::ValidationError(format!(
-                        "Unknown effect '{}' in variant '{}'",
-                        effect_spec.id, variant_name
+                        "Variant '{}' metadata is {}x{} but its effects produce {}x{}",
+                        variant_name, variant.metadata.width, variant.metadata.height, width, height
                     )));
                 }
             }
@@ -168,8 +254,9 @@ pub fn load_media_types<P: AsRef<Path>>(
         if path.extension().and_then(|s| s.to_str()) == Some("json") {
             let config = MediaTypeConfig::load_from_file(&path)?;
 
-            // Validate that all effects are registered
-            config.validate_effects(registry)?;
+            // Validate effects and their params
+            config.validate_effects(registry)
+                .map_err(|e| ConfigError::InvalidFile(path.clone(), Box::new(e)))?;
 
             media_types.insert(config.name.clone(), config);
         }
@@ -188,6 +275,15 @@ pub enum ConfigError {
     FileReadError(std::path::PathBuf, std::io::Error),
     ParseError(std::path::PathBuf, serde_json::Error),
     ValidationError(String),
+    /// Params of the effect at `index` in a variant's chain are invalid
+    InvalidEffect {
+        variant: String,
+        index: usize,
+        effect: String,
+        error: EffectError,
+    },
+    /// Validation failed for a config file
+    InvalidFile(std::path::PathBuf, Box<ConfigError>),
     DirectoryNotFound(std::path::PathBuf),
     NotADirectory(std::path::PathBuf),
     DirectoryReadError(std::path::PathBuf, std::io::Error),
@@ -204,6 +300,10 @@ impl std::fmt::Display for ConfigError {
                 write!(f, "Failed to parse JSON in {}: {}", path.display(), e)
             }
             ConfigError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
+            ConfigError::InvalidEffect { variant, index, effect, error } => {
+                write!(f, "Validation error: effect {} '{}' in variant '{}': {}", index, effect, variant, error)
+            }
+            ConfigError::InvalidFile(path, e) => write!(f, "{}: {}", path.display(), e),
             ConfigError::DirectoryNotFound(path) => {
                 write!(f, "Directory not found: {}", path.display())
             }
@@ -254,6 +354,81 @@ mod tests {
         assert_eq!(config.effects.len(), 2);
         assert_eq!(config.metadata.width, 150);
         assert_eq!(config.breakpoint, Some(768));
+        assert!(config.formats.is_empty());
+    }
+
+    #[test]
+    fn test_variant_formats_deserialization() {
+        let json = r#"{
+            "effects": [{ "id": "webp" }],
+            "formats": ["avif", "webp", "jpeg"],
+            "metadata": { "width": 150, "height": 150 },
+            "breakpoint": null
+        }"#;
+
+        let config: VariantConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.formats, vec![ImageFormat::Avif, ImageFormat::WebP, ImageFormat::Jpeg]);
+    }
+
+    #[test]
+    fn test_variant_version() {
+        let json = r#"{
+            "effects": [
+                { "id": "resize_square", "params": { "size": 150 } },
+                { "id": "webp", "params": { "quality": 90, "lossless": false } }
+            ],
+            "metadata": { "width": 150, "height": 150 },
+            "breakpoint": null
+        }"#;
+
+        let config: VariantConfig = serde_json::from_str(json).unwrap();
+        let reordered: VariantConfig = serde_json::from_str(&json.replace(
+            r#""quality": 90, "lossless": false"#,
+            r#""lossless": false, "quality": 90"#,
+        )).unwrap();
+        assert_eq!(config.version(), reordered.version());
+
+        // Metadata is informational and doesn't change the output
+        let mut resized = config.clone();
+        resized.metadata.width = 300;
+        assert_eq!(config.version(), resized.version());
+
+        let changed: VariantConfig = serde_json::from_str(&json.replace("90", "80")).unwrap();
+        assert_ne!(config.version(), changed.version());
+    }
+
+    #[test]
+    fn test_variant_needs_context() {
+        let json = r#"{
+            "effects": [
+                { "id": "resize_ratio", "params": { "width": 330, "height": 516 } },
+                { "id": "overlay", "params": { "path": "$frame" } }
+            ],
+            "metadata": { "width": 330, "height": 516 },
+            "breakpoint": null
+        }"#;
+
+        let config: VariantConfig = serde_json::from_str(json).unwrap();
+        assert!(config.needs_context());
+        assert!(!config.uses_focal_point());
+
+        let fixed: VariantConfig = serde_json::from_str(&json.replace("$frame", "static/frame.png")).unwrap();
+        assert!(!fixed.needs_context());
+    }
+
+    #[test]
+    fn test_focal_point_needs_no_context() {
+        let json = r#"{
+            "effects": [
+                { "id": "smart_crop", "params": { "width": 330, "height": 516, "focalX": "$focalX", "focalY": "$focalY" } }
+            ],
+            "metadata": { "width": 330, "height": 516 },
+            "breakpoint": null
+        }"#;
+
+        let config: VariantConfig = serde_json::from_str(json).unwrap();
+        assert!(!config.needs_context());
+        assert!(config.uses_focal_point());
     }
 
     #[test]
@@ -294,4 +469,66 @@ mod tests {
         let config: MediaTypeConfig = serde_json::from_str(json).unwrap();
         assert!(config.validate().is_err());
     }
+
+    fn media_type(effects: &str, width: u32, height: u32) -> MediaTypeConfig {
+        let json = format!(r#"{{
+            "name": "card",
+            "defaultVariant": "default",
+            "variants": {{
+                "default": {{
+                    "effects": {},
+                    "metadata": {{ "width": {}, "height": {} }},
+                    "breakpoint": null
+                }}
+            }}
+        }}"#, effects, width, height);
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_validate_effect_params() {
+        let registry = EffectRegistry::new();
+
+        let valid = media_type(r#"[{ "id": "resize_ratio", "params": { "width": 330, "height": 516 } }, { "id": "webp", "params": { "quality": 90 } }]"#, 330, 516);
+        assert!(valid.validate_effects(&registry).is_ok());
+
+        // Typo in a param name
+        let typo = media_type(r#"[{ "id": "resize_ratio", "params": { "width": 330, "height": 516 } }, { "id": "webp", "params": { "qualty": 90 } }]"#, 330, 516);
+        let error = typo.validate_effects(&registry).unwrap_err();
+        assert!(matches!(&error, ConfigError::InvalidEffect { index: 1, effect, .. } if effect == "webp"));
+        assert!(error.to_string().contains("qualty"));
+
+        // Wrong type
+        let wrong_type = media_type(r#"[{ "id": "resize_square", "params": { "size": "500" } }]"#, 500, 500);
+        assert!(matches!(wrong_type.validate_effects(&registry), Err(ConfigError::InvalidEffect { index: 0, .. })));
+
+        // Out of range
+        let out_of_range = media_type(r#"[{ "id": "resize_square", "params": { "size": 500 } }, { "id": "jpeg", "params": { "quality": 0 } }]"#, 500, 500);
+        assert!(matches!(out_of_range.validate_effects(&registry), Err(ConfigError::InvalidEffect { index: 1, .. })));
+
+        // References are resolved at render time
+        let references = media_type(r#"[{ "id": "resize_square", "params": { "size": 500 } }, { "id": "overlay", "params": { "path": "$frame", "opacity": "$opacity" } }]"#, 500, 500);
+        assert!(references.validate_effects(&registry).is_ok());
+    }
+
+    #[test]
+    fn test_validate_metadata_dimensions() {
+        let registry = EffectRegistry::new();
+
+        let mismatch = media_type(r#"[{ "id": "resize_square", "params": { "size": 500 } }]"#, 500, 300);
+        assert!(matches!(mismatch.validate_effects(&registry), Err(ConfigError::ValidationError(_))));
+
+        // The last resize wins
+        let resized_twice = media_type(r#"[{ "id": "resize_square", "params": { "size": 500 } }, { "id": "resize_ratio", "params": { "width": 200, "height": 100 } }]"#, 200, 100);
+        assert!(resized_twice.validate_effects(&registry).is_ok());
+    }
+
+    #[test]
+    fn test_load_shipped_media_types() {
+        let registry = EffectRegistry::new();
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/media-types");
+
+        let media_types = load_media_types(dir, &registry).unwrap();
+        assert!(media_types.contains_key("card"));
+    }
 }
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use rocket::serde::json::serde_json;
+
+/// Variables set from values stored with the image itself, see `with_focal_point`
+///
+/// Unlike entity values they don't stop a variant from being pregenerated,
+/// without them the effects fall back to their defaults
+pub const IMAGE_VARIABLES: [&str; 2] = ["focalX", "focalY"];
+
+/// Point of an image a crop is centered on, relative to its size (0..1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FocalPoint {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Per-image values for effect params written as "$name" in a media type config
+///
+/// Used by compositing pipelines where part of the effect chain depends on the
+/// rendered entity (e.g. the frame and level effect of an unlocked card).
+///
+/// The default context has no values and an empty tag, variables are dropped
+#[derive(Debug, Clone, Default)]
+pub struct RenderContext {
+    /// Identifies the rendered values, becomes part of the cache key
+    tag: String,
+
+    values: HashMap<String, serde_json::Value>,
+}
+
+impl RenderContext {
+    /// Create an empty context
+    ///
+    /// The tag has to change whenever any of the values change, otherwise stale
+    /// cached images are served
+    pub fn new(tag: String) -> Self {
+        Self {
+            tag,
+            values: HashMap::new(),
+        }
+    }
+
+    /// Set a value, None leaves the variable unresolved
+    pub fn with<V: Into<serde_json::Value>>(mut self, name: &str, value: Option<V>) -> Self {
+        if let Some(value) = value {
+            self.values.insert(name.to_string(), value.into());
+        }
+        self
+    }
+
+    /// Set "$focalX"/"$focalY" from the focal point of the image, if it has one
+    ///
+    /// The point is rounded to 1/1000 and appended to the tag
+    pub fn with_focal_point(mut self, focal_point: Option<FocalPoint>) -> Self {
+        if let Some(focal_point) = focal_point {
+            let x = (focal_point.x.clamp(0.0, 1.0) * 1000.0).round() as u32;
+            let y = (focal_point.y.clamp(0.0, 1.0) * 1000.0).round() as u32;
+
+            if !self.tag.is_empty() {
+                self.tag.push('-');
+            }
+            self.tag.push_str(&format!("fp{}-{}", x, y));
+
+            self = self
+                .with("focalX", Some(x as f32 / 1000.0))
+                .with("focalY", Some(y as f32 / 1000.0));
+        }
+        self
+    }
+
+    /// Tag used in the cache key
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Replace "$name" params with their context values
+    ///
+    /// Params referencing an unset value are dropped, so effects can treat
+    /// them as missing (e.g. an overlay without a path does nothing)
+    pub fn resolve(&self, params: &HashMap<String, serde_json::Value>) -> HashMap<String, serde_json::Value> {
+        params
+            .iter()
+            .filter_map(|(key, value)| {
+                match value.as_str().and_then(|s| s.strip_prefix('$')) {
+                    Some(name) => self.values.get(name).map(|v| (key.clone(), v.clone())),
+                    None => Some((key.clone(), value.clone())),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let context = RenderContext::new("tag".to_string())
+            .with("frame", Some("frame.png"))
+            .with::<f32>("opacity", None);
+
+        let mut params = HashMap::new();
+        params.insert("path".to_string(), serde_json::json!("$frame"));
+        params.insert("opacity".to_string(), serde_json::json!("$opacity"));
+        params.insert("scale".to_string(), serde_json::json!(2));
+
+        let resolved = context.resolve(&params);
+        assert_eq!(resolved.get("path").unwrap(), "frame.png");
+        assert!(!resolved.contains_key("opacity"));
+        assert_eq!(resolved.get("scale").unwrap().as_u64(), Some(2));
+    }
+
+    #[test]
+    fn test_focal_point() {
+        let context = RenderContext::default()
+            .with_focal_point(Some(FocalPoint { x: 0.25, y: 0.6667 }));
+        assert_eq!(context.tag(), "fp250-667");
+
+        let mut params = HashMap::new();
+        params.insert("focalX".to_string(), serde_json::json!("$focalX"));
+        let resolved = context.resolve(&params);
+        assert_eq!(resolved.get("focalX").unwrap().as_f64(), Some(0.25));
+
+        let context = RenderContext::new("card".to_string())
+            .with_focal_point(Some(FocalPoint { x: 0.5, y: 0.5 }));
+        assert_eq!(context.tag(), "card-fp500-500");
+
+        // Without a focal point the default cache entries are used
+        assert_eq!(RenderContext::default().with_focal_point(None).tag(), "");
+    }
+}
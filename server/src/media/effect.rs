@@ -2,9 +2,11 @@ use std::collections::HashMap;
 use std::fmt;
 use opencv::core::Mat;
 use rocket::serde::json::serde_json;
+use serde::{Deserialize, Serialize};
 
 /// Supported image formats
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ImageFormat {
     Jpeg,
     Png,
@@ -55,6 +57,55 @@ impl fmt::Display for ImageFormat {
     }
 }
 
+/// JSON type of an effect parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    Integer,
+    Number,
+    Bool,
+    String,
+}
+
+impl ParamKind {
+    fn matches(&self, value: &serde_json::Value) -> bool {
+        match self {
+            ParamKind::Integer => value.is_i64() || value.is_u64(),
+            ParamKind::Number => value.is_number(),
+            ParamKind::Bool => value.is_boolean(),
+            ParamKind::String => value.is_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParamKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamKind::Integer => write!(f, "an integer"),
+            ParamKind::Number => write!(f, "a number"),
+            ParamKind::Bool => write!(f, "a boolean"),
+            ParamKind::String => write!(f, "a string"),
+        }
+    }
+}
+
+/// A parameter accepted by an effect
+#[derive(Debug, Clone, Copy)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub required: bool,
+}
+
+impl ParamSpec {
+    pub const fn required(name: &'static str, kind: ParamKind) -> Self {
+        Self { name, kind, required: true }
+    }
+
+    pub const fn optional(name: &'static str, kind: ParamKind) -> Self {
+        Self { name, kind, required: false }
+    }
+}
+
 /// Parameters passed to image effects
 #[derive(Debug, Clone)]
 pub struct EffectParams {
@@ -109,6 +160,55 @@ impl EffectParams {
             .map(|v| v.to_string())
             .ok_or_else(|| EffectError::MissingParameter(key.to_string()))
     }
+
+    /// Get a "#rrggbb" color parameter as BGR, the channel order of OpenCV
+    pub fn get_color(&self, key: &str) -> Result<[u8; 3], EffectError> {
+        let value = self.get_string(key)?;
+        let invalid = || EffectError::InvalidParameter(format!("{} must be a color like #ff8800", key));
+
+        let hex = value.strip_prefix('#').filter(|hex| hex.len() == 6).ok_or_else(invalid)?;
+        let channel = |i: usize| hex.get(i..i + 2).and_then(|c| u8::from_str_radix(c, 16).ok()).ok_or_else(invalid);
+
+        Ok([channel(4)?, channel(2)?, channel(0)?])
+    }
+
+    /// Whether a parameter is a "$name" reference resolved from a `RenderContext`
+    pub fn is_reference(&self, key: &str) -> bool {
+        matches!(self.params.get(key).and_then(|v| v.as_str()), Some(s) if s.starts_with('$'))
+    }
+
+    /// Copy without "$name" references, for validating the fixed params only
+    pub fn without_references(&self) -> Self {
+        let params = self.params
+            .iter()
+            .filter(|(key, _)| !self.is_reference(key))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        Self { params }
+    }
+
+    /// Check names and types against an effect's schema
+    ///
+    /// References are accepted for any type, they are only known at render time
+    pub fn check_schema(&self, schema: &[ParamSpec]) -> Result<(), EffectError> {
+        if let Some(key) = self.params.keys().find(|key| !schema.iter().any(|spec| spec.name == key.as_str())) {
+            return Err(EffectError::InvalidParameter(format!("unknown parameter '{}'", key)));
+        }
+
+        for spec in schema {
+            match self.params.get(spec.name) {
+                None if spec.required => return Err(EffectError::MissingParameter(spec.name.to_string())),
+                Some(value) if !self.is_reference(spec.name) && !spec.kind.matches(value) => {
+                    return Err(EffectError::InvalidParameter(format!(
+                        "{} must be {}", spec.name, spec.kind
+                    )));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
 }
 
 /// Errors that can occur during effect application
@@ -158,8 +258,22 @@ pub trait ImageEffect: Send + Sync {
         None // Default: effect doesn't change format
     }
 
-    /// Validate parameters (optional)
+    /// Parameters this effect accepts
+    ///
+    /// Checked when media types are loaded, unknown parameters are rejected
+    fn params(&self) -> &'static [ParamSpec] {
+        &[]
+    }
+
+    /// Validate parameter values (optional), called after the schema check
     fn validate_params(&self, _params: &EffectParams) -> Result<(), EffectError> {
         Ok(())
     }
+
+    /// Width and height of the output if this effect sets them
+    ///
+    /// None means the effect keeps the size of its input
+    fn output_size(&self, _params: &EffectParams) -> Option<(u32, u32)> {
+        None
+    }
 }
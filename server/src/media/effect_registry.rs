@@ -17,11 +17,25 @@ impl EffectRegistry {
         // Register transformation effects
         registry.register(Arc::new(crate::media::effects::ResizeSquareEffect));
         registry.register(Arc::new(crate::media::effects::ResizeRatioEffect));
+        registry.register(Arc::new(crate::media::effects::SmartCropEffect));
+
+        // Register compositing effects
+        registry.register(Arc::new(crate::media::effects::OverlayEffect));
+        registry.register(Arc::new(crate::media::effects::TextEffect));
+        registry.register(Arc::new(crate::media::effects::RoundedCornersEffect));
+        registry.register(Arc::new(crate::media::effects::BorderEffect));
+        registry.register(Arc::new(crate::media::effects::WatermarkEffect));
+
+        // Register filter effects
+        registry.register(Arc::new(crate::media::effects::BlurEffect));
+        registry.register(Arc::new(crate::media::effects::GrayscaleEffect));
+        registry.register(Arc::new(crate::media::effects::SharpenEffect));
 
         // Register format conversion effects
         registry.register(Arc::new(crate::media::effects::WebPEffect));
         registry.register(Arc::new(crate::media::effects::JpegEffect));
         registry.register(Arc::new(crate::media::effects::PngEffect));
+        registry.register(Arc::new(crate::media::effects::AvifEffect));
 
         registry
     }
@@ -0,0 +1,162 @@
+use opencv::core::Mat;
+use opencv::prelude::*;
+use ravif::{Encoder, Img, RGB8, RGBA8};
+use crate::media::effect::{ImageEffect, EffectParams, EffectError, ParamKind, ParamSpec, ImageFormat};
+
+/// Convert image to AVIF format
+///
+/// This effect doesn't modify the image pixels - it just marks that the output
+/// should be encoded as AVIF. The actual encoding happens in the MediaManager,
+/// using `encode` since OpenCV has no AVIF writer on our target systems.
+pub struct AvifEffect;
+
+impl ImageEffect for AvifEffect {
+    fn id(&self) -> &'static str {
+        "avif"
+    }
+
+    fn apply(&self, image: Mat, _params: &EffectParams) -> Result<Mat, EffectError> {
+        // No pixel transformation - just pass through
+        // The format change is signaled via output_format()
+        Ok(image)
+    }
+
+    fn output_format(&self) -> Option<ImageFormat> {
+        Some(ImageFormat::Avif)
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &[
+            ParamSpec::optional("quality", ParamKind::Integer),
+            ParamSpec::optional("speed", ParamKind::Integer),
+        ]
+    }
+
+    fn validate_params(&self, params: &EffectParams) -> Result<(), EffectError> {
+        // Quality parameter is optional, but if provided should be 1-100
+        if let Ok(quality) = params.get_u32("quality") {
+            if !(1..=100).contains(&quality) {
+                return Err(EffectError::InvalidParameter(
+                    "quality must be between 1 and 100".to_string()
+                ));
+            }
+        }
+
+        // Speed parameter is optional, 1 is slowest/smallest, 10 fastest
+        if let Ok(speed) = params.get_u32("speed") {
+            if !(1..=10).contains(&speed) {
+                return Err(EffectError::InvalidParameter(
+                    "speed must be between 1 and 10".to_string()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Encode a BGR or BGRA image as AVIF
+pub fn encode(image: &Mat, quality: u32, speed: u32) -> Result<Vec<u8>, EffectError> {
+    let continuous;
+    let image = if image.is_continuous() {
+        image
+    } else {
+        continuous = image.try_clone()?;
+        &continuous
+    };
+
+    let size = image.size()?;
+    let (width, height) = (size.width as usize, size.height as usize);
+    let bytes = image.data_bytes()?;
+
+    let encoder = Encoder::new()
+        .with_quality(quality.clamp(1, 100) as f32)
+        .with_speed(speed.clamp(1, 10) as u8);
+
+    let encoded = match image.channels() {
+        3 => {
+            let pixels: Vec<RGB8> = bytes
+                .chunks_exact(3)
+                .map(|p| RGB8::new(p[2], p[1], p[0]))
+                .collect();
+            encoder.encode_rgb(Img::new(pixels.as_slice(), width, height))
+        }
+        4 => {
+            let pixels: Vec<RGBA8> = bytes
+                .chunks_exact(4)
+                .map(|p| RGBA8::new(p[2], p[1], p[0], p[3]))
+                .collect();
+            encoder.encode_rgba(Img::new(pixels.as_slice(), width, height))
+        }
+        channels => {
+            return Err(EffectError::ProcessingError(format!(
+                "AVIF encoding expects 3 or 4 channels, got {}", channels
+            )));
+        }
+    };
+
+    encoded
+        .map(|encoded| encoded.avif_file)
+        .map_err(|e| EffectError::ProcessingError(format!("AVIF encoding failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{Scalar, CV_8UC3, CV_8UC4};
+    use std::collections::HashMap;
+
+    /// ISO-BMFF files start with the ftyp box, AVIF uses the "avif" major brand
+    fn assert_avif(bytes: &[u8]) {
+        assert!(bytes.len() > 12);
+        assert_eq!(&bytes[4..8], b"ftyp");
+        assert_eq!(&bytes[8..12], b"avif");
+
+        let parsed = avif_parse::read_avif(&mut &bytes[..]).unwrap();
+        assert!(!parsed.primary_item.is_empty());
+    }
+
+    #[test]
+    fn test_output_format() {
+        let effect = AvifEffect;
+        assert_eq!(effect.output_format(), Some(ImageFormat::Avif));
+    }
+
+    #[test]
+    fn test_encode_bgr() {
+        let image = Mat::new_rows_cols_with_default(16, 24, CV_8UC3, Scalar::new(255.0, 0.0, 0.0, 0.0)).unwrap();
+        assert_avif(&encode(&image, 80, 10).unwrap());
+    }
+
+    #[test]
+    fn test_encode_bgra() {
+        let image = Mat::new_rows_cols_with_default(16, 16, CV_8UC4, Scalar::new(0.0, 255.0, 0.0, 128.0)).unwrap();
+        let bytes = encode(&image, 80, 10).unwrap();
+        assert_avif(&bytes);
+
+        let parsed = avif_parse::read_avif(&mut &bytes[..]).unwrap();
+        assert!(parsed.alpha_item.is_some());
+    }
+
+    #[test]
+    fn test_validate_params() {
+        let effect = AvifEffect;
+
+        // Valid: no params
+        let params = EffectParams::new(HashMap::new());
+        assert!(effect.validate_params(&params).is_ok());
+
+        // Valid: quality and speed in range
+        let mut params_map = HashMap::new();
+        params_map.insert("quality".to_string(), serde_json::json!(80));
+        params_map.insert("speed".to_string(), serde_json::json!(4));
+        let params = EffectParams::new(params_map);
+        assert!(effect.validate_params(&params).is_ok());
+
+        // Invalid: speed out of range
+        let mut params_map = HashMap::new();
+        params_map.insert("speed".to_string(), serde_json::json!(0));
+        let params = EffectParams::new(params_map);
+        assert!(effect.validate_params(&params).is_err());
+    }
+}
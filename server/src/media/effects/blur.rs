@@ -0,0 +1,86 @@
+use opencv::core::{Mat, Size};
+use opencv::imgproc::gaussian_blur_def;
+use crate::media::effect::{ImageEffect, EffectParams, EffectError, ParamKind, ParamSpec};
+
+/// Gaussian blur, e.g. for backgrounds behind a card
+///
+/// The kernel size is derived from `sigma` (in pixels of the current image)
+pub struct BlurEffect;
+
+impl ImageEffect for BlurEffect {
+    fn id(&self) -> &'static str {
+        "blur"
+    }
+
+    fn apply(&self, image: Mat, params: &EffectParams) -> Result<Mat, EffectError> {
+        self.validate_params(params)?;
+        let sigma = params.get_f32("sigma")? as f64;
+
+        let mut blurred = Mat::default();
+        gaussian_blur_def(&image, &mut blurred, Size::new(0, 0), sigma)?;
+
+        Ok(blurred)
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &[
+            ParamSpec::required("sigma", ParamKind::Number),
+        ]
+    }
+
+    fn validate_params(&self, params: &EffectParams) -> Result<(), EffectError> {
+        let sigma = params.get_f32("sigma")?;
+
+        if sigma <= 0.0 || sigma > 100.0 {
+            return Err(EffectError::InvalidParameter(
+                "sigma must be between 0 and 100".to_string()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{Rect, Scalar, CV_8UC3};
+    use opencv::prelude::*;
+    use rocket::serde::json::serde_json;
+    use std::collections::HashMap;
+
+    fn params(sigma: serde_json::Value) -> EffectParams {
+        let mut params_map = HashMap::new();
+        params_map.insert("sigma".to_string(), sigma);
+        EffectParams::new(params_map)
+    }
+
+    #[test]
+    fn test_blurs_edges() {
+        let effect = BlurEffect;
+
+        // Black left half, white right half
+        let mut image = Mat::new_rows_cols_with_default(10, 20, CV_8UC3, Scalar::all(0.0)).unwrap();
+        Mat::roi_mut(&mut image, Rect::new(10, 0, 10, 10))
+            .unwrap()
+            .set_to_def(&Scalar::all(255.0))
+            .unwrap();
+
+        let result = effect.apply(image, &params(serde_json::json!(2.0))).unwrap();
+        assert_eq!(result.size().unwrap(), Size::new(20, 10));
+
+        // The edge gets gray, far away pixels keep their color
+        let row: Vec<u8> = (0..20).map(|x| result.at_2d::<opencv::core::Vec3b>(5, x).unwrap()[0]).collect();
+        assert!(row[9] > 0 && row[9] < 255 && row[10] > 0 && row[10] < 255);
+        assert!(row[0] < 5 && row[19] > 250);
+    }
+
+    #[test]
+    fn test_validate_params() {
+        let effect = BlurEffect;
+
+        assert!(effect.validate_params(&params(serde_json::json!(1.5))).is_ok());
+        assert!(effect.validate_params(&params(serde_json::json!(0))).is_err());
+        assert!(effect.validate_params(&EffectParams::new(HashMap::new())).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use opencv::core::Mat;
+use opencv::prelude::*;
+use crate::media::effect::{ImageEffect, EffectParams, EffectError, ParamKind, ParamSpec};
+
+/// Solid or gradient border drawn over the edge of the image
+///
+/// The border is inset so the size stays the same. `width` is in pixels,
+/// `color` is "#rrggbb" (default black). With `gradientTo` the border fades
+/// from `color` at the top to `gradientTo` at the bottom.
+pub struct BorderEffect;
+
+impl ImageEffect for BorderEffect {
+    fn id(&self) -> &'static str {
+        "border"
+    }
+
+    fn apply(&self, image: Mat, params: &EffectParams) -> Result<Mat, EffectError> {
+        self.validate_params(params)?;
+        let border = params.get_u32("width")? as usize;
+        let top = params.get_color("color").unwrap_or([0, 0, 0]);
+        let bottom = params.get_color("gradientTo").unwrap_or(top);
+
+        let channels = image.channels() as usize;
+        if !(channels == 3 || channels == 4) {
+            return Err(EffectError::ProcessingError(
+                "border expects a BGR or BGRA image".to_string()
+            ));
+        }
+
+        let mut image = if image.is_continuous() { image } else { image.try_clone()? };
+        let size = image.size()?;
+        let (width, height) = (size.width as usize, size.height as usize);
+
+        for (index, pixel) in image.data_bytes_mut()?.chunks_exact_mut(channels).enumerate() {
+            let (x, y) = (index % width, index / width);
+            let in_border = x < border || y < border || x >= width.saturating_sub(border) || y >= height.saturating_sub(border);
+
+            if in_border {
+                let t = if height > 1 { y as f32 / (height - 1) as f32 } else { 0.0 };
+                for (value, (from, to)) in pixel[..3].iter_mut().zip(top.iter().zip(&bottom)) {
+                    *value = (*from as f32 * (1.0 - t) + *to as f32 * t).round() as u8;
+                }
+            }
+        }
+
+        Ok(image)
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &[
+            ParamSpec::required("width", ParamKind::Integer),
+            ParamSpec::optional("color", ParamKind::String),
+            ParamSpec::optional("gradientTo", ParamKind::String),
+        ]
+    }
+
+    fn validate_params(&self, params: &EffectParams) -> Result<(), EffectError> {
+        let width = params.get_i32("width")?;
+
+        if width <= 0 {
+            return Err(EffectError::InvalidParameter(
+                "width must be positive".to_string()
+            ));
+        }
+
+        for key in ["color", "gradientTo"] {
+            if let Err(EffectError::InvalidParameter(msg)) = params.get_color(key) {
+                return Err(EffectError::InvalidParameter(msg));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{Scalar, Vec3b, CV_8UC3};
+    use rocket::serde::json::serde_json;
+    use std::collections::HashMap;
+
+    fn params(values: serde_json::Value) -> EffectParams {
+        let params_map: HashMap<String, serde_json::Value> = serde_json::from_value(values).unwrap();
+        EffectParams::new(params_map)
+    }
+
+    #[test]
+    fn test_solid_border() {
+        let effect = BorderEffect;
+        let image = Mat::new_rows_cols_with_default(10, 10, CV_8UC3, Scalar::all(0.0)).unwrap();
+
+        let result = effect.apply(image, &params(serde_json::json!({ "width": 2, "color": "#ff8000" }))).unwrap();
+        let pixel = |x, y| result.at_2d::<Vec3b>(y, x).unwrap().0;
+
+        // BGR order
+        assert_eq!(pixel(0, 0), [0, 128, 255]);
+        assert_eq!(pixel(1, 5), [0, 128, 255]);
+        assert_eq!(pixel(9, 9), [0, 128, 255]);
+        assert_eq!(pixel(2, 2), [0, 0, 0]);
+        assert_eq!(pixel(7, 7), [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_gradient_border() {
+        let effect = BorderEffect;
+        let image = Mat::new_rows_cols_with_default(11, 11, CV_8UC3, Scalar::all(0.0)).unwrap();
+
+        let result = effect.apply(image, &params(serde_json::json!({
+            "width": 1, "color": "#000000", "gradientTo": "#ffffff"
+        }))).unwrap();
+        let pixel = |x, y| result.at_2d::<Vec3b>(y, x).unwrap().0;
+
+        assert_eq!(pixel(0, 0), [0, 0, 0]);
+        assert_eq!(pixel(0, 5), [128, 128, 128]);
+        assert_eq!(pixel(10, 10), [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_validate_params() {
+        let effect = BorderEffect;
+
+        assert!(effect.validate_params(&params(serde_json::json!({ "width": 4 }))).is_ok());
+        assert!(effect.validate_params(&params(serde_json::json!({ "width": 4, "color": "#00ff00", "gradientTo": "#0000ff" }))).is_ok());
+        assert!(effect.validate_params(&params(serde_json::json!({ "width": 0 }))).is_err());
+        assert!(effect.validate_params(&params(serde_json::json!({ "width": 4, "color": "green" }))).is_err());
+        assert!(effect.validate_params(&params(serde_json::json!({ "width": 4, "gradientTo": "#12345" }))).is_err());
+    }
+}
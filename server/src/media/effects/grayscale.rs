@@ -0,0 +1,113 @@
+use opencv::core::Mat;
+use opencv::prelude::*;
+use crate::media::effect::{ImageEffect, EffectParams, EffectError, ParamKind, ParamSpec};
+
+/// Desaturate the image, e.g. for locked or not yet owned cards
+///
+/// `amount` (default 1.0) blends between the original (0.0) and full
+/// grayscale (1.0). The channel count is kept, alpha is left untouched.
+pub struct GrayscaleEffect;
+
+impl ImageEffect for GrayscaleEffect {
+    fn id(&self) -> &'static str {
+        "grayscale"
+    }
+
+    fn apply(&self, image: Mat, params: &EffectParams) -> Result<Mat, EffectError> {
+        self.validate_params(params)?;
+        let amount = params.get_f32("amount").unwrap_or(1.0);
+
+        let mut image = if image.is_continuous() { image } else { image.try_clone()? };
+        desaturate(&mut image, amount)?;
+
+        Ok(image)
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &[
+            ParamSpec::optional("amount", ParamKind::Number),
+        ]
+    }
+
+    fn validate_params(&self, params: &EffectParams) -> Result<(), EffectError> {
+        if let Ok(amount) = params.get_f32("amount") {
+            if !(0.0..=1.0).contains(&amount) {
+                return Err(EffectError::InvalidParameter(
+                    "amount must be between 0 and 1".to_string()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Blend each BGR(A) pixel towards its luma (BT.601)
+fn desaturate(image: &mut Mat, amount: f32) -> Result<(), EffectError> {
+    let channels = image.channels() as usize;
+
+    if !(channels == 3 || channels == 4) {
+        return Err(EffectError::ProcessingError(
+            "grayscale expects a BGR or BGRA image".to_string()
+        ));
+    }
+
+    for pixel in image.data_bytes_mut()?.chunks_exact_mut(channels) {
+        let luma = 0.114 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.299 * pixel[2] as f32;
+
+        for value in pixel[..3].iter_mut() {
+            *value = (*value as f32 * (1.0 - amount) + luma * amount).round() as u8;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{Scalar, CV_8UC3, CV_8UC4};
+    use rocket::serde::json::serde_json;
+    use std::collections::HashMap;
+
+    fn params(amount: Option<serde_json::Value>) -> EffectParams {
+        let mut params_map = HashMap::new();
+        if let Some(amount) = amount {
+            params_map.insert("amount".to_string(), amount);
+        }
+        EffectParams::new(params_map)
+    }
+
+    #[test]
+    fn test_grayscale() {
+        let effect = GrayscaleEffect;
+
+        // Pure red in BGR
+        let image = Mat::new_rows_cols_with_default(2, 2, CV_8UC3, Scalar::new(0.0, 0.0, 255.0, 0.0)).unwrap();
+        let result = effect.apply(image, &params(None)).unwrap();
+
+        // 0.299 * 255
+        assert!(result.data_bytes().unwrap().iter().all(|b| *b == 76));
+    }
+
+    #[test]
+    fn test_partial_amount_keeps_alpha() {
+        let effect = GrayscaleEffect;
+
+        let image = Mat::new_rows_cols_with_default(2, 2, CV_8UC4, Scalar::new(0.0, 0.0, 255.0, 128.0)).unwrap();
+        let result = effect.apply(image, &params(Some(serde_json::json!(0.5)))).unwrap();
+
+        for pixel in result.data_bytes().unwrap().chunks_exact(4) {
+            assert_eq!(pixel, &[38, 38, 166, 128]);
+        }
+    }
+
+    #[test]
+    fn test_validate_params() {
+        let effect = GrayscaleEffect;
+
+        assert!(effect.validate_params(&params(None)).is_ok());
+        assert!(effect.validate_params(&params(Some(serde_json::json!(0.3)))).is_ok());
+        assert!(effect.validate_params(&params(Some(serde_json::json!(1.5)))).is_err());
+    }
+}
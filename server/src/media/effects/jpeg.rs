@@ -1,5 +1,5 @@
 use opencv::core::Mat;
-use crate::media::effect::{ImageEffect, EffectParams, EffectError, ImageFormat};
+use crate::media::effect::{ImageEffect, EffectParams, EffectError, ParamKind, ParamSpec, ImageFormat};
 
 /// Convert image to JPEG format
 ///
@@ -22,6 +22,12 @@ impl ImageEffect for JpegEffect {
         Some(ImageFormat::Jpeg)
     }
 
+    fn params(&self) -> &'static [ParamSpec] {
+        &[
+            ParamSpec::optional("quality", ParamKind::Integer),
+        ]
+    }
+
     fn validate_params(&self, params: &EffectParams) -> Result<(), EffectError> {
         // Quality parameter is optional, but if provided should be 1-100
         if let Ok(quality) = params.get_u32("quality") {
@@ -1,20 +1,39 @@
 // Image transformation effects
 pub mod resize_square;
 pub mod resize_ratio;
+pub mod smart_crop;
+
+// Compositing effects
+pub mod overlay;
+pub mod text;
+pub mod rounded_corners;
+pub mod border;
+pub mod watermark;
 
 // Format conversion effects
 pub mod webp;
 pub mod jpeg;
 pub mod png;
+pub mod avif;
 
-// Future effects
-// pub mod sharpen;
-// pub mod blur;
-// pub mod grayscale;
+// Filter effects
+pub mod blur;
+pub mod grayscale;
+pub mod sharpen;
 
 // Re-export for convenience
 pub use resize_square::ResizeSquareEffect;
 pub use resize_ratio::ResizeRatioEffect;
+pub use smart_crop::SmartCropEffect;
+pub use overlay::OverlayEffect;
+pub use text::TextEffect;
+pub use rounded_corners::RoundedCornersEffect;
+pub use border::BorderEffect;
+pub use watermark::WatermarkEffect;
+pub use blur::BlurEffect;
+pub use grayscale::GrayscaleEffect;
+pub use sharpen::SharpenEffect;
 pub use webp::WebPEffect;
 pub use jpeg::JpegEffect;
 pub use png::PngEffect;
+pub use avif::AvifEffect;
@@ -0,0 +1,142 @@
+use opencv::core::Mat;
+use opencv::imgcodecs::{imread, IMREAD_UNCHANGED};
+use opencv::imgproc::{resize, INTER_LINEAR};
+use opencv::prelude::*;
+use crate::media::effect::{ImageEffect, EffectParams, EffectError, ParamKind, ParamSpec};
+
+/// Blend an image file over the whole image, e.g. a card frame or level effect
+///
+/// The overlay is stretched to the image size, its alpha channel (if any) is
+/// multiplied with the opacity. Without a path the image is passed through, so
+/// optional layers can be left unresolved in a render context.
+pub struct OverlayEffect;
+
+impl ImageEffect for OverlayEffect {
+    fn id(&self) -> &'static str {
+        "overlay"
+    }
+
+    fn apply(&self, image: Mat, params: &EffectParams) -> Result<Mat, EffectError> {
+        let path = match params.get_string("path") {
+            Ok(path) => path,
+            Err(_) => return Ok(image),
+        };
+        let opacity = params.get_f32("opacity").unwrap_or(1.0);
+
+        if !(0.0..=1.0).contains(&opacity) {
+            return Err(EffectError::InvalidParameter(
+                "opacity must be between 0 and 1".to_string()
+            ));
+        }
+
+        let overlay = imread(&path, IMREAD_UNCHANGED)?;
+        if overlay.empty() {
+            return Err(EffectError::ProcessingError(format!(
+                "Failed to load overlay {}", path
+            )));
+        }
+
+        // Stretch overlay to image size
+        let mut resized = Mat::default();
+        resize(&overlay, &mut resized, image.size()?, 0.0, 0.0, INTER_LINEAR)?;
+
+        let mut base = if image.is_continuous() { image } else { image.try_clone()? };
+        blend(&mut base, &resized, opacity)?;
+
+        Ok(base)
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &[
+            ParamSpec::optional("path", ParamKind::String),
+            ParamSpec::optional("opacity", ParamKind::Number),
+        ]
+    }
+
+    fn validate_params(&self, params: &EffectParams) -> Result<(), EffectError> {
+        if let Ok(opacity) = params.get_f32("opacity") {
+            if !(0.0..=1.0).contains(&opacity) {
+                return Err(EffectError::InvalidParameter(
+                    "opacity must be between 0 and 1".to_string()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Alpha blend a BGR or BGRA overlay onto a BGR image of the same size
+pub(crate) fn blend(base: &mut Mat, overlay: &Mat, opacity: f32) -> Result<(), EffectError> {
+    let overlay_channels = overlay.channels() as usize;
+
+    if base.channels() != 3 || !(overlay_channels == 3 || overlay_channels == 4) {
+        return Err(EffectError::ProcessingError(
+            "overlay expects a BGR image and a BGR or BGRA overlay".to_string()
+        ));
+    }
+
+    if base.size()? != overlay.size()? {
+        return Err(EffectError::ProcessingError(
+            "overlay size does not match image size".to_string()
+        ));
+    }
+
+    let overlay_bytes = overlay.data_bytes()?;
+
+    for (pixel, over) in base.data_bytes_mut()?
+        .chunks_exact_mut(3)
+        .zip(overlay_bytes.chunks_exact(overlay_channels))
+    {
+        let alpha = if overlay_channels == 4 { over[3] as f32 / 255.0 } else { 1.0 } * opacity;
+
+        for (value, over_value) in pixel.iter_mut().zip(&over[..3]) {
+            *value = (*value as f32 * (1.0 - alpha) + *over_value as f32 * alpha).round() as u8;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{Scalar, CV_8UC3, CV_8UC4};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_blend() {
+        let mut base = Mat::new_rows_cols_with_default(2, 2, CV_8UC3, Scalar::all(0.0)).unwrap();
+
+        let overlay = Mat::new_rows_cols_with_default(2, 2, CV_8UC4, Scalar::new(200.0, 200.0, 200.0, 255.0)).unwrap();
+        blend(&mut base, &overlay, 0.5).unwrap();
+        assert!(base.data_bytes().unwrap().iter().all(|b| *b == 100));
+
+        // Transparent overlay leaves the image untouched
+        let overlay = Mat::new_rows_cols_with_default(2, 2, CV_8UC4, Scalar::new(255.0, 255.0, 255.0, 0.0)).unwrap();
+        blend(&mut base, &overlay, 1.0).unwrap();
+        assert!(base.data_bytes().unwrap().iter().all(|b| *b == 100));
+    }
+
+    #[test]
+    fn test_missing_path_passes_through() {
+        let effect = OverlayEffect;
+        let image = Mat::new_rows_cols_with_default(2, 2, CV_8UC3, Scalar::all(7.0)).unwrap();
+
+        let result = effect.apply(image, &EffectParams::new(HashMap::new())).unwrap();
+        assert!(result.data_bytes().unwrap().iter().all(|b| *b == 7));
+    }
+
+    #[test]
+    fn test_validate_params() {
+        let effect = OverlayEffect;
+
+        let mut params_map = HashMap::new();
+        params_map.insert("opacity".to_string(), serde_json::json!(0.5));
+        assert!(effect.validate_params(&EffectParams::new(params_map)).is_ok());
+
+        let mut params_map = HashMap::new();
+        params_map.insert("opacity".to_string(), serde_json::json!(1.5));
+        assert!(effect.validate_params(&EffectParams::new(params_map)).is_err());
+    }
+}
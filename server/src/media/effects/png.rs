@@ -1,5 +1,5 @@
 use opencv::core::Mat;
-use crate::media::effect::{ImageEffect, EffectParams, EffectError, ImageFormat};
+use crate::media::effect::{ImageEffect, EffectParams, EffectError, ParamKind, ParamSpec, ImageFormat};
 
 /// Convert image to PNG format
 ///
@@ -22,6 +22,12 @@ impl ImageEffect for PngEffect {
         Some(ImageFormat::Png)
     }
 
+    fn params(&self) -> &'static [ParamSpec] {
+        &[
+            ParamSpec::optional("compression", ParamKind::Integer),
+        ]
+    }
+
     fn validate_params(&self, params: &EffectParams) -> Result<(), EffectError> {
         // Compression parameter is optional, but if provided should be 0-9
         if let Ok(compression) = params.get_u32("compression") {
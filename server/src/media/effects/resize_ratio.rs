@@ -1,7 +1,7 @@
 use opencv::core::{Mat, Rect, Size};
 use opencv::imgproc::{resize, INTER_LINEAR};
 use opencv::prelude::*;
-use crate::media::effect::{ImageEffect, EffectParams, EffectError, ImageFormat};
+use crate::media::effect::{ImageEffect, EffectParams, EffectError, ParamKind, ParamSpec, ImageFormat};
 
 /// Resize image to a specific aspect ratio, cropping center if needed
 pub struct ResizeRatioEffect;
@@ -76,6 +76,13 @@ impl ImageEffect for ResizeRatioEffect {
         Ok(resized)
     }
 
+    fn params(&self) -> &'static [ParamSpec] {
+        &[
+            ParamSpec::required("width", ParamKind::Integer),
+            ParamSpec::required("height", ParamKind::Integer),
+        ]
+    }
+
     fn validate_params(&self, params: &EffectParams) -> Result<(), EffectError> {
         let width = params.get_i32("width")?;
         let height = params.get_i32("height")?;
@@ -88,6 +95,10 @@ impl ImageEffect for ResizeRatioEffect {
 
         Ok(())
     }
+
+    fn output_size(&self, params: &EffectParams) -> Option<(u32, u32)> {
+        Some((params.get_u32("width").ok()?, params.get_u32("height").ok()?))
+    }
 }
 
 #[cfg(test)]
@@ -1,7 +1,7 @@
 use opencv::core::{Mat, Rect, Size};
 use opencv::imgproc::{resize, INTER_LINEAR};
 use opencv::prelude::*;
-use crate::media::effect::{ImageEffect, EffectParams, EffectError, ImageFormat};
+use crate::media::effect::{ImageEffect, EffectParams, EffectError, ParamKind, ParamSpec, ImageFormat};
 
 /// Resize image to a square, cropping center if aspect ratio doesn't match
 pub struct ResizeSquareEffect;
@@ -64,6 +64,12 @@ impl ImageEffect for ResizeSquareEffect {
         Ok(resized)
     }
 
+    fn params(&self) -> &'static [ParamSpec] {
+        &[
+            ParamSpec::required("size", ParamKind::Integer),
+        ]
+    }
+
     fn validate_params(&self, params: &EffectParams) -> Result<(), EffectError> {
         let size = params.get_i32("size")?;
         if size <= 0 {
@@ -73,6 +79,11 @@ impl ImageEffect for ResizeSquareEffect {
         }
         Ok(())
     }
+
+    fn output_size(&self, params: &EffectParams) -> Option<(u32, u32)> {
+        let size = params.get_u32("size").ok()?;
+        Some((size, size))
+    }
 }
 
 #[cfg(test)]
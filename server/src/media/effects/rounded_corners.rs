@@ -0,0 +1,137 @@
+use opencv::core::Mat;
+use opencv::imgproc::{cvt_color_def, COLOR_BGR2BGRA};
+use opencv::prelude::*;
+use crate::media::effect::{ImageEffect, EffectParams, EffectError, ParamKind, ParamSpec};
+
+/// Cut rounded corners into the alpha channel
+///
+/// `radius` is in pixels and capped at half the shorter side. The output is
+/// BGRA with anti-aliased edges, so it needs a format with alpha (png, webp,
+/// avif) and should come after effects expecting BGR like `overlay`.
+pub struct RoundedCornersEffect;
+
+impl ImageEffect for RoundedCornersEffect {
+    fn id(&self) -> &'static str {
+        "rounded_corners"
+    }
+
+    fn apply(&self, image: Mat, params: &EffectParams) -> Result<Mat, EffectError> {
+        self.validate_params(params)?;
+        let radius = params.get_u32("radius")? as f32;
+
+        let mut image = match image.channels() {
+            4 if image.is_continuous() => image,
+            4 => image.try_clone()?,
+            3 => {
+                let mut bgra = Mat::default();
+                cvt_color_def(&image, &mut bgra, COLOR_BGR2BGRA)?;
+                bgra
+            }
+            channels => return Err(EffectError::ProcessingError(format!(
+                "rounded_corners expects 3 or 4 channels, got {}", channels
+            ))),
+        };
+
+        let size = image.size()?;
+        let (width, height) = (size.width as usize, size.height as usize);
+        let radius = radius.min(width.min(height) as f32 / 2.0);
+
+        for (index, pixel) in image.data_bytes_mut()?.chunks_exact_mut(4).enumerate() {
+            let coverage = corner_coverage(index % width, index / width, width, height, radius);
+            if coverage < 1.0 {
+                pixel[3] = (pixel[3] as f32 * coverage).round() as u8;
+            }
+        }
+
+        Ok(image)
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &[
+            ParamSpec::required("radius", ParamKind::Integer),
+        ]
+    }
+
+    fn validate_params(&self, params: &EffectParams) -> Result<(), EffectError> {
+        let radius = params.get_i32("radius")?;
+
+        if radius <= 0 {
+            return Err(EffectError::InvalidParameter(
+                "radius must be positive".to_string()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// How much of a pixel lies inside the rounded rectangle, 0.0 to 1.0
+///
+/// Approximated from the distance of the pixel center to the corner circle
+fn corner_coverage(x: usize, y: usize, width: usize, height: usize, radius: f32) -> f32 {
+    let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+
+    // Center of the corner circle closest to the pixel, if it is in a corner square
+    let cx = if px < radius { radius } else if px > width as f32 - radius { width as f32 - radius } else { return 1.0 };
+    let cy = if py < radius { radius } else if py > height as f32 - radius { height as f32 - radius } else { return 1.0 };
+
+    let distance = ((px - cx).powi(2) + (py - cy).powi(2)).sqrt();
+    (radius - distance + 0.5).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{Scalar, Vec4b, CV_8UC3};
+    use rocket::serde::json::serde_json;
+    use std::collections::HashMap;
+
+    fn params(radius: serde_json::Value) -> EffectParams {
+        let mut params_map = HashMap::new();
+        params_map.insert("radius".to_string(), radius);
+        EffectParams::new(params_map)
+    }
+
+    #[test]
+    fn test_rounded_corners() {
+        let effect = RoundedCornersEffect;
+        let image = Mat::new_rows_cols_with_default(40, 60, CV_8UC3, Scalar::all(200.0)).unwrap();
+
+        let result = effect.apply(image, &params(serde_json::json!(10))).unwrap();
+        assert_eq!(result.channels(), 4);
+
+        let alpha = |x, y| result.at_2d::<Vec4b>(y, x).unwrap()[3];
+
+        // Corners are transparent, edges and center opaque
+        assert_eq!(alpha(0, 0), 0);
+        assert_eq!(alpha(59, 0), 0);
+        assert_eq!(alpha(0, 39), 0);
+        assert_eq!(alpha(59, 39), 0);
+        assert_eq!(alpha(30, 0), 255);
+        assert_eq!(alpha(0, 20), 255);
+        assert_eq!(alpha(30, 20), 255);
+
+        // Colors are kept
+        assert_eq!(result.at_2d::<Vec4b>(0, 0).unwrap()[0], 200);
+    }
+
+    #[test]
+    fn test_corner_coverage() {
+        assert_eq!(corner_coverage(10, 10, 20, 20, 5.0), 1.0);
+        assert_eq!(corner_coverage(0, 0, 20, 20, 5.0), 0.0);
+        assert_eq!(corner_coverage(10, 0, 20, 20, 5.0), 1.0);
+
+        // Anti-aliased edge
+        let edge = corner_coverage(1, 1, 20, 20, 5.0);
+        assert!(edge > 0.0 && edge < 1.0, "coverage {}", edge);
+    }
+
+    #[test]
+    fn test_validate_params() {
+        let effect = RoundedCornersEffect;
+
+        assert!(effect.validate_params(&params(serde_json::json!(16))).is_ok());
+        assert!(effect.validate_params(&params(serde_json::json!(0))).is_err());
+        assert!(effect.validate_params(&EffectParams::new(HashMap::new())).is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use opencv::core::{add_weighted_def, Mat, Size};
+use opencv::imgproc::gaussian_blur_def;
+use crate::media::effect::{ImageEffect, EffectParams, EffectError, ParamKind, ParamSpec};
+
+/// Unsharp mask, e.g. after downscaling large art
+///
+/// `image + amount * (image - blur(image, sigma))`, amount defaults to 1.0
+/// and sigma to 1.0. Values are saturated to 0..255.
+pub struct SharpenEffect;
+
+impl ImageEffect for SharpenEffect {
+    fn id(&self) -> &'static str {
+        "sharpen"
+    }
+
+    fn apply(&self, image: Mat, params: &EffectParams) -> Result<Mat, EffectError> {
+        self.validate_params(params)?;
+        let amount = params.get_f32("amount").unwrap_or(1.0) as f64;
+        let sigma = params.get_f32("sigma").unwrap_or(1.0) as f64;
+
+        let mut blurred = Mat::default();
+        gaussian_blur_def(&image, &mut blurred, Size::new(0, 0), sigma)?;
+
+        let mut sharpened = Mat::default();
+        add_weighted_def(&image, 1.0 + amount, &blurred, -amount, 0.0, &mut sharpened)?;
+
+        Ok(sharpened)
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &[
+            ParamSpec::optional("amount", ParamKind::Number),
+            ParamSpec::optional("sigma", ParamKind::Number),
+        ]
+    }
+
+    fn validate_params(&self, params: &EffectParams) -> Result<(), EffectError> {
+        if let Ok(amount) = params.get_f32("amount") {
+            if amount <= 0.0 || amount > 10.0 {
+                return Err(EffectError::InvalidParameter(
+                    "amount must be between 0 and 10".to_string()
+                ));
+            }
+        }
+
+        if let Ok(sigma) = params.get_f32("sigma") {
+            if sigma <= 0.0 || sigma > 100.0 {
+                return Err(EffectError::InvalidParameter(
+                    "sigma must be between 0 and 100".to_string()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{Rect, Scalar, Vec3b, CV_8UC3};
+    use opencv::prelude::*;
+    use rocket::serde::json::serde_json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_increases_edge_contrast() {
+        let effect = SharpenEffect;
+
+        // Dark gray left half, light gray right half
+        let mut image = Mat::new_rows_cols_with_default(10, 20, CV_8UC3, Scalar::all(64.0)).unwrap();
+        Mat::roi_mut(&mut image, Rect::new(10, 0, 10, 10))
+            .unwrap()
+            .set_to_def(&Scalar::all(192.0))
+            .unwrap();
+
+        let result = effect.apply(image, &EffectParams::new(HashMap::new())).unwrap();
+        let value = |x| result.at_2d::<Vec3b>(5, x).unwrap()[0];
+
+        // Overshoot on both sides of the edge, flat areas unchanged
+        assert!(value(9) < 64);
+        assert!(value(10) > 192);
+        assert_eq!(value(0), 64);
+        assert_eq!(value(19), 192);
+    }
+
+    #[test]
+    fn test_validate_params() {
+        let effect = SharpenEffect;
+
+        let mut params_map = HashMap::new();
+        params_map.insert("amount".to_string(), serde_json::json!(1.5));
+        params_map.insert("sigma".to_string(), serde_json::json!(2));
+        assert!(effect.validate_params(&EffectParams::new(params_map)).is_ok());
+
+        let mut params_map = HashMap::new();
+        params_map.insert("amount".to_string(), serde_json::json!(-1));
+        assert!(effect.validate_params(&EffectParams::new(params_map)).is_err());
+    }
+}
@@ -0,0 +1,434 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use opencv::core::{self, Mat, Rect, Size, Vector, CV_32F};
+use opencv::imgproc::{self, resize, INTER_AREA, INTER_LINEAR};
+use opencv::objdetect::CascadeClassifier;
+use opencv::prelude::*;
+use crate::media::effect::{ImageEffect, EffectParams, EffectError, ParamKind, ParamSpec};
+
+/// Haar cascade shipped with the OpenCV packages of most distributions
+const DEFAULT_CASCADE: &str = "/usr/share/opencv4/haarcascades/haarcascade_frontalface_default.xml";
+
+/// Longest side of the copy used for face and saliency detection
+const DETECTION_SIZE: f32 = 512.0;
+
+thread_local! {
+    /// Loaded cascades by path, None if the file couldn't be loaded
+    static CASCADES: RefCell<HashMap<String, Option<CascadeClassifier>>> = RefCell::new(HashMap::new());
+}
+
+/// Crop to an aspect ratio around the interesting part of the image and resize
+///
+/// The crop is centered on, in this order:
+/// - the focal point `focalX`/`focalY` (0..1, usually "$focalX"/"$focalY"
+///   resolved from the focal point stored with the image)
+/// - detected faces, all of them if they fit into the crop, else the largest
+/// - the region with the most edges (gradient magnitude) along the cropped axis
+/// - the center, like `resize_ratio`
+///
+/// Face detection uses the Haar cascade at `cascade` and is skipped if the file
+/// doesn't exist or `faces` is false.
+pub struct SmartCropEffect;
+
+impl ImageEffect for SmartCropEffect {
+    fn id(&self) -> &'static str {
+        "smart_crop"
+    }
+
+    fn apply(&self, image: Mat, params: &EffectParams) -> Result<Mat, EffectError> {
+        self.validate_params(params)?;
+        let target_width = params.get_i32("width")?;
+        let target_height = params.get_i32("height")?;
+
+        let (width, height) = {
+            let img_size = image.size()?;
+            (img_size.width, img_size.height)
+        };
+
+        let (crop_width, crop_height) = crop_size(width, height, target_width, target_height);
+
+        let cropped_image = if crop_width != width || crop_height != height {
+            let center = match (params.get_f32("focalX"), params.get_f32("focalY")) {
+                (Ok(x), Ok(y)) => (x * width as f32, y * height as f32),
+                _ => {
+                    let faces = if params.get_bool("faces").unwrap_or(true) {
+                        let cascade = params.get_string("cascade").unwrap_or_else(|_| DEFAULT_CASCADE.to_string());
+                        face_center(&image, &cascade, (crop_width, crop_height))?
+                    } else {
+                        None
+                    };
+
+                    match faces {
+                        Some(center) => center,
+                        None => saliency_center(&image, (crop_width, crop_height))?,
+                    }
+                }
+            };
+
+            let roi = Mat::roi(&image, crop_window(width, height, target_width, target_height, center))?;
+
+            // Clone the ROI to get an owned Mat
+            roi.try_clone()?
+        } else {
+            image
+        };
+
+        // Resize to target dimensions
+        let mut resized = Mat::default();
+        resize(
+            &cropped_image,
+            &mut resized,
+            Size {
+                width: target_width,
+                height: target_height,
+            },
+            0.0,
+            0.0,
+            INTER_LINEAR,
+        )?;
+
+        Ok(resized)
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &[
+            ParamSpec::required("width", ParamKind::Integer),
+            ParamSpec::required("height", ParamKind::Integer),
+            ParamSpec::optional("focalX", ParamKind::Number),
+            ParamSpec::optional("focalY", ParamKind::Number),
+            ParamSpec::optional("faces", ParamKind::Bool),
+            ParamSpec::optional("cascade", ParamKind::String),
+        ]
+    }
+
+    fn validate_params(&self, params: &EffectParams) -> Result<(), EffectError> {
+        let width = params.get_i32("width")?;
+        let height = params.get_i32("height")?;
+
+        if width <= 0 || height <= 0 {
+            return Err(EffectError::InvalidParameter(
+                "width and height must be positive".to_string()
+            ));
+        }
+
+        for key in ["focalX", "focalY"] {
+            if let Ok(value) = params.get_f32(key) {
+                if !(0.0..=1.0).contains(&value) {
+                    return Err(EffectError::InvalidParameter(format!(
+                        "{} must be between 0 and 1", key
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn output_size(&self, params: &EffectParams) -> Option<(u32, u32)> {
+        Some((params.get_u32("width").ok()?, params.get_u32("height").ok()?))
+    }
+}
+
+/// Largest size with the target aspect ratio that fits into the image
+fn crop_size(width: i32, height: i32, target_width: i32, target_height: i32) -> (i32, i32) {
+    let actual_ratio = width as f32 / height as f32;
+    let target_ratio = target_width as f32 / target_height as f32;
+
+    if is_close::default().is_close(actual_ratio, target_ratio) {
+        (width, height)
+    } else if actual_ratio > target_ratio {
+        // Image is wider than target - crop width
+        (((target_ratio * height as f32) as i32).clamp(1, width), height)
+    } else {
+        // Image is taller than target - crop height
+        (width, ((width as f32 / target_ratio) as i32).clamp(1, height))
+    }
+}
+
+/// Crop of `crop_size` centered on `center` (pixels), moved inside the image
+fn crop_window(width: i32, height: i32, target_width: i32, target_height: i32, center: (f32, f32)) -> Rect {
+    let (crop_width, crop_height) = crop_size(width, height, target_width, target_height);
+
+    let x = (center.0 - crop_width as f32 / 2.0).round() as i32;
+    let y = (center.1 - crop_height as f32 / 2.0).round() as i32;
+
+    Rect {
+        x: x.clamp(0, width - crop_width),
+        y: y.clamp(0, height - crop_height),
+        width: crop_width,
+        height: crop_height,
+    }
+}
+
+/// Grayscale copy scaled down to `DETECTION_SIZE` and the scale used
+fn detection_image(image: &Mat) -> Result<(Mat, f32), EffectError> {
+    let gray = match image.channels() {
+        1 => image.try_clone()?,
+        channels => {
+            let code = if channels == 4 { imgproc::COLOR_BGRA2GRAY } else { imgproc::COLOR_BGR2GRAY };
+            let mut gray = Mat::default();
+            imgproc::cvt_color_def(image, &mut gray, code)?;
+            gray
+        }
+    };
+
+    let size = gray.size()?;
+    let scale = (DETECTION_SIZE / size.width.max(size.height) as f32).min(1.0);
+    if scale >= 1.0 {
+        return Ok((gray, 1.0));
+    }
+
+    let mut small = Mat::default();
+    let small_size = Size {
+        width: ((size.width as f32 * scale) as i32).max(1),
+        height: ((size.height as f32 * scale) as i32).max(1),
+    };
+    resize(&gray, &mut small, small_size, 0.0, 0.0, INTER_AREA)?;
+
+    Ok((small, scale))
+}
+
+/// Center of the detected faces, None without faces or cascade
+///
+/// Uses the center of all faces if they fit into the crop, else the largest face
+fn face_center(image: &Mat, cascade: &str, crop: (i32, i32)) -> Result<Option<(f32, f32)>, EffectError> {
+    let (gray, scale) = detection_image(image)?;
+    let mut equalized = Mat::default();
+    imgproc::equalize_hist(&gray, &mut equalized)?;
+
+    let faces = CASCADES.with(|cascades| -> Result<Vec<Rect>, EffectError> {
+        let mut cascades = cascades.borrow_mut();
+        let classifier = cascades
+            .entry(cascade.to_string())
+            .or_insert_with(|| load_cascade(cascade));
+
+        let classifier = match classifier {
+            Some(classifier) => classifier,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut faces = Vector::<Rect>::new();
+        classifier.detect_multi_scale(
+            &equalized,
+            &mut faces,
+            1.1,
+            5,
+            0,
+            Size { width: 24, height: 24 },
+            Size::default(),
+        )?;
+
+        Ok(faces.to_vec())
+    })?;
+
+    // Back to image coordinates
+    let faces: Vec<Rect> = faces
+        .into_iter()
+        .map(|face| Rect {
+            x: (face.x as f32 / scale) as i32,
+            y: (face.y as f32 / scale) as i32,
+            width: (face.width as f32 / scale) as i32,
+            height: (face.height as f32 / scale) as i32,
+        })
+        .collect();
+
+    Ok(faces_center(&faces, crop))
+}
+
+fn load_cascade(path: &str) -> Option<CascadeClassifier> {
+    if !Path::new(path).is_file() {
+        return None;
+    }
+
+    match CascadeClassifier::new(path) {
+        Ok(classifier) if !classifier.empty().unwrap_or(true) => Some(classifier),
+        _ => {
+            println!("Failed to load face cascade {}, smart_crop continues without faces", path);
+            None
+        }
+    }
+}
+
+/// Center of the bounding box of all faces if it fits into the crop, else of the largest face
+fn faces_center(faces: &[Rect], crop: (i32, i32)) -> Option<(f32, f32)> {
+    let center = |rect: &Rect| (rect.x as f32 + rect.width as f32 / 2.0, rect.y as f32 + rect.height as f32 / 2.0);
+
+    let first = faces.first()?;
+    let bounds = faces.iter().skip(1).fold(*first, |bounds, face| {
+        let x = bounds.x.min(face.x);
+        let y = bounds.y.min(face.y);
+        Rect {
+            x,
+            y,
+            width: (bounds.x + bounds.width).max(face.x + face.width) - x,
+            height: (bounds.y + bounds.height).max(face.y + face.height) - y,
+        }
+    });
+
+    if bounds.width <= crop.0 && bounds.height <= crop.1 {
+        return Some(center(&bounds));
+    }
+
+    faces.iter().max_by_key(|face| face.area()).map(center)
+}
+
+/// Center of the crop with the most edges
+///
+/// Sums the gradient magnitude per column (or row) and slides the crop along
+/// the axis that gets cropped, the other axis stays centered
+fn saliency_center(image: &Mat, crop: (i32, i32)) -> Result<(f32, f32), EffectError> {
+    let (gray, scale) = detection_image(image)?;
+
+    let mut dx = Mat::default();
+    let mut dy = Mat::default();
+    imgproc::sobel_def(&gray, &mut dx, CV_32F, 1, 0)?;
+    imgproc::sobel_def(&gray, &mut dy, CV_32F, 0, 1)?;
+
+    let mut magnitude = Mat::default();
+    core::magnitude(&dx, &dy, &mut magnitude)?;
+
+    let size = magnitude.size()?;
+    let (cols, rows) = (size.width as usize, size.height as usize);
+    let values = magnitude.data_typed::<f32>()?;
+
+    let image_size = image.size()?;
+    let mut center = (image_size.width as f32 / 2.0, image_size.height as f32 / 2.0);
+
+    if crop.0 < image_size.width {
+        let sums: Vec<f32> = (0..cols).map(|col| (0..rows).map(|row| values[row * cols + col]).sum()).collect();
+        let window = ((crop.0 as f32 * scale) as usize).clamp(1, cols);
+        center.0 = (best_offset(&sums, window) as f32 + window as f32 / 2.0) / scale;
+    } else if crop.1 < image_size.height {
+        let sums: Vec<f32> = values.chunks_exact(cols).map(|row| row.iter().sum()).collect();
+        let window = ((crop.1 as f32 * scale) as usize).clamp(1, rows);
+        center.1 = (best_offset(&sums, window) as f32 + window as f32 / 2.0) / scale;
+    }
+
+    Ok(center)
+}
+
+/// Start of the `window` long run of `sums` with the highest total, the first on ties
+fn best_offset(sums: &[f32], window: usize) -> usize {
+    if window >= sums.len() {
+        return 0;
+    }
+
+    let mut total: f32 = sums[..window].iter().sum();
+    let mut best = (0, total);
+
+    for start in 1..=sums.len() - window {
+        total += sums[start + window - 1] - sums[start - 1];
+        if total > best.1 {
+            best = (start, total);
+        }
+    }
+
+    best.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{Scalar, CV_8UC3};
+    use rocket::serde::json::serde_json;
+    use std::collections::HashMap;
+
+    fn params(values: serde_json::Value) -> EffectParams {
+        let params_map: HashMap<String, serde_json::Value> = serde_json::from_value(values).unwrap();
+        EffectParams::new(params_map)
+    }
+
+    #[test]
+    fn test_crop_window() {
+        // Wide image to a square, centered
+        assert_eq!(crop_window(400, 200, 100, 100, (200.0, 100.0)), Rect { x: 100, y: 0, width: 200, height: 200 });
+
+        // Moved inside the image
+        assert_eq!(crop_window(400, 200, 100, 100, (10.0, 100.0)), Rect { x: 0, y: 0, width: 200, height: 200 });
+        assert_eq!(crop_window(400, 200, 100, 100, (390.0, 100.0)), Rect { x: 200, y: 0, width: 200, height: 200 });
+
+        // Tall image to a landscape ratio
+        assert_eq!(crop_window(200, 400, 200, 100, (100.0, 300.0)), Rect { x: 0, y: 250, width: 200, height: 100 });
+    }
+
+    #[test]
+    fn test_faces_center() {
+        let face = |x, y, size| Rect { x, y, width: size, height: size };
+
+        assert_eq!(faces_center(&[], (100, 100)), None);
+
+        // Both faces fit, centered between them
+        assert_eq!(faces_center(&[face(0, 0, 20), face(60, 0, 20)], (100, 100)), Some((40.0, 10.0)));
+
+        // Too far apart, the larger one wins
+        assert_eq!(faces_center(&[face(0, 0, 20), face(300, 0, 40)], (100, 100)), Some((320.0, 20.0)));
+    }
+
+    #[test]
+    fn test_best_offset() {
+        assert_eq!(best_offset(&[0.0, 1.0, 5.0, 5.0, 1.0], 2), 2);
+        assert_eq!(best_offset(&[3.0, 0.0, 0.0], 1), 0);
+        assert_eq!(best_offset(&[1.0, 2.0], 3), 0);
+    }
+
+    #[test]
+    fn test_focal_point() {
+        let effect = SmartCropEffect;
+
+        // Left half black, right half white
+        let mut image = Mat::new_rows_cols_with_default(100, 200, CV_8UC3, Scalar::all(0.0)).unwrap();
+        Mat::roi_mut(&mut image, Rect { x: 100, y: 0, width: 100, height: 100 })
+            .unwrap()
+            .set_to_def(&Scalar::all(255.0))
+            .unwrap();
+
+        let result = effect.apply(image.try_clone().unwrap(), &params(serde_json::json!({
+            "width": 10, "height": 10, "focalX": 0.9, "focalY": 0.5
+        }))).unwrap();
+        assert_eq!(result.size().unwrap(), Size { width: 10, height: 10 });
+        assert!(result.data_bytes().unwrap().iter().all(|b| *b == 255));
+
+        let result = effect.apply(image, &params(serde_json::json!({
+            "width": 10, "height": 10, "focalX": 0.1, "focalY": 0.5
+        }))).unwrap();
+        assert!(result.data_bytes().unwrap().iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn test_saliency() {
+        let effect = SmartCropEffect;
+
+        // Flat image with a checkerboard detail on the right
+        let mut image = Mat::new_rows_cols_with_default(100, 300, CV_8UC3, Scalar::all(128.0)).unwrap();
+        for i in 0..10 {
+            for j in 0..10 {
+                if (i + j) % 2 == 0 {
+                    Mat::roi_mut(&mut image, Rect { x: 200 + i * 10, y: j * 10, width: 10, height: 10 })
+                        .unwrap()
+                        .set_to_def(&Scalar::all(255.0))
+                        .unwrap();
+                }
+            }
+        }
+
+        let center = saliency_center(&image, (100, 100)).unwrap();
+        assert!(center.0 > 200.0, "center {:?}", center);
+
+        let result = effect.apply(image, &params(serde_json::json!({
+            "width": 50, "height": 50, "faces": false
+        }))).unwrap();
+        assert_eq!(result.size().unwrap(), Size { width: 50, height: 50 });
+    }
+
+    #[test]
+    fn test_validate_params() {
+        let effect = SmartCropEffect;
+
+        assert!(effect.validate_params(&params(serde_json::json!({"width": 330, "height": 516}))).is_ok());
+        assert!(effect.validate_params(&params(serde_json::json!({"width": 330, "height": 516, "focalX": 0.2, "focalY": 1}))).is_ok());
+        assert!(effect.validate_params(&params(serde_json::json!({"height": 516}))).is_err());
+        assert!(effect.validate_params(&params(serde_json::json!({"width": 0, "height": 516}))).is_err());
+        assert!(effect.validate_params(&params(serde_json::json!({"width": 330, "height": 516, "focalX": 1.5}))).is_err());
+    }
+}
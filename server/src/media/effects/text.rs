@@ -0,0 +1,128 @@
+use opencv::core::{Mat, Point, Scalar};
+use opencv::imgproc::{get_text_size, put_text, FONT_HERSHEY_SIMPLEX, LINE_AA};
+use opencv::prelude::*;
+use crate::media::effect::{ImageEffect, EffectParams, EffectError, ParamKind, ParamSpec};
+
+/// Draw a horizontally centered, outlined line of text, e.g. the card name
+///
+/// Params: text, scale (default 1.0), thickness (default 2) and y, the
+/// baseline position relative to the image height (default 0.9).
+/// Text too wide for the image is scaled down. Without text the image is
+/// passed through. The Hershey fonts only cover ASCII, OpenCV draws every
+/// other character (e.g. in non-ASCII card names) as "?".
+pub struct TextEffect;
+
+impl ImageEffect for TextEffect {
+    fn id(&self) -> &'static str {
+        "text"
+    }
+
+    fn apply(&self, mut image: Mat, params: &EffectParams) -> Result<Mat, EffectError> {
+        let text = match params.get_string("text") {
+            Ok(text) if !text.is_empty() => text,
+            _ => return Ok(image),
+        };
+        self.validate_params(params)?;
+
+        let mut scale = params.get_f32("scale").unwrap_or(1.0) as f64;
+        let thickness = params.get_i32("thickness").unwrap_or(2);
+        let y = params.get_f32("y").unwrap_or(0.9);
+
+        let size = image.size()?;
+        let mut baseline = 0;
+        let mut text_size = get_text_size(&text, FONT_HERSHEY_SIMPLEX, scale, thickness, &mut baseline)?;
+
+        // Leave some horizontal padding
+        let max_width = size.width * 9 / 10;
+        if text_size.width > max_width && text_size.width > 0 {
+            scale *= max_width as f64 / text_size.width as f64;
+            text_size = get_text_size(&text, FONT_HERSHEY_SIMPLEX, scale, thickness, &mut baseline)?;
+        }
+
+        let origin = Point::new(
+            ((size.width - text_size.width) / 2).max(0),
+            (size.height as f32 * y) as i32,
+        );
+
+        // Outline first so the text stays readable on bright art
+        put_text(&mut image, &text, origin, FONT_HERSHEY_SIMPLEX, scale, Scalar::all(0.0), thickness + 2, LINE_AA, false)?;
+        put_text(&mut image, &text, origin, FONT_HERSHEY_SIMPLEX, scale, Scalar::all(255.0), thickness, LINE_AA, false)?;
+
+        Ok(image)
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &[
+            ParamSpec::optional("text", ParamKind::String),
+            ParamSpec::optional("scale", ParamKind::Number),
+            ParamSpec::optional("thickness", ParamKind::Integer),
+            ParamSpec::optional("y", ParamKind::Number),
+        ]
+    }
+
+    fn validate_params(&self, params: &EffectParams) -> Result<(), EffectError> {
+        if let Ok(scale) = params.get_f32("scale") {
+            if scale <= 0.0 {
+                return Err(EffectError::InvalidParameter(
+                    "scale must be positive".to_string()
+                ));
+            }
+        }
+
+        if let Ok(thickness) = params.get_i32("thickness") {
+            if thickness <= 0 {
+                return Err(EffectError::InvalidParameter(
+                    "thickness must be positive".to_string()
+                ));
+            }
+        }
+
+        if let Ok(y) = params.get_f32("y") {
+            if !(0.0..=1.0).contains(&y) {
+                return Err(EffectError::InvalidParameter(
+                    "y must be between 0 and 1".to_string()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::CV_8UC3;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_draws_text() {
+        let effect = TextEffect;
+        let image = Mat::new_rows_cols_with_default(100, 200, CV_8UC3, Scalar::all(0.0)).unwrap();
+
+        let mut params_map = HashMap::new();
+        params_map.insert("text".to_string(), serde_json::json!("A rather long card name"));
+        let result = effect.apply(image, &EffectParams::new(params_map)).unwrap();
+
+        assert_eq!(result.size().unwrap(), opencv::core::Size::new(200, 100));
+        assert!(result.data_bytes().unwrap().iter().any(|b| *b == 255));
+    }
+
+    #[test]
+    fn test_validate_params() {
+        let effect = TextEffect;
+
+        let mut params_map = HashMap::new();
+        params_map.insert("scale".to_string(), serde_json::json!(0.8));
+        params_map.insert("y".to_string(), serde_json::json!(0.95));
+        assert!(effect.validate_params(&EffectParams::new(params_map)).is_ok());
+
+        let mut params_map = HashMap::new();
+        params_map.insert("y".to_string(), serde_json::json!(2));
+        assert!(effect.validate_params(&EffectParams::new(params_map)).is_err());
+
+        let mut params_map = HashMap::new();
+        params_map.insert("thickness".to_string(), serde_json::json!(0));
+        assert!(effect.validate_params(&EffectParams::new(params_map)).is_err());
+    }
+}
@@ -0,0 +1,263 @@
+use opencv::core::{add_weighted_def, Mat, Point, Rect, Scalar, Size};
+use opencv::imgcodecs::{imread, IMREAD_UNCHANGED};
+use opencv::imgproc::{get_text_size, put_text, resize, FONT_HERSHEY_SIMPLEX, INTER_AREA, LINE_AA};
+use opencv::prelude::*;
+use crate::media::effect::{ImageEffect, EffectParams, EffectError, ParamKind, ParamSpec};
+use super::overlay::blend;
+
+const POSITIONS: [&str; 5] = ["center", "top-left", "top-right", "bottom-left", "bottom-right"];
+
+/// Semi-transparent image or text watermark in a corner or the center
+///
+/// Params: path (image file) or text, opacity (default 0.5), position (one of
+/// `POSITIONS`, default "bottom-right"), size as fraction of the image width
+/// (default 0.25), margin as fraction of the shorter side (default 0.03) and
+/// color for text ("#rrggbb", default white). Image watermarks keep their
+/// aspect ratio and alpha. Without path and text the image is passed through.
+pub struct WatermarkEffect;
+
+impl ImageEffect for WatermarkEffect {
+    fn id(&self) -> &'static str {
+        "watermark"
+    }
+
+    fn apply(&self, image: Mat, params: &EffectParams) -> Result<Mat, EffectError> {
+        self.validate_params(params)?;
+        let opacity = params.get_f32("opacity").unwrap_or(0.5);
+        let position = params.get_string("position").unwrap_or_else(|_| String::from("bottom-right"));
+        let relative_size = params.get_f32("size").unwrap_or(0.25);
+
+        let size = image.size()?;
+        let margin = (size.width.min(size.height) as f32 * params.get_f32("margin").unwrap_or(0.03)) as i32;
+        let max_width = ((size.width as f32 * relative_size) as i32).max(1);
+
+        if let Ok(path) = params.get_string("path") {
+            return image_watermark(image, &path, opacity, &position, max_width, margin);
+        }
+
+        match params.get_string("text") {
+            Ok(text) if !text.is_empty() => {
+                let color = params.get_color("color").unwrap_or([255, 255, 255]);
+                text_watermark(image, &text, color, opacity, &position, max_width, margin)
+            }
+            _ => Ok(image),
+        }
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &[
+            ParamSpec::optional("path", ParamKind::String),
+            ParamSpec::optional("text", ParamKind::String),
+            ParamSpec::optional("opacity", ParamKind::Number),
+            ParamSpec::optional("position", ParamKind::String),
+            ParamSpec::optional("size", ParamKind::Number),
+            ParamSpec::optional("margin", ParamKind::Number),
+            ParamSpec::optional("color", ParamKind::String),
+        ]
+    }
+
+    fn validate_params(&self, params: &EffectParams) -> Result<(), EffectError> {
+        if params.get_string("path").is_ok() && params.get_string("text").is_ok() {
+            return Err(EffectError::InvalidParameter(
+                "path and text can't be combined".to_string()
+            ));
+        }
+
+        if let Ok(opacity) = params.get_f32("opacity") {
+            if !(0.0..=1.0).contains(&opacity) {
+                return Err(EffectError::InvalidParameter(
+                    "opacity must be between 0 and 1".to_string()
+                ));
+            }
+        }
+
+        if let Ok(position) = params.get_string("position") {
+            if !POSITIONS.contains(&position.as_str()) {
+                return Err(EffectError::InvalidParameter(format!(
+                    "position must be one of {}", POSITIONS.join(", ")
+                )));
+            }
+        }
+
+        if let Ok(size) = params.get_f32("size") {
+            if size <= 0.0 || size > 1.0 {
+                return Err(EffectError::InvalidParameter(
+                    "size must be between 0 and 1".to_string()
+                ));
+            }
+        }
+
+        if let Ok(margin) = params.get_f32("margin") {
+            if !(0.0..=0.5).contains(&margin) {
+                return Err(EffectError::InvalidParameter(
+                    "margin must be between 0 and 0.5".to_string()
+                ));
+            }
+        }
+
+        if let Err(EffectError::InvalidParameter(msg)) = params.get_color("color") {
+            return Err(EffectError::InvalidParameter(msg));
+        }
+
+        Ok(())
+    }
+}
+
+fn image_watermark(mut image: Mat, path: &str, opacity: f32, position: &str, max_width: i32, margin: i32) -> Result<Mat, EffectError> {
+    let mark = imread(path, IMREAD_UNCHANGED)?;
+    if mark.empty() {
+        return Err(EffectError::ProcessingError(format!(
+            "Failed to load watermark {}", path
+        )));
+    }
+
+    // Scale to the target width keeping the aspect ratio, never taller than the image
+    let size = image.size()?;
+    let mark_size = mark.size()?;
+    let scale = (max_width as f32 / mark_size.width as f32)
+        .min(size.height as f32 / mark_size.height as f32);
+    let target = Size::new(
+        ((mark_size.width as f32 * scale) as i32).clamp(1, size.width),
+        ((mark_size.height as f32 * scale) as i32).clamp(1, size.height),
+    );
+
+    let mut resized = Mat::default();
+    resize(&mark, &mut resized, target, 0.0, 0.0, INTER_AREA)?;
+
+    let origin = place(position, size, target, margin);
+    let rect = Rect::new(origin.x, origin.y, target.width, target.height);
+
+    // Blend on a copy of the covered region, ROIs aren't continuous
+    let mut region = Mat::roi(&image, rect)?.try_clone()?;
+    blend(&mut region, &resized, opacity)?;
+
+    {
+        let mut target_region = Mat::roi_mut(&mut image, rect)?;
+        region.copy_to(&mut *target_region)?;
+    }
+
+    Ok(image)
+}
+
+fn text_watermark(image: Mat, text: &str, color: [u8; 3], opacity: f32, position: &str, max_width: i32, margin: i32) -> Result<Mat, EffectError> {
+    let thickness = (max_width / 150).max(1);
+    let mut baseline = 0;
+    let unit_size = get_text_size(text, FONT_HERSHEY_SIMPLEX, 1.0, thickness, &mut baseline)?;
+    let scale = max_width as f64 / unit_size.width.max(1) as f64;
+    let text_size = get_text_size(text, FONT_HERSHEY_SIMPLEX, scale, thickness, &mut baseline)?;
+
+    let origin = place(position, image.size()?, text_size, margin);
+    let color = Scalar::new(color[0] as f64, color[1] as f64, color[2] as f64, 255.0);
+
+    // Draw fully opaque on a copy and mix it in, so overlapping strokes don't add up
+    let mut layer = image.try_clone()?;
+    put_text(&mut layer, text, Point::new(origin.x, origin.y + text_size.height), FONT_HERSHEY_SIMPLEX, scale, color, thickness, LINE_AA, false)?;
+
+    let mut mixed = Mat::default();
+    add_weighted_def(&image, 1.0 - opacity as f64, &layer, opacity as f64, 0.0, &mut mixed)?;
+
+    Ok(mixed)
+}
+
+/// Top left corner of a `mark` sized box at `position`, kept inside the image
+fn place(position: &str, image: Size, mark: Size, margin: i32) -> Point {
+    let left = margin;
+    let top = margin;
+    let right = image.width - mark.width - margin;
+    let bottom = image.height - mark.height - margin;
+    let (center_x, center_y) = ((image.width - mark.width) / 2, (image.height - mark.height) / 2);
+
+    let (x, y) = match position {
+        "top-left" => (left, top),
+        "top-right" => (right, top),
+        "bottom-left" => (left, bottom),
+        "center" => (center_x, center_y),
+        _ => (right, bottom),
+    };
+
+    Point::new(
+        x.clamp(0, (image.width - mark.width).max(0)),
+        y.clamp(0, (image.height - mark.height).max(0)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{Vector, CV_8UC3, CV_8UC4};
+    use opencv::imgcodecs::imwrite;
+    use rocket::serde::json::serde_json;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn params(values: serde_json::Value) -> EffectParams {
+        let params_map: HashMap<String, serde_json::Value> = serde_json::from_value(values).unwrap();
+        EffectParams::new(params_map)
+    }
+
+    /// Whether any pixel in the rect differs from the gray background
+    fn changed(image: &Mat, rect: Rect) -> bool {
+        let region = Mat::roi(image, rect).unwrap().try_clone().unwrap();
+        region.data_bytes().unwrap().iter().any(|b| *b != 100)
+    }
+
+    #[test]
+    fn test_place() {
+        let image = Size::new(100, 50);
+        let mark = Size::new(20, 10);
+
+        assert_eq!(place("top-left", image, mark, 5), Point::new(5, 5));
+        assert_eq!(place("bottom-right", image, mark, 5), Point::new(75, 35));
+        assert_eq!(place("center", image, mark, 5), Point::new(40, 20));
+
+        // Too large for the margin, moved back inside
+        assert_eq!(place("bottom-right", image, Size::new(100, 50), 5), Point::new(0, 0));
+    }
+
+    #[test]
+    fn test_image_watermark() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("mark.png");
+
+        let mark = Mat::new_rows_cols_with_default(10, 20, CV_8UC4, Scalar::new(0.0, 0.0, 255.0, 255.0)).unwrap();
+        imwrite(path.to_str().unwrap(), &mark, &Vector::new()).unwrap();
+
+        let image = Mat::new_rows_cols_with_default(100, 200, CV_8UC3, Scalar::all(100.0)).unwrap();
+        let result = WatermarkEffect.apply(image, &params(serde_json::json!({
+            "path": path.to_str().unwrap(), "opacity": 1.0, "position": "top-left", "size": 0.2, "margin": 0
+        }))).unwrap();
+
+        // 40x20 red box in the top left corner
+        assert_eq!(result.at_2d::<opencv::core::Vec3b>(5, 5).unwrap().0, [0, 0, 255]);
+        assert_eq!(result.at_2d::<opencv::core::Vec3b>(19, 39).unwrap().0, [0, 0, 255]);
+        assert!(!changed(&result, Rect::new(40, 20, 160, 80)));
+    }
+
+    #[test]
+    fn test_text_watermark() {
+        let image = Mat::new_rows_cols_with_default(100, 200, CV_8UC3, Scalar::all(100.0)).unwrap();
+        let result = WatermarkEffect.apply(image, &params(serde_json::json!({ "text": "CardCollector" }))).unwrap();
+
+        assert_eq!(result.size().unwrap(), Size::new(200, 100));
+        assert!(changed(&result, Rect::new(100, 50, 100, 50)));
+        assert!(!changed(&result, Rect::new(0, 0, 100, 50)));
+    }
+
+    #[test]
+    fn test_passes_through_without_mark() {
+        let image = Mat::new_rows_cols_with_default(10, 10, CV_8UC3, Scalar::all(100.0)).unwrap();
+        let result = WatermarkEffect.apply(image, &params(serde_json::json!({ "opacity": 0.3 }))).unwrap();
+        assert!(!changed(&result, Rect::new(0, 0, 10, 10)));
+    }
+
+    #[test]
+    fn test_validate_params() {
+        let effect = WatermarkEffect;
+
+        assert!(effect.validate_params(&params(serde_json::json!({ "text": "CC", "position": "center", "color": "#ffcc00" }))).is_ok());
+        assert!(effect.validate_params(&params(serde_json::json!({ "path": "mark.png", "text": "CC" }))).is_err());
+        assert!(effect.validate_params(&params(serde_json::json!({ "text": "CC", "position": "middle" }))).is_err());
+        assert!(effect.validate_params(&params(serde_json::json!({ "text": "CC", "opacity": 2 }))).is_err());
+        assert!(effect.validate_params(&params(serde_json::json!({ "text": "CC", "size": 0 }))).is_err());
+    }
+}
@@ -1,5 +1,5 @@
 use opencv::core::Mat;
-use crate::media::effect::{ImageEffect, EffectParams, EffectError, ImageFormat};
+use crate::media::effect::{ImageEffect, EffectParams, EffectError, ParamKind, ParamSpec, ImageFormat};
 
 /// Convert image to WebP format
 ///
@@ -22,6 +22,13 @@ impl ImageEffect for WebPEffect {
         Some(ImageFormat::WebP)
     }
 
+    fn params(&self) -> &'static [ParamSpec] {
+        &[
+            ParamSpec::optional("quality", ParamKind::Integer),
+            ParamSpec::optional("lossless", ParamKind::Bool),
+        ]
+    }
+
     fn validate_params(&self, params: &EffectParams) -> Result<(), EffectError> {
         // Quality parameter is optional, but if provided should be 1-100
         if let Ok(quality) = params.get_u32("quality") {
@@ -32,11 +39,6 @@ impl ImageEffect for WebPEffect {
             }
         }
 
-        // Lossless parameter is optional boolean
-        if let Ok(_lossless) = params.get_bool("lossless") {
-            // Valid boolean
-        }
-
         Ok(())
     }
 }
@@ -0,0 +1,15 @@
+use serde::{Serialize, Deserialize};
+use rocketjson::JsonBody;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate, JsonBody)]
+#[serde(rename_all="camelCase")]
+pub struct MediaFocalPointSetRequest {
+    pub x: f32,
+    pub y: f32
+}
+
+#[derive(Debug, Serialize)]
+pub struct MediaFocalPointResponse {
+    pub message: String
+}
@@ -0,0 +1,68 @@
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+use rocket::State;
+use rocket::http::Status;
+
+use super::data::{MediaFocalPointSetRequest, MediaFocalPointResponse};
+use crate::sql::Sql;
+use crate::media::context::FocalPoint;
+use crate::media::sql as media_sql;
+use crate::media::storage::is_content_id;
+use crate::shared::Id;
+use crate::shared::crypto::JwtToken;
+use crate::shared::user;
+use crate::verify_user;
+
+///sets the point smart_crop centers on, for every entity using the image
+#[put("/media/<image_id>/focal-point", data="<data>")]
+pub async fn media_focal_point_set_route(image_id: String, data: MediaFocalPointSetRequest, sql: &State<Sql>, token: JwtToken) -> ApiResponseErr<MediaFocalPointResponse> {
+    let user_id = token.id;
+    verify_user!(sql, &user_id, true);
+
+    if !(0.0..=1.0).contains(&data.x) || !(0.0..=1.0).contains(&data.y) {
+        return ApiResponseErr::api_err(Status::BadRequest, String::from("Focal point has to be between 0 and 1"))
+    }
+
+    if !is_content_id(&image_id) {
+        return ApiResponseErr::api_err(Status::NotFound, String::from("Image not found"))
+    }
+
+    if !rjtry!(can_edit_focal_point(sql, &image_id, &user_id).await) {
+        return ApiResponseErr::api_err(Status::Forbidden, String::from("Only the uploader or a moderator can set the focal point"))
+    }
+
+    rjtry!(media_sql::set_focal_point(sql, &image_id, FocalPoint { x: data.x, y: data.y }).await);
+
+    ApiResponseErr::ok(Status::Ok, MediaFocalPointResponse {
+        message: String::from("Focal point set")
+    })
+}
+
+///falls back to face and saliency detection
+#[delete("/media/<image_id>/focal-point")]
+pub async fn media_focal_point_delete_route(image_id: String, sql: &State<Sql>, token: JwtToken) -> ApiResponseErr<MediaFocalPointResponse> {
+    let user_id = token.id;
+    verify_user!(sql, &user_id, true);
+
+    if !is_content_id(&image_id) {
+        return ApiResponseErr::api_err(Status::NotFound, String::from("Image not found"))
+    }
+
+    if !rjtry!(can_edit_focal_point(sql, &image_id, &user_id).await) {
+        return ApiResponseErr::api_err(Status::Forbidden, String::from("Only the uploader or a moderator can remove the focal point"))
+    }
+
+    rjtry!(media_sql::delete_focal_point(sql, &image_id).await);
+
+    ApiResponseErr::ok(Status::Ok, MediaFocalPointResponse {
+        message: String::from("Focal point removed")
+    })
+}
+
+///admins, or users who uploaded the image or moderate a collector using it
+async fn can_edit_focal_point(sql: &Sql, image_id: &str, user_id: &Id) -> Result<bool, sqlx::Error> {
+    if user::sql::get_user_rank(sql, user_id).await? == user::data::UserRanking::Admin {
+        return Ok(true);
+    }
+
+    media_sql::can_edit_image(sql, image_id, user_id).await
+}
@@ -0,0 +1,4 @@
+mod data;
+mod logic;
+
+pub use logic::{media_focal_point_set_route, media_focal_point_delete_route};
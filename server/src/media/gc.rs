@@ -0,0 +1,332 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tokio::time::{Instant, MissedTickBehavior};
+
+use crate::sql::Sql;
+use super::cache::{FilesystemCache, CacheEntry, CacheError};
+use super::storage::{OriginalStore, StoredImage, StorageError, is_content_id};
+use super::sql;
+
+/// Limits of a GC run
+#[derive(Debug, Clone)]
+pub struct GcOptions {
+    /// Maximum size of the variant cache in bytes, 0 for no limit
+    pub max_cache_bytes: u64,
+
+    /// Files younger than this are never orphans, covers uploads whose
+    /// DB row isn't written yet
+    pub grace: Duration,
+}
+
+/// What a GC run removed, or would remove in dry-run mode
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GcReport {
+    pub dry_run: bool,
+
+    /// Size of the variant cache before the run
+    pub cache_bytes: u64,
+    pub orphaned_variants: usize,
+    pub evicted_variants: usize,
+    pub freed_cache_bytes: u64,
+
+    pub orphaned_originals: Vec<String>,
+    pub freed_original_bytes: u64,
+}
+
+impl std::fmt::Display for GcReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Media GC{}: {} {} orphaned and {} evicted variants ({} of {} bytes), {} orphaned originals ({} bytes)",
+            if self.dry_run { " (dry run)" } else { "" },
+            if self.dry_run { "would remove" } else { "removed" },
+            self.orphaned_variants,
+            self.evicted_variants,
+            self.freed_cache_bytes,
+            self.cache_bytes,
+            self.orphaned_originals.len(),
+            self.freed_original_bytes,
+        )
+    }
+}
+
+/// Garbage collector for the variant cache and the original storage
+///
+/// Removes cached variants and originals whose image no DB row references,
+/// then evicts the least recently used variants until the cache fits
+/// `max_cache_bytes`. Only content ids are collected, named images like
+/// `card-image-default` are kept.
+pub struct MediaGc {
+    cache: Arc<FilesystemCache>,
+    storage: Arc<dyn OriginalStore>,
+    options: GcOptions,
+
+    /// Runs from the background task and admins don't overlap
+    running: Mutex<()>,
+}
+
+impl MediaGc {
+    pub fn new(cache: Arc<FilesystemCache>, storage: Arc<dyn OriginalStore>, options: GcOptions) -> Self {
+        Self {
+            cache,
+            storage,
+            options,
+            running: Mutex::new(()),
+        }
+    }
+
+    /// Run a collection, with `dry_run` nothing is deleted
+    pub async fn run(&self, sql: &Sql, dry_run: bool) -> Result<GcReport, GcError> {
+        let _running = self.running.lock().await;
+
+        let referenced = Arc::new(sql::get_referenced_images(sql).await?);
+        let cutoff = SystemTime::now()
+            .checked_sub(self.options.grace)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        let mut report = GcReport {
+            dry_run,
+            ..GcReport::default()
+        };
+
+        for image in plan_originals(self.storage.list().await?, &referenced, cutoff) {
+            if !dry_run {
+                self.storage.delete(&image.image_id).await?;
+            }
+            report.freed_original_bytes += image.len;
+            report.orphaned_originals.push(image.image_id);
+        }
+
+        //NOTE: walking the cache is blocking file system work
+        let cache = self.cache.clone();
+        let options = self.options.clone();
+        let plan = tokio::task::spawn_blocking(move || {
+            collect_cache(&cache, &referenced, &options, cutoff, dry_run)
+        }).await??;
+
+        report.cache_bytes = plan.cache_bytes;
+        report.orphaned_variants = plan.orphaned.len();
+        report.evicted_variants = plan.evicted.len();
+        report.freed_cache_bytes = plan.orphaned.iter()
+            .chain(&plan.evicted)
+            .map(|entry| entry.len)
+            .sum();
+
+        Ok(report)
+    }
+}
+
+/// Run the GC every `interval`, starting one interval after launch
+pub fn spawn(gc: Arc<MediaGc>, sql: Sql, interval: Duration, dry_run: bool) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval_at(Instant::now() + interval, interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            match gc.run(&sql, dry_run).await {
+                Ok(report) => println!("{}", report),
+                Err(err) => println!("Media GC failed: {}", err),
+            }
+        }
+    });
+}
+
+/// Variants a run removes, orphans and least recently used ones
+#[derive(Debug)]
+struct CachePlan {
+    cache_bytes: u64,
+    orphaned: Vec<CacheEntry>,
+    evicted: Vec<CacheEntry>,
+}
+
+fn collect_cache(cache: &FilesystemCache, referenced: &HashSet<String>, options: &GcOptions, cutoff: SystemTime, dry_run: bool) -> Result<CachePlan, CacheError> {
+    let plan = plan_cache(cache.entries()?, referenced, options, cutoff);
+
+    if !dry_run {
+        for entry in plan.orphaned.iter().chain(&plan.evicted) {
+            cache.remove_entry(entry)?;
+        }
+    }
+
+    Ok(plan)
+}
+
+fn plan_cache(entries: Vec<CacheEntry>, referenced: &HashSet<String>, options: &GcOptions, cutoff: SystemTime) -> CachePlan {
+    let cache_bytes = entries.iter().map(|entry| entry.len).sum();
+
+    let (orphaned, mut kept): (Vec<CacheEntry>, Vec<CacheEntry>) = entries
+        .into_iter()
+        .partition(|entry| is_orphan(&entry.image_id, referenced, entry.accessed, cutoff));
+
+    let mut evicted = Vec::new();
+    if options.max_cache_bytes > 0 {
+        let mut bytes: u64 = kept.iter().map(|entry| entry.len).sum();
+
+        // Most recently used first, evict from the back
+        kept.sort_by_key(|entry| std::cmp::Reverse(entry.accessed));
+        while bytes > options.max_cache_bytes {
+            match kept.pop() {
+                Some(entry) => {
+                    bytes -= entry.len;
+                    evicted.push(entry);
+                }
+                None => break,
+            }
+        }
+    }
+
+    CachePlan {
+        cache_bytes,
+        orphaned,
+        evicted,
+    }
+}
+
+fn plan_originals(images: Vec<StoredImage>, referenced: &HashSet<String>, cutoff: SystemTime) -> Vec<StoredImage> {
+    images
+        .into_iter()
+        .filter(|image| is_orphan(&image.image_id, referenced, image.modified, cutoff))
+        .collect()
+}
+
+fn is_orphan(image_id: &str, referenced: &HashSet<String>, modified: SystemTime, cutoff: SystemTime) -> bool {
+    is_content_id(image_id) && !referenced.contains(image_id) && modified < cutoff
+}
+
+/// Errors that can occur during a GC run
+#[derive(Debug)]
+pub enum GcError {
+    Sql(sqlx::Error),
+    Cache(CacheError),
+    Storage(StorageError),
+    Task(tokio::task::JoinError),
+}
+
+impl std::fmt::Display for GcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GcError::Sql(e) => write!(f, "Failed to load referenced images: {}", e),
+            GcError::Cache(e) => write!(f, "Cache error: {}", e),
+            GcError::Storage(e) => write!(f, "Storage error: {}", e),
+            GcError::Task(e) => write!(f, "GC task failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for GcError {}
+
+impl From<sqlx::Error> for GcError {
+    fn from(err: sqlx::Error) -> Self {
+        GcError::Sql(err)
+    }
+}
+
+impl From<CacheError> for GcError {
+    fn from(err: CacheError) -> Self {
+        GcError::Cache(err)
+    }
+}
+
+impl From<StorageError> for GcError {
+    fn from(err: StorageError) -> Self {
+        GcError::Storage(err)
+    }
+}
+
+impl From<tokio::task::JoinError> for GcError {
+    fn from(err: tokio::task::JoinError) -> Self {
+        GcError::Task(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn id(n: u8) -> String {
+        format!("{:064x}", n)
+    }
+
+    fn at(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    fn entry(image_id: &str, len: u64, accessed: u64) -> CacheEntry {
+        CacheEntry {
+            path: PathBuf::from(format!("{}-{}", image_id, accessed)),
+            image_id: image_id.to_string(),
+            len,
+            accessed: at(accessed),
+        }
+    }
+
+    fn options(max_cache_bytes: u64) -> GcOptions {
+        GcOptions {
+            max_cache_bytes,
+            grace: Duration::from_secs(0),
+        }
+    }
+
+    #[test]
+    fn test_orphans() {
+        let referenced: HashSet<String> = [id(1)].into_iter().collect();
+        let cutoff = at(100);
+
+        let plan = plan_cache(vec![
+            entry(&id(1), 10, 10),
+            entry(&id(2), 10, 10),
+            // Within the grace period
+            entry(&id(3), 10, 150),
+            // Named default images are never orphans
+            entry("card-image-default", 10, 10),
+        ], &referenced, &options(0), cutoff);
+
+        assert_eq!(plan.cache_bytes, 40);
+        assert_eq!(plan.orphaned.len(), 1);
+        assert_eq!(plan.orphaned[0].image_id, id(2));
+        assert!(plan.evicted.is_empty());
+    }
+
+    #[test]
+    fn test_eviction_by_last_access() {
+        let referenced: HashSet<String> = [id(1), id(2)].into_iter().collect();
+
+        let plan = plan_cache(vec![
+            entry(&id(1), 10, 30),
+            entry(&id(1), 10, 10),
+            entry(&id(2), 10, 20),
+            entry(&id(2), 10, 40),
+        ], &referenced, &options(25), at(0));
+
+        let evicted: Vec<SystemTime> = plan.evicted.iter().map(|entry| entry.accessed).collect();
+        assert_eq!(evicted, vec![at(10), at(20)]);
+    }
+
+    #[test]
+    fn test_orphaned_originals() {
+        let referenced: HashSet<String> = [id(1)].into_iter().collect();
+
+        let image = |image_id: String, modified: u64| StoredImage {
+            image_id,
+            len: 10,
+            modified: at(modified),
+        };
+
+        let orphans = plan_originals(vec![
+            image(id(1), 10),
+            image(id(2), 10),
+            image(id(3), 150),
+            image(String::from("collector-image-default"), 10),
+        ], &referenced, at(100));
+
+        let orphans: Vec<String> = orphans.into_iter().map(|image| image.image_id).collect();
+        assert_eq!(orphans, vec![id(2)]);
+    }
+}
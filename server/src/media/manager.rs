@@ -1,7 +1,10 @@
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
 use dashmap::DashMap;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Semaphore};
+use rocket::http::Accept;
+use sha2::{Sha256, Digest};
 use opencv::core::{Mat, Vector};
 use opencv::prelude::*;
 use opencv::imgcodecs::{imencode, imdecode, IMREAD_COLOR};
@@ -9,19 +12,70 @@ use opencv::imgcodecs::{IMWRITE_WEBP_QUALITY, IMWRITE_JPEG_QUALITY, IMWRITE_PNG_
 
 use super::effect::{ImageEffect, ImageFormat, EffectParams, EffectError};
 use super::effect_registry::EffectRegistry;
-use super::config::{MediaTypeConfig, EffectSpec};
-use super::cache::{ImageCache, CacheKey, CacheError};
-use super::storage::{ImageStorage, StorageError};
+use super::effects::avif;
+use super::context::{RenderContext, FocalPoint};
+use super::config::{self, MediaTypeConfig, VariantConfig, EffectSpec, ConfigError};
+use super::negotiate::negotiate_format;
+use super::cache::{ImageCache, CacheKey, CacheError, CacheStats};
+use super::body::MediaBody;
+use super::storage::{OriginalStore, StorageError};
+use super::pool::{GenerationPool, PoolError, PoolStats};
+
+/// Queued generations allowed by default before requests are rejected
+const DEFAULT_MAX_QUEUE: usize = 64;
+
+/// Images generated in the background at once, the rest wait
+const BACKGROUND_CONCURRENCY: usize = 1;
 
 /// Main media manager coordinating image transformations and caching
+///
+/// Cheap to clone, clones share all state so background generation can
+/// run on a clone
+#[derive(Clone)]
 pub struct MediaManager {
     effect_registry: Arc<EffectRegistry>,
-    media_types: HashMap<String, MediaTypeConfig>,
+
+    /// Swapped as a whole on reload, requests keep the snapshot they started with
+    media_types: Arc<RwLock<Arc<HashMap<String, MediaTypeConfig>>>>,
     cache: Arc<dyn ImageCache>,
-    storage: Arc<ImageStorage>,
+    storage: Arc<dyn OriginalStore>,
 
     /// Per-cache-key locks for thread-safe generation
     generation_locks: Arc<DashMap<String, Arc<Mutex<()>>>>,
+
+    /// Blocking pool for decoding, effects and encoding
+    generation_pool: Arc<GenerationPool>,
+
+    /// Limits pre-generation and regeneration, so they leave the pool to requests
+    background_permits: Arc<Semaphore>,
+}
+
+/// Entry in `generation_locks`, removed on drop once no other request uses it
+struct GenerationLock<'a> {
+    locks: &'a DashMap<String, Arc<Mutex<()>>>,
+    key: String,
+    lock: Arc<Mutex<()>>,
+}
+
+impl<'a> GenerationLock<'a> {
+    fn acquire(locks: &'a DashMap<String, Arc<Mutex<()>>>, key: String) -> Self {
+        let lock = locks
+            .entry(key.clone())
+            .or_insert_with(|| Arc::new(Mutex::new(())))
+            .clone();
+
+        Self { locks, key, lock }
+    }
+}
+
+impl Drop for GenerationLock<'_> {
+    fn drop(&mut self) {
+        //NOTE: the map and this entry hold one reference each, more means other
+        //requests still wait for the lock and the last of them removes it
+        self.locks.remove_if(&self.key, |_, lock| {
+            Arc::ptr_eq(lock, &self.lock) && Arc::strong_count(lock) == 2
+        });
+    }
 }
 
 impl MediaManager {
@@ -30,30 +84,88 @@ impl MediaManager {
         effect_registry: EffectRegistry,
         media_types: HashMap<String, MediaTypeConfig>,
         cache: Arc<dyn ImageCache>,
-        storage: Arc<ImageStorage>,
+        storage: Arc<dyn OriginalStore>,
     ) -> Self {
         Self {
             effect_registry: Arc::new(effect_registry),
-            media_types,
+            media_types: Arc::new(RwLock::new(Arc::new(media_types))),
             cache,
             storage,
             generation_locks: Arc::new(DashMap::new()),
+            generation_pool: Arc::new(GenerationPool::new(0, DEFAULT_MAX_QUEUE)),
+            background_permits: Arc::new(Semaphore::new(BACKGROUND_CONCURRENCY)),
         }
     }
 
+    /// Limit concurrent generations (0 for the number of CPUs) and queued ones
+    pub fn with_generation_limits(mut self, concurrency: usize, max_queue: usize) -> Self {
+        self.generation_pool = Arc::new(GenerationPool::new(concurrency, max_queue));
+        self
+    }
+
+    /// Current media types
+    fn media_types(&self) -> Arc<HashMap<String, MediaTypeConfig>> {
+        self.media_types.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /// Reload the media types from a directory
+    ///
+    /// The whole directory is parsed and validated before the configs are
+    /// swapped, on error the current ones stay in place. Changed variants get
+    /// new cache keys through their config version, the stale files are left
+    /// to the GC.
+    pub fn reload_media_types<P: AsRef<Path>>(&self, dir: P) -> Result<ReloadReport, ConfigError> {
+        let media_types = config::load_media_types(dir, &self.effect_registry)?;
+
+        let mut current = self.media_types.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let report = ReloadReport::new(&current, &media_types);
+        *current = Arc::new(media_types);
+
+        Ok(report)
+    }
+
     /// Get a transformed image variant
     ///
-    /// Returns the image bytes and the actual format
+    /// Returns the image bytes, the actual format and the ETag
     pub async fn get_image(
         &self,
         media_type: &str,
         image_id: &str,
         variant: Option<&str>,
-    ) -> Result<(Vec<u8>, ImageFormat), ManagerError> {
+    ) -> Result<MediaImage, ManagerError> {
+        self.get_image_with_context(media_type, image_id, variant, &RenderContext::default(), None).await
+    }
+
+    /// Get a composited image variant
+    ///
+    /// "$name" effect params are resolved from the context, the context tag
+    /// becomes part of the cache key. The format is negotiated from `accept`
+    /// for variants declaring candidate formats, others use the format of
+    /// their effect chain
+    pub async fn get_composite_image(
+        &self,
+        media_type: &str,
+        image_id: &str,
+        variant: Option<&str>,
+        context: &RenderContext,
+        accept: Option<&Accept>,
+    ) -> Result<MediaImage, ManagerError> {
+        self.get_image_with_context(media_type, image_id, variant, context, accept).await
+    }
+
+    async fn get_image_with_context(
+        &self,
+        media_type: &str,
+        image_id: &str,
+        variant: Option<&str>,
+        context: &RenderContext,
+        accept: Option<&Accept>,
+    ) -> Result<MediaImage, ManagerError> {
         let variant_name = variant.unwrap_or("default");
 
         // Get media type config
-        let media_config = self.media_types
+        let media_types = self.media_types();
+        let media_config = media_types
             .get(media_type)
             .ok_or_else(|| ManagerError::UnknownMediaType(media_type.to_string()))?;
 
@@ -62,85 +174,233 @@ impl MediaManager {
             .get(variant_name)
             .ok_or_else(|| ManagerError::UnknownVariant(variant_name.to_string()))?;
 
-        // Determine output format by scanning effect chain
-        let format = self.determine_format(&variant_config.effects);
+        // Determine output format, cached separately per format
+        let format = self.select_format(variant_config, accept);
+
+        let cache_key = Self::cache_key(media_type, image_id, variant_name, variant_config, format, context);
+        let etag = Self::etag(&cache_key);
+        let version = Self::url_version(variant_config, context);
+
+        // Fast path: check cache without lock, streamed from the cache if possible
+        if let Some(cached) = self.cache.open(&cache_key).await? {
+            return Ok(MediaImage { body: cached, format, etag, version });
+        }
 
-        // Create cache key
+        let body = self.generate_cached(&cache_key, variant_config, context, false, false).await?;
+
+        Ok(MediaImage { body, format, etag, version })
+    }
+
+    /// Version of a variant in `/media` URLs (`?v=<version>`)
+    ///
+    /// The config version plus the context tag, changes whenever a reload or
+    /// a focal point changes the output of an unchanged image
+    fn url_version(variant_config: &VariantConfig, context: &RenderContext) -> String {
+        if context.tag().is_empty() {
+            variant_config.version()
+        } else {
+            format!("{}-{}", variant_config.version(), context.tag())
+        }
+    }
+
+    /// Create the cache key of a variant, tagged with the render context if set
+    fn cache_key(
+        media_type: &str,
+        image_id: &str,
+        variant_name: &str,
+        variant_config: &VariantConfig,
+        format: ImageFormat,
+        context: &RenderContext,
+    ) -> CacheKey {
         let cache_key = CacheKey::new(
             media_type.to_string(),
             image_id.to_string(),
             variant_name.to_string(),
             format,
-        );
+        ).with_version(variant_config.version());
 
-        // Fast path: check cache without lock
-        if let Some(cached) = self.cache.get(&cache_key).await? {
-            return Ok((cached, format));
+        if context.tag().is_empty() {
+            cache_key
+        } else {
+            cache_key.with_context(context.tag().to_string())
         }
+    }
 
-        // Get or create generation lock for this specific cache key
-        let lock_key = cache_key.to_string_key();
-        let lock = self.generation_locks
-            .entry(lock_key.clone())
-            .or_insert_with(|| Arc::new(Mutex::new(())))
-            .clone();
+    /// Generate and cache a variant under its generation lock
+    ///
+    /// Without `force` a variant cached while waiting for the lock is
+    /// returned instead of generating it again. `background` jobs wait for a
+    /// slot of the generation pool instead of being rejected.
+    async fn generate_cached(
+        &self,
+        cache_key: &CacheKey,
+        variant_config: &VariantConfig,
+        context: &RenderContext,
+        force: bool,
+        background: bool,
+    ) -> Result<MediaBody, ManagerError> {
+        // Get or create generation lock for this specific cache key,
+        // the entry is removed again when the last waiting request is done
+        let lock = GenerationLock::acquire(&self.generation_locks, cache_key.to_string_key());
 
         // Acquire lock - ensures only one generation at a time for this key
-        let _guard = lock.lock().await;
+        let _guard = lock.lock.lock().await;
 
         // Double-check: maybe generated while waiting for lock
-        if let Some(cached) = self.cache.get(&cache_key).await? {
-            return Ok((cached, format));
+        if !force {
+            if let Some(cached) = self.cache.open(cache_key).await? {
+                return Ok(cached);
+            }
         }
 
         // We're the first - generate the variant
-        let bytes = self.generate_variant(image_id, &variant_config.effects, format).await?;
+        let bytes = self.generate_variant(&cache_key.image_id, &variant_config.effects, cache_key.format, context, background).await?;
 
         // Cache the result
-        self.cache.set(&cache_key, &bytes).await?;
+        self.cache.set(cache_key, &bytes).await?;
+
+        Ok(MediaBody::Memory(bytes))
+    }
 
-        // Clean up lock entry if possible (optional optimization)
-        // Keep lock in map for now - it's cheap memory-wise
+    /// Generate every variant of an image in all its formats
+    ///
+    /// Variants depending on a render context are skipped, they can't be
+    /// generated without one. Runs in the background: only
+    /// `BACKGROUND_CONCURRENCY` images are generated at once, one variant at a
+    /// time, and generations wait for a free slot of the pool instead of
+    /// failing when requests keep it busy.
+    /// Returns the number of generated variants.
+    async fn generate_all(&self, media_type: &str, image_id: &str, force: bool) -> Result<usize, ManagerError> {
+        let _permit = self.background_permits.acquire().await.map_err(|_| ManagerError::Overloaded)?;
+
+        let media_types = self.media_types();
+        let media_config = media_types
+            .get(media_type)
+            .ok_or_else(|| ManagerError::UnknownMediaType(media_type.to_string()))?;
 
-        Ok((bytes, format))
+        // The default variant is the most requested, generate it first
+        let mut variants: Vec<(&String, &VariantConfig)> = media_config.variants
+            .iter()
+            .filter(|(_, variant_config)| !variant_config.needs_context())
+            .collect();
+        let default = &media_config.default_variant;
+        variants.sort_by(|(a, _), (b, _)| (*a != default, a).cmp(&(*b != default, b)));
+
+        let context = RenderContext::default();
+        let mut generated = 0;
+
+        for (variant_name, variant_config) in variants {
+            for format in self.variant_formats(variant_config) {
+                let cache_key = Self::cache_key(media_type, image_id, variant_name, variant_config, format, &context);
+
+                if !force && self.cache.exists(&cache_key).await? {
+                    continue;
+                }
+
+                self.generate_cached(&cache_key, variant_config, &context, force, true).await?;
+                generated += 1;
+            }
+        }
+
+        Ok(generated)
+    }
+
+    /// Generate all variants of an image in the background
+    pub fn pregenerate(&self, media_type: &str, image_id: &str) {
+        let manager = self.clone();
+        let media_type = media_type.to_string();
+        let image_id = image_id.to_string();
+
+        tokio::spawn(async move {
+            if let Err(err) = manager.generate_all(&media_type, &image_id, false).await {
+                println!("Pre-generating {} {} failed: {}", media_type, image_id, err);
+            }
+        });
+    }
+
+    /// Regenerate all cached variants of a media type in the background
+    ///
+    /// Used after the config of a media type changed, cached images are
+    /// replaced one at a time. Only images with a cached variant of the type
+    /// are listed, others are generated on their first request as usual.
+    /// Returns the number of images queued.
+    pub async fn regenerate_media_type(&self, media_type: &str) -> Result<usize, ManagerError> {
+        if !self.media_types().contains_key(media_type) {
+            return Err(ManagerError::UnknownMediaType(media_type.to_string()));
+        }
+
+        let image_ids = self.cache.list_images(media_type).await?;
+        let count = image_ids.len();
+
+        let manager = self.clone();
+        let media_type = media_type.to_string();
+
+        tokio::spawn(async move {
+            let mut generated = 0;
+
+            for image_id in image_ids {
+                match manager.generate_all(&media_type, &image_id, true).await {
+                    Ok(count) => generated += count,
+                    Err(err) => println!("Regenerating {} {} failed: {}", media_type, image_id, err),
+                }
+            }
+
+            println!("Regenerated {} variants of {}", generated, media_type);
+        });
+
+        Ok(count)
     }
 
     /// Generate a variant by applying the effect chain
+    ///
+    /// Decoding, effects and encoding run in the generation pool
     async fn generate_variant(
         &self,
         image_id: &str,
         effects: &[EffectSpec],
         output_format: ImageFormat,
+        context: &RenderContext,
+        background: bool,
     ) -> Result<Vec<u8>, ManagerError> {
         // Load original image
         let original_bytes = self.storage.retrieve(image_id).await?;
 
-        // Decode image using OpenCV
-        let buffer = Vector::<u8>::from_slice(&original_bytes);
-        let mut image = imdecode(&buffer, IMREAD_COLOR)?;
+        // Create effect params, resolving "$name" values from the context
+        let chain: Vec<(String, EffectParams)> = effects
+            .iter()
+            .map(|effect_spec| (effect_spec.id.clone(), EffectParams::new(context.resolve(&effect_spec.params))))
+            .collect();
 
-        // Apply effect chain
-        for effect_spec in effects {
-            let effect = self.effect_registry
-                .get(&effect_spec.id)
-                .ok_or_else(|| ManagerError::UnknownEffect(effect_spec.id.clone()))?;
+        let effect_registry = self.effect_registry.clone();
+        let effects = effects.to_vec();
 
-            // Create effect params
-            let params = EffectParams::new(effect_spec.params.clone());
+        let job = move || {
+            // Decode image using OpenCV
+            let buffer = Vector::<u8>::from_slice(&original_bytes);
+            let mut image = imdecode(&buffer, IMREAD_COLOR)?;
 
-            // Apply effect
-            image = effect.apply(image, &params)?;
-        }
+            // Apply effect chain
+            for (effect_id, params) in &chain {
+                let effect = effect_registry
+                    .get(effect_id)
+                    .ok_or_else(|| ManagerError::UnknownEffect(effect_id.clone()))?;
 
-        // Encode to output format
-        let bytes = self.encode_image(image, output_format, effects)?;
+                image = effect.apply(image, params)?;
+            }
+
+            // Encode to output format
+            Self::encode_image(image, output_format, &effects)
+        };
 
-        Ok(bytes)
+        if background {
+            self.generation_pool.run_background(job).await?
+        } else {
+            self.generation_pool.run(job).await?
+        }
     }
 
     /// Encode image to bytes in the specified format
     fn encode_image(
-        &self,
         image: Mat,
         format: ImageFormat,
         effects: &[EffectSpec],
@@ -150,36 +410,68 @@ impl MediaManager {
 
         match format {
             ImageFormat::Jpeg => {
-                let quality = self.extract_quality_param(effects, "jpeg").unwrap_or(85);
+                let quality = Self::extract_quality_param(effects, "jpeg").unwrap_or(85);
                 params.push(IMWRITE_JPEG_QUALITY);
                 params.push(quality as i32);
                 imencode(".jpg", &image, &mut encoded_buffer, &params)?;
             }
             ImageFormat::Png => {
-                let compression = self.extract_compression_param(effects).unwrap_or(6);
+                let compression = Self::extract_compression_param(effects).unwrap_or(6);
                 params.push(IMWRITE_PNG_COMPRESSION);
                 params.push(compression as i32);
                 imencode(".png", &image, &mut encoded_buffer, &params)?;
             }
             ImageFormat::WebP => {
-                let quality = self.extract_quality_param(effects, "webp").unwrap_or(90);
+                let quality = Self::extract_quality_param(effects, "webp").unwrap_or(90);
                 params.push(IMWRITE_WEBP_QUALITY);
                 params.push(quality as i32);
                 imencode(".webp", &image, &mut encoded_buffer, &params)?;
             }
             ImageFormat::Avif => {
-                // AVIF not yet supported by OpenCV - would need separate library
-                // For now, fall back to WebP
-                let quality = self.extract_quality_param(effects, "avif").unwrap_or(80);
-                params.push(IMWRITE_WEBP_QUALITY);
-                params.push(quality as i32);
-                imencode(".webp", &image, &mut encoded_buffer, &params)?;
+                // OpenCV builds usually lack an AVIF writer, encode with ravif instead
+                let quality = Self::extract_quality_param(effects, "avif").unwrap_or(80);
+                let speed = Self::extract_speed_param(effects).unwrap_or(6);
+                return Ok(avif::encode(&image, quality, speed)?);
             }
         }
 
         Ok(encoded_buffer.to_vec())
     }
 
+    /// Strong ETag for a cache key
+    ///
+    /// Image ids are content hashes and the key includes the config version,
+    /// so the key identifies the generated bytes
+    fn etag(cache_key: &CacheKey) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(cache_key.to_string_key().as_bytes());
+        format!("\"{}\"", &format!("{:x}", hasher.finalize())[..32])
+    }
+
+    /// Pick the output format, negotiated if the variant declares candidate formats
+    fn select_format(&self, variant_config: &VariantConfig, accept: Option<&Accept>) -> ImageFormat {
+        let chain_format = self.determine_format(&variant_config.effects);
+
+        match accept {
+            Some(accept) if !variant_config.formats.is_empty() => {
+                negotiate_format(&variant_config.formats, accept).unwrap_or(chain_format)
+            }
+            _ => chain_format,
+        }
+    }
+
+    /// All formats a variant can be served in, the chain format is used without Accept header
+    fn variant_formats(&self, variant_config: &VariantConfig) -> Vec<ImageFormat> {
+        let mut formats = variant_config.formats.clone();
+
+        let chain_format = self.determine_format(&variant_config.effects);
+        if !formats.contains(&chain_format) {
+            formats.push(chain_format);
+        }
+
+        formats
+    }
+
     /// Determine output format by scanning effect chain
     fn determine_format(&self, effects: &[EffectSpec]) -> ImageFormat {
         let mut format = ImageFormat::Jpeg; // Default
@@ -196,7 +488,7 @@ impl MediaManager {
     }
 
     /// Extract quality parameter from effect chain (for JPEG/WebP)
-    fn extract_quality_param(&self, effects: &[EffectSpec], effect_id: &str) -> Option<u32> {
+    fn extract_quality_param(effects: &[EffectSpec], effect_id: &str) -> Option<u32> {
         effects
             .iter()
             .find(|e| e.id == effect_id)
@@ -206,7 +498,7 @@ impl MediaManager {
     }
 
     /// Extract compression parameter from effect chain (for PNG)
-    fn extract_compression_param(&self, effects: &[EffectSpec]) -> Option<u32> {
+    fn extract_compression_param(effects: &[EffectSpec]) -> Option<u32> {
         effects
             .iter()
             .find(|e| e.id == "png")
@@ -215,19 +507,76 @@ impl MediaManager {
             .map(|v| v as u32)
     }
 
+    /// Extract speed parameter from effect chain (for AVIF)
+    fn extract_speed_param(effects: &[EffectSpec]) -> Option<u32> {
+        effects
+            .iter()
+            .find(|e| e.id == "avif")
+            .and_then(|e| e.params.get("speed"))
+            .and_then(|v| v.as_u64())
+            .map(|v| v as u32)
+    }
+
     /// Upload a new image and return its hash-based ID
-    pub async fn upload_image(&self, data: &[u8]) -> Result<String, ManagerError> {
+    ///
+    /// With a media type all its variants are generated in the background,
+    /// so the first viewer doesn't wait for them
+    pub async fn upload_image(&self, data: &[u8], media_type: Option<&str>) -> Result<String, ManagerError> {
+        if let Some(media_type) = media_type {
+            if !self.media_types().contains_key(media_type) {
+                return Err(ManagerError::UnknownMediaType(media_type.to_string()));
+            }
+        }
+
         let image_id = self.storage.store(data).await?;
+
+        if let Some(media_type) = media_type {
+            self.pregenerate(media_type, &image_id);
+        }
+
         Ok(image_id)
     }
 
+    /// Hit/miss counters of the image cache (and its tiers)
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    /// Running and queued generations
+    pub fn generation_stats(&self) -> PoolStats {
+        self.generation_pool.stats()
+    }
+
+    /// Whether a variant takes the focal point of the image
+    ///
+    /// Lets routes skip the focal point lookup for variants that ignore it
+    pub fn uses_focal_point(&self, media_type: &str, variant: Option<&str>) -> Result<bool, ManagerError> {
+        let variant_name = variant.unwrap_or("default");
+
+        let media_types = self.media_types();
+        let media_config = media_types
+            .get(media_type)
+            .ok_or_else(|| ManagerError::UnknownMediaType(media_type.to_string()))?;
+
+        let variant_config = media_config.variants
+            .get(variant_name)
+            .ok_or_else(|| ManagerError::UnknownVariant(variant_name.to_string()))?;
+
+        Ok(variant_config.uses_focal_point())
+    }
+
     /// Get information about all variants for a media type and image
+    ///
+    /// Variant URLs are versioned, `focal_point` is the focal point of the
+    /// image, used for the versions of variants taking it
     pub fn get_media_info(
         &self,
         media_type: &str,
         image_id: &str,
+        focal_point: Option<FocalPoint>,
     ) -> Result<MediaInfo, ManagerError> {
-        let media_config = self.media_types
+        let media_types = self.media_types();
+        let media_config = media_types
             .get(media_type)
             .ok_or_else(|| ManagerError::UnknownMediaType(media_type.to_string()))?;
 
@@ -236,12 +585,19 @@ impl MediaManager {
         for (variant_name, variant_config) in &media_config.variants {
             let format = self.determine_format(&variant_config.effects);
 
+            let context = if variant_config.uses_focal_point() {
+                RenderContext::default().with_focal_point(focal_point)
+            } else {
+                RenderContext::default()
+            };
+
             variants.push(VariantInfo {
                 name: variant_name.clone(),
-                url: format!("/media/{}/{}/{}", media_type, image_id, variant_name),
+                url: format!("/media/{}/{}/{}?v={}", media_type, image_id, variant_name, Self::url_version(variant_config, &context)),
                 width: variant_config.metadata.width,
                 height: variant_config.metadata.height,
                 format: format.to_string(),
+                formats: variant_config.formats.iter().map(|f| f.to_string()).collect(),
                 breakpoint: variant_config.breakpoint,
             });
         }
@@ -255,6 +611,66 @@ impl MediaManager {
     }
 }
 
+/// Differences applied by `MediaManager::reload_media_types`
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReloadReport {
+    /// All media types after the reload
+    pub media_types: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+
+    /// New or changed variants of existing media types, as "{media_type}/{variant}"
+    pub changed_variants: Vec<String>,
+}
+
+impl ReloadReport {
+    fn new(old: &HashMap<String, MediaTypeConfig>, new: &HashMap<String, MediaTypeConfig>) -> Self {
+        let mut report = ReloadReport::default();
+
+        for (name, media_config) in new {
+            report.media_types.push(name.clone());
+
+            let old_config = match old.get(name) {
+                Some(old_config) => old_config,
+                None => {
+                    report.added.push(name.clone());
+                    continue;
+                }
+            };
+
+            for (variant_name, variant_config) in &media_config.variants {
+                let changed = old_config.variants
+                    .get(variant_name)
+                    .is_none_or(|old_variant| old_variant.version() != variant_config.version());
+
+                if changed {
+                    report.changed_variants.push(format!("{}/{}", name, variant_name));
+                }
+            }
+        }
+
+        report.removed = old.keys().filter(|name| !new.contains_key(*name)).cloned().collect();
+
+        report.media_types.sort();
+        report.added.sort();
+        report.removed.sort();
+        report.changed_variants.sort();
+        report
+    }
+}
+
+/// A generated or cached image variant
+#[derive(Debug)]
+pub struct MediaImage {
+    pub body: MediaBody,
+    pub format: ImageFormat,
+    /// Quoted strong ETag
+    pub etag: String,
+    /// Version of the variant in versioned `/media` URLs, see `MediaManager::get_media_info`
+    pub version: String,
+}
+
 /// Information about all variants of a media type
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct MediaInfo {
@@ -275,6 +691,8 @@ pub struct VariantInfo {
     pub width: u32,
     pub height: u32,
     pub format: String,
+    /// Candidate formats served depending on the Accept header
+    pub formats: Vec<String>,
     pub breakpoint: Option<u32>,
 }
 
@@ -288,6 +706,9 @@ pub enum ManagerError {
     StorageError(StorageError),
     EffectError(EffectError),
     OpenCVError(opencv::Error),
+    /// Too many generations running and queued
+    Overloaded,
+    GenerationFailed(tokio::task::JoinError),
 }
 
 impl std::fmt::Display for ManagerError {
@@ -300,6 +721,8 @@ impl std::fmt::Display for ManagerError {
             ManagerError::StorageError(e) => write!(f, "Storage error: {}", e),
             ManagerError::EffectError(e) => write!(f, "Effect error: {}", e),
             ManagerError::OpenCVError(e) => write!(f, "OpenCV error: {}", e),
+            ManagerError::Overloaded => write!(f, "Too many images are being generated"),
+            ManagerError::GenerationFailed(e) => write!(f, "Generation failed: {}", e),
         }
     }
 }
@@ -329,3 +752,71 @@ impl From<opencv::Error> for ManagerError {
         ManagerError::OpenCVError(err)
     }
 }
+
+impl From<PoolError> for ManagerError {
+    fn from(err: PoolError) -> Self {
+        match err {
+            PoolError::Overloaded => ManagerError::Overloaded,
+            PoolError::Failed(e) => ManagerError::GenerationFailed(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::{FilesystemCache, FilesystemStore};
+    use tempfile::TempDir;
+
+    fn media_type_json(size: u32) -> String {
+        format!(r#"{{
+            "name": "profile",
+            "defaultVariant": "default",
+            "variants": {{
+                "default": {{
+                    "effects": [{{ "id": "resize_square", "params": {{ "size": {size} }} }}, {{ "id": "webp" }}],
+                    "metadata": {{ "width": {size}, "height": {size} }},
+                    "breakpoint": null
+                }}
+            }}
+        }}"#, size = size)
+    }
+
+    #[test]
+    fn test_reload_media_types() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_dir = temp_dir.path().join("media-types");
+        std::fs::create_dir(&config_dir).unwrap();
+        std::fs::write(config_dir.join("profile.json"), media_type_json(500)).unwrap();
+
+        let manager = MediaManager::new(
+            EffectRegistry::new(),
+            HashMap::new(),
+            Arc::new(FilesystemCache::new(temp_dir.path().join("cache"))),
+            Arc::new(FilesystemStore::new(temp_dir.path().join("originals"))),
+        );
+
+        let report = manager.reload_media_types(&config_dir).unwrap();
+        assert_eq!(report.added, vec!["profile"]);
+        let version = manager.media_types()["profile"].variants["default"].version();
+
+        // Unchanged configs keep their version
+        let report = manager.reload_media_types(&config_dir).unwrap();
+        assert!(report.added.is_empty() && report.changed_variants.is_empty());
+
+        std::fs::write(config_dir.join("profile.json"), media_type_json(400)).unwrap();
+        let report = manager.reload_media_types(&config_dir).unwrap();
+        assert_eq!(report.changed_variants, vec!["profile/default"]);
+        assert_ne!(manager.media_types()["profile"].variants["default"].version(), version);
+
+        // Variant URLs carry the new version
+        let new_version = manager.media_types()["profile"].variants["default"].version();
+        let info = manager.get_media_info("profile", "abc", None).unwrap();
+        assert_eq!(info.variants[0].url, format!("/media/profile/abc/default?v={}", new_version));
+
+        // A broken file keeps the loaded configs
+        std::fs::write(config_dir.join("profile.json"), media_type_json(400).replace("webp", "gif")).unwrap();
+        assert!(manager.reload_media_types(&config_dir).is_err());
+        assert_eq!(manager.media_types()["profile"].variants["default"].metadata.width, 400);
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use super::cache::{ImageCache, CacheKey, CacheError, CacheStats};
+
+/// In-memory image cache with a byte budget and LRU eviction
+///
+/// Entries larger than a quarter of the budget are not cached, so a single
+/// large variant can't flush everything else.
+pub struct MemoryCache {
+    max_bytes: usize,
+    state: Mutex<LruState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+struct LruState {
+    entries: HashMap<CacheKey, MemoryEntry>,
+
+    /// Last access tick to key, oldest first
+    recency: BTreeMap<u64, CacheKey>,
+
+    bytes: usize,
+    tick: u64,
+}
+
+struct MemoryEntry {
+    data: Vec<u8>,
+    tick: u64,
+}
+
+impl LruState {
+    /// Mark an entry as most recently used and return its data
+    fn touch(&mut self, key: &CacheKey) -> Option<&Vec<u8>> {
+        let entry = self.entries.get_mut(key)?;
+
+        self.recency.remove(&entry.tick);
+        self.tick += 1;
+        entry.tick = self.tick;
+        self.recency.insert(self.tick, key.clone());
+
+        Some(&entry.data)
+    }
+
+    fn remove(&mut self, key: &CacheKey) -> Option<MemoryEntry> {
+        let entry = self.entries.remove(key)?;
+        self.recency.remove(&entry.tick);
+        self.bytes -= entry.data.len();
+        Some(entry)
+    }
+
+    /// Insert an entry, evicting least recently used ones to stay in budget
+    fn insert(&mut self, key: CacheKey, data: Vec<u8>, max_bytes: usize) {
+        self.remove(&key);
+
+        while self.bytes + data.len() > max_bytes {
+            match self.recency.pop_first() {
+                Some((_, oldest)) => {
+                    if let Some(entry) = self.entries.remove(&oldest) {
+                        self.bytes -= entry.data.len();
+                    }
+                }
+                None => break,
+            }
+        }
+
+        self.tick += 1;
+        self.bytes += data.len();
+        self.recency.insert(self.tick, key.clone());
+        self.entries.insert(key, MemoryEntry { data, tick: self.tick });
+    }
+}
+
+impl MemoryCache {
+    /// Create a memory cache holding at most `max_bytes` of image data
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            state: Mutex::new(LruState {
+                entries: HashMap::new(),
+                recency: BTreeMap::new(),
+                bytes: 0,
+                tick: 0,
+            }),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether an entry of this size would be cached
+    pub fn accepts(&self, len: u64) -> bool {
+        len <= (self.max_bytes / 4) as u64
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruState> {
+        // A panic while holding the lock can't leave the state half updated
+        // in a way that matters for a cache, so keep using it
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[async_trait::async_trait]
+impl ImageCache for MemoryCache {
+    async fn get(&self, key: &CacheKey) -> Result<Option<Vec<u8>>, CacheError> {
+        let data = self.lock().touch(key).cloned();
+
+        match data {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+
+        Ok(data)
+    }
+
+    async fn set(&self, key: &CacheKey, data: &[u8]) -> Result<(), CacheError> {
+        if !self.accepts(data.len() as u64) {
+            // Drop a stale smaller version, the caller replaced it
+            self.lock().remove(key);
+            return Ok(());
+        }
+
+        self.lock().insert(key.clone(), data.to_vec(), self.max_bytes);
+        Ok(())
+    }
+
+    async fn exists(&self, key: &CacheKey) -> Result<bool, CacheError> {
+        Ok(self.lock().entries.contains_key(key))
+    }
+
+    async fn delete(&self, key: &CacheKey) -> Result<(), CacheError> {
+        self.lock().remove(key);
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), CacheError> {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.recency.clear();
+        state.bytes = 0;
+        Ok(())
+    }
+
+    async fn list_images(&self, media_type: &str) -> Result<Vec<String>, CacheError> {
+        let image_ids: HashSet<String> = self.lock().entries
+            .keys()
+            .filter(|key| key.media_type == media_type)
+            .map(|key| key.image_id.clone())
+            .collect();
+
+        Ok(image_ids.into_iter().collect())
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            name: "memory",
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            bytes: Some(self.lock().bytes as u64),
+            tiers: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::ImageFormat;
+
+    fn key(id: &str) -> CacheKey {
+        CacheKey::new(
+            "card".to_string(),
+            id.to_string(),
+            "default".to_string(),
+            ImageFormat::WebP,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_lru_eviction() {
+        // 10 byte entries, 40 byte budget
+        let cache = MemoryCache::new(40);
+
+        for id in ["a", "b", "c", "d"] {
+            cache.set(&key(id), &[0; 10]).await.unwrap();
+        }
+        assert_eq!(cache.stats().bytes, Some(40));
+
+        // Use "a" so "b" becomes the least recently used
+        assert!(cache.get(&key("a")).await.unwrap().is_some());
+
+        cache.set(&key("e"), &[0; 10]).await.unwrap();
+        assert_eq!(cache.stats().bytes, Some(40));
+        assert!(cache.exists(&key("a")).await.unwrap());
+        assert!(!cache.exists(&key("b")).await.unwrap());
+        assert!(cache.exists(&key("e")).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_oversized_and_replace() {
+        let cache = MemoryCache::new(40);
+
+        // More than a quarter of the budget
+        cache.set(&key("big"), &[0; 11]).await.unwrap();
+        assert!(!cache.exists(&key("big")).await.unwrap());
+
+        cache.set(&key("a"), &[1; 10]).await.unwrap();
+        cache.set(&key("a"), &[2; 5]).await.unwrap();
+        assert_eq!(cache.stats().bytes, Some(5));
+        assert_eq!(cache.get(&key("a")).await.unwrap().unwrap(), vec![2; 5]);
+
+        cache.delete(&key("a")).await.unwrap();
+        assert_eq!(cache.stats().bytes, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_stats() {
+        let cache = MemoryCache::new(40);
+        cache.set(&key("a"), b"data").await.unwrap();
+
+        cache.get(&key("a")).await.unwrap();
+        cache.get(&key("a")).await.unwrap();
+        cache.get(&key("b")).await.unwrap();
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 1);
+    }
+}
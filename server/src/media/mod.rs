@@ -1,16 +1,34 @@
 pub mod effect;
 pub mod effect_registry;
+pub mod context;
+pub mod negotiate;
+pub mod sniff;
 pub mod effects;
 pub mod config;
 pub mod cache;
+pub mod memory_cache;
+pub mod tiered_cache;
+pub mod body;
 pub mod storage;
+pub mod s3_store;
+pub mod sql;
+pub mod pool;
+pub mod gc;
 pub mod manager;
 pub mod routes;
+pub mod focal_point;
+pub mod command;
 
 // Re-export commonly used types
 pub use effect::{ImageEffect, ImageFormat, EffectParams, EffectError};
 pub use effect_registry::EffectRegistry;
+pub use context::RenderContext;
 pub use config::{MediaTypeConfig, VariantConfig, EffectSpec, ConfigError};
-pub use cache::{ImageCache, CacheKey, CacheError, FilesystemCache};
-pub use storage::{ImageStorage, StorageError};
-pub use manager::{MediaManager, ManagerError, MediaInfo, VariantInfo};
+pub use cache::{ImageCache, CacheKey, CacheError, CacheStats, FilesystemCache};
+pub use memory_cache::MemoryCache;
+pub use tiered_cache::TieredCache;
+pub use body::MediaBody;
+pub use storage::{OriginalStore, FilesystemStore, StorageError};
+pub use gc::{MediaGc, GcOptions};
+pub use manager::{MediaManager, ManagerError, MediaImage, MediaInfo, VariantInfo};
+pub use routes::{MediaResponse, MediaError};
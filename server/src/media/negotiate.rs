@@ -0,0 +1,107 @@
+use rocket::http::{Accept, MediaType};
+use super::effect::ImageFormat;
+
+/// Pick the best candidate format for an Accept header
+///
+/// Candidates are ranked by the most specific matching media range first
+/// (image/webp over image/* over */*) and its q-value second, ties go to the
+/// earlier candidate, so candidates should be listed by server preference.
+/// AVIF and WebP are only picked if the header names them, old browsers send
+/// wildcards without decoding them. Returns None if no candidate is acceptable.
+pub fn negotiate_format(candidates: &[ImageFormat], accept: &Accept) -> Option<ImageFormat> {
+    let mut best: Option<(ImageFormat, (u8, f32))> = None;
+
+    for candidate in candidates {
+        let rank = match candidate_rank(*candidate, accept) {
+            Some((specificity, weight)) if weight > 0.0 => (specificity, weight),
+            _ => continue,
+        };
+
+        if !matches!(best, Some((_, best_rank)) if best_rank >= rank) {
+            best = Some((*candidate, rank));
+        }
+    }
+
+    best.map(|(format, _)| format)
+}
+
+/// Specificity and weight of the most specific media range matching the format
+fn candidate_rank(format: ImageFormat, accept: &Accept) -> Option<(u8, f32)> {
+    let (top, sub) = format.mime_type().split_once('/')?;
+
+    let (specificity, weight) = accept
+        .iter()
+        .filter_map(|media| {
+            let specificity = match_specificity(media.media_type(), top, sub)?;
+            Some((specificity, media.weight_or(1.0)))
+        })
+        .max_by(|(a, _), (b, _)| a.cmp(b))?;
+
+    if requires_explicit(format) && specificity < EXPLICIT {
+        return None;
+    }
+
+    Some((specificity, weight))
+}
+
+/// Specificity of a media range naming the exact type
+const EXPLICIT: u8 = 2;
+
+/// Formats browsers only decode if they list them
+fn requires_explicit(format: ImageFormat) -> bool {
+    matches!(format, ImageFormat::Avif | ImageFormat::WebP)
+}
+
+fn match_specificity(media_type: &MediaType, top: &str, sub: &str) -> Option<u8> {
+    match (media_type.top().as_str(), media_type.sub().as_str()) {
+        ("*", "*") => Some(0),
+        (t, "*") if t.eq_ignore_ascii_case(top) => Some(1),
+        (t, s) if t.eq_ignore_ascii_case(top) && s.eq_ignore_ascii_case(sub) => Some(EXPLICIT),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const CANDIDATES: [ImageFormat; 3] = [ImageFormat::Avif, ImageFormat::WebP, ImageFormat::Jpeg];
+
+    fn negotiate(accept: &str) -> Option<ImageFormat> {
+        negotiate_format(&CANDIDATES, &Accept::from_str(accept).unwrap())
+    }
+
+    #[test]
+    fn test_modern_browser() {
+        assert_eq!(negotiate("image/avif,image/webp,image/apng,image/*,*/*;q=0.8"), Some(ImageFormat::Avif));
+        assert_eq!(negotiate("image/webp,image/*;q=0.8"), Some(ImageFormat::WebP));
+        // Explicit types rank above wildcards with a higher q-value
+        assert_eq!(negotiate("image/webp;q=0.5,*/*"), Some(ImageFormat::WebP));
+        assert_eq!(negotiate("image/jpeg,image/webp;q=0.5"), Some(ImageFormat::Jpeg));
+    }
+
+    #[test]
+    fn test_browser_without_avif() {
+        // Firefox 65-92
+        assert_eq!(negotiate("image/webp,*/*"), Some(ImageFormat::WebP));
+        // Chrome 79-84
+        assert_eq!(negotiate("image/webp,image/apng,image/*,*/*;q=0.8"), Some(ImageFormat::WebP));
+    }
+
+    #[test]
+    fn test_old_client() {
+        assert_eq!(negotiate("image/jpeg"), Some(ImageFormat::Jpeg));
+        assert_eq!(negotiate("image/png,image/jpeg;q=0.5"), Some(ImageFormat::Jpeg));
+        assert_eq!(negotiate("*/*"), Some(ImageFormat::Jpeg));
+        assert_eq!(negotiate("image/*"), Some(ImageFormat::Jpeg));
+    }
+
+    #[test]
+    fn test_excluded_and_unacceptable() {
+        assert_eq!(negotiate("image/avif;q=0,image/webp"), Some(ImageFormat::WebP));
+        assert_eq!(negotiate("image/*,image/avif;q=0"), Some(ImageFormat::Jpeg));
+        assert_eq!(negotiate("text/html"), None);
+        assert_eq!(negotiate("image/webp;q=0,text/html"), None);
+    }
+}
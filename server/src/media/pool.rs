@@ -0,0 +1,218 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use serde::Serialize;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Bounded pool for CPU-heavy image work (decoding, effects, encoding)
+///
+/// Jobs run on tokio's blocking threads so they don't starve the async
+/// workers. At most `concurrency` jobs run at once and at most `max_queue`
+/// more wait for a slot, further jobs are rejected so requests fail fast
+/// instead of piling up. Background jobs (`run_background`) wait for a slot
+/// outside of the queue and are never rejected.
+pub struct GenerationPool {
+    permits: Arc<Semaphore>,
+    concurrency: usize,
+    max_queue: usize,
+    queued: AtomicUsize,
+    rejected: AtomicU64,
+}
+
+/// Current load of a `GenerationPool`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStats {
+    pub concurrency: usize,
+    pub running: usize,
+    pub queued: usize,
+    pub max_queue: usize,
+    pub rejected: u64,
+}
+
+/// Leaves the queue when dropped, also if the waiting request is cancelled
+struct QueueSlot<'a>(&'a AtomicUsize);
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl GenerationPool {
+    /// Create a pool running `concurrency` jobs at once, 0 for the number of CPUs
+    pub fn new(concurrency: usize, max_queue: usize) -> Self {
+        let concurrency = match concurrency {
+            0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+
+        Self {
+            permits: Arc::new(Semaphore::new(concurrency)),
+            concurrency,
+            max_queue,
+            queued: AtomicUsize::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// Run a job once a slot is free
+    pub async fn run<F, T>(&self, job: F) -> Result<T, PoolError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let permit = match self.permits.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                if self.queued.fetch_add(1, Ordering::SeqCst) >= self.max_queue {
+                    self.queued.fetch_sub(1, Ordering::SeqCst);
+                    self.rejected.fetch_add(1, Ordering::Relaxed);
+                    return Err(PoolError::Overloaded);
+                }
+
+                let _slot = QueueSlot(&self.queued);
+                self.permits.clone().acquire_owned().await.map_err(|_| PoolError::Overloaded)?
+            }
+        };
+
+        Self::spawn(permit, job).await
+    }
+
+    /// Run a job once a slot is free, however long that takes
+    ///
+    /// For background work like pre-generation, it doesn't count against
+    /// `max_queue`. Callers should limit how many of these wait at once.
+    pub async fn run_background<F, T>(&self, job: F) -> Result<T, PoolError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let permit = self.permits.clone().acquire_owned().await.map_err(|_| PoolError::Overloaded)?;
+
+        Self::spawn(permit, job).await
+    }
+
+    async fn spawn<F, T>(permit: OwnedSemaphorePermit, job: F) -> Result<T, PoolError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        //NOTE: the permit moves into the job, a cancelled request keeps its slot until the job is done
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            job()
+        })
+        .await
+        .map_err(PoolError::Failed)
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        PoolStats {
+            concurrency: self.concurrency,
+            running: self.concurrency - self.permits.available_permits(),
+            queued: self.queued.load(Ordering::SeqCst),
+            max_queue: self.max_queue,
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Errors that can occur when running a job
+#[derive(Debug)]
+pub enum PoolError {
+    /// All slots busy and the queue full
+    Overloaded,
+
+    /// The job panicked
+    Failed(tokio::task::JoinError),
+}
+
+impl std::fmt::Display for PoolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PoolError::Overloaded => write!(f, "Generation queue is full"),
+            PoolError::Failed(e) => write!(f, "Generation job failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PoolError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_rejects_when_queue_full() {
+        let pool = Arc::new(GenerationPool::new(1, 1));
+
+        // Occupy the only slot until released
+        let (release, blocked) = mpsc::channel::<()>();
+        let running = {
+            let pool = pool.clone();
+            tokio::spawn(async move { pool.run(move || blocked.recv().unwrap()).await })
+        };
+        while pool.stats().running == 0 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        // One job may wait
+        let queued = {
+            let pool = pool.clone();
+            tokio::spawn(async move { pool.run(|| 2).await })
+        };
+        while pool.stats().queued == 0 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        // The next one is rejected
+        assert!(matches!(pool.run(|| 3).await, Err(PoolError::Overloaded)));
+        assert_eq!(pool.stats().rejected, 1);
+
+        release.send(()).unwrap();
+        running.await.unwrap().unwrap();
+        assert_eq!(queued.await.unwrap().unwrap(), 2);
+
+        let stats = pool.stats();
+        assert_eq!(stats.running, 0);
+        assert_eq!(stats.queued, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_background_jobs_wait() {
+        let pool = Arc::new(GenerationPool::new(1, 0));
+
+        let (release, blocked) = mpsc::channel::<()>();
+        let running = {
+            let pool = pool.clone();
+            tokio::spawn(async move { pool.run(move || blocked.recv().unwrap()).await })
+        };
+        while pool.stats().running == 0 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        // Requests are rejected without a queue, background jobs wait for the slot
+        assert!(matches!(pool.run(|| 2).await, Err(PoolError::Overloaded)));
+        let background = {
+            let pool = pool.clone();
+            tokio::spawn(async move { pool.run_background(|| 3).await })
+        };
+
+        release.send(()).unwrap();
+        running.await.unwrap().unwrap();
+        assert_eq!(background.await.unwrap().unwrap(), 3);
+        assert_eq!(pool.stats().rejected, 1);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_job() {
+        let pool = GenerationPool::new(1, 0);
+
+        assert!(matches!(pool.run(|| panic!("effect failed")).await, Err(PoolError::Failed(_))));
+
+        // The slot is released again
+        assert_eq!(pool.run(|| 1).await.unwrap(), 1);
+    }
+}
@@ -1,28 +1,203 @@
-use rocket::{State, get, http::{ContentType, Status}};
+use rocket::{State, get, http::{Accept, ContentType, Status}};
+use rocket::request::Request;
+use rocket::response::{self, Responder, Response};
 use rocket::serde::json::Json;
+use std::io::{Cursor, Seek, SeekFrom};
 
-use super::manager::{MediaManager, MediaInfo};
+use crate::sql::Sql;
+use super::body::{ByteRange, FileRange, MediaBody};
+use super::context::RenderContext;
+use super::manager::{MediaManager, MediaImage, MediaInfo, ManagerError};
+use super::sql as media_sql;
+
+/// Seconds clients should wait before retrying an overloaded request
+const RETRY_AFTER: u32 = 5;
+
+/// Image response with caching headers
+///
+/// Sends a strong ETag and answers matching `If-None-Match` requests with
+/// 304. Supports single `Range` requests (206), cached files are streamed.
+/// Always sends `Vary: Accept`, variants with candidate formats depend on it
+pub struct MediaResponse {
+    image: MediaImage,
+    cache_control: &'static str,
+}
+
+impl MediaResponse {
+    /// For versioned /media URLs (`?v=<version>` matching the image), their
+    /// content never changes
+    pub fn immutable(image: MediaImage) -> Self {
+        Self {
+            image,
+            cache_control: "public, max-age=31536000, immutable",
+        }
+    }
+
+    /// For URLs whose image can change (e.g. /user/<id>/profile-image),
+    /// clients revalidate with the ETag
+    pub fn revalidate(image: MediaImage) -> Self {
+        Self {
+            image,
+            cache_control: "public, no-cache",
+        }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for MediaResponse {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let MediaImage { body, format, etag, .. } = self.image;
+
+        let not_modified = request
+            .headers()
+            .get("If-None-Match")
+            .any(|header| etag_matches(header, &etag));
+
+        let mut response = Response::build().finalize();
+
+        if not_modified {
+            response.set_status(Status::NotModified);
+        } else {
+            let len = body.len();
+
+            // If-Range: only send a part if the client still has the same image
+            let if_range = request.headers().get_one("If-Range");
+            let range = match request.headers().get_one("Range") {
+                Some(range) if if_range.is_none_or(|tag| tag.trim() == etag) => ByteRange::parse(range, len),
+                _ => ByteRange::Full,
+            };
+
+            match range {
+                ByteRange::Full => set_body(&mut response, body, 0, len)?,
+                ByteRange::Partial(start, end) => {
+                    response.set_status(Status::PartialContent);
+                    response.set_raw_header("Content-Range", format!("bytes {}-{}/{}", start, end, len));
+                    set_body(&mut response, body, start, end - start + 1)?;
+                }
+                ByteRange::Unsatisfiable => {
+                    response.set_status(Status::RangeNotSatisfiable);
+                    response.set_raw_header("Content-Range", format!("bytes */{}", len));
+                }
+            }
+
+            if range != ByteRange::Unsatisfiable {
+                // Parse Content-Type based on format
+                response.set_header(ContentType::parse_flexible(format.mime_type())
+                    .unwrap_or(ContentType::Binary));
+            }
+        }
+
+        response.set_raw_header("ETag", etag);
+        response.set_raw_header("Cache-Control", self.cache_control);
+        response.set_raw_header("Vary", "Accept");
+        response.set_raw_header("Accept-Ranges", "bytes");
+
+        Ok(response)
+    }
+}
+
+/// Set `len` bytes of the body starting at `start` as a sized response body
+///
+/// Files are streamed from disk, Rocket sets Content-Length from the size
+fn set_body(response: &mut Response<'static>, body: MediaBody, start: u64, len: u64) -> Result<(), Status> {
+    match body {
+        MediaBody::Memory(mut bytes) => {
+            bytes.truncate((start + len) as usize);
+            bytes.drain(..start as usize);
+            response.set_sized_body(bytes.len(), Cursor::new(bytes));
+        }
+        MediaBody::File(file, file_len) if start == 0 && len == file_len => {
+            response.set_sized_body(len as usize, file);
+        }
+        MediaBody::File(file, _) => {
+            // Seeking an unused file handle doesn't block
+            let mut file = file.try_into_std().map_err(|_| Status::InternalServerError)?;
+            file.seek(SeekFrom::Start(start)).map_err(|_| Status::InternalServerError)?;
+
+            response.set_sized_body(len as usize, FileRange::new(tokio::fs::File::from_std(file), start, len));
+        }
+    }
+
+    Ok(())
+}
+
+/// Error of an image route
+///
+/// Overload is answered with 503 and `Retry-After` so clients back off
+/// instead of piling up, anything else with its status
+#[derive(Debug)]
+pub enum MediaError {
+    Status(Status),
+    Overloaded,
+}
+
+impl From<Status> for MediaError {
+    fn from(status: Status) -> Self {
+        MediaError::Status(status)
+    }
+}
+
+impl From<ManagerError> for MediaError {
+    fn from(err: ManagerError) -> Self {
+        match err {
+            ManagerError::Overloaded => MediaError::Overloaded,
+            _ => MediaError::Status(Status::NotFound),
+        }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for MediaError {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        match self {
+            MediaError::Status(status) => status.respond_to(request),
+            MediaError::Overloaded => Response::build()
+                .status(Status::ServiceUnavailable)
+                .raw_header("Retry-After", RETRY_AFTER.to_string())
+                .ok(),
+        }
+    }
+}
+
+/// Check an If-None-Match header value against a quoted ETag
+///
+/// Uses weak comparison as required for If-None-Match, so W/ prefixes match
+fn etag_matches(header: &str, etag: &str) -> bool {
+    header
+        .split(',')
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
 
 /// Get default variant of a media type
-#[get("/media/<media_type>/<image_id>")]
+/// Ranked explicitly, Rocket's default rank would win over /media/unlocked/<card_unlocked_id> (rank 0)
+#[get("/media/<media_type>/<image_id>?<v>", rank = 1)]
 pub async fn get_media_default(
     media_type: String,
     image_id: String,
+    v: Option<String>,
+    accept: Option<&Accept>,
+    sql: &State<Sql>,
     media_manager: &State<MediaManager>,
-) -> Result<(ContentType, Vec<u8>), Status> {
-    get_media_variant(media_type, image_id, None, media_manager).await
+) -> Result<MediaResponse, MediaError> {
+    get_media_variant(media_type, image_id, None, v, accept, sql, media_manager).await
 }
 
 /// Get metadata about all variants for responsive images
 /// Ranked higher (lower number) to match before the generic variant route
-#[get("/media/<media_type>/<image_id>/info", rank = 1)]
+/// and /media/unlocked/<card_unlocked_id>/<variant> (rank 1)
+#[get("/media/<media_type>/<image_id>/info", rank = 0)]
 pub async fn get_media_info(
     media_type: String,
     image_id: String,
+    sql: &State<Sql>,
     media_manager: &State<MediaManager>,
 ) -> Result<Json<MediaInfo>, String> {
+    // The focal point is part of the URL version of variants taking it
+    let focal_point = media_sql::get_focal_point(sql, &image_id)
+        .await
+        .map_err(|e| format!("Failed to get focal point: {}", e))?;
+
     let info = media_manager
-        .get_media_info(&media_type, &image_id)
+        .get_media_info(&media_type, &image_id, focal_point)
         .map_err(|e| format!("Failed to get media info: {}", e))?;
 
     Ok(Json(info))
@@ -30,34 +205,56 @@ pub async fn get_media_info(
 
 /// Get specific variant of a media type
 /// Ranked lower (higher number) so "info" route is tried first
-#[get("/media/<media_type>/<image_id>/<variant>", rank = 2)]
+#[get("/media/<media_type>/<image_id>/<variant>?<v>", rank = 2)]
 pub async fn get_media(
     media_type: String,
     image_id: String,
     variant: String,
+    v: Option<String>,
+    accept: Option<&Accept>,
+    sql: &State<Sql>,
     media_manager: &State<MediaManager>,
-) -> Result<(ContentType, Vec<u8>), Status> {
-    get_media_variant(media_type, image_id, Some(variant), media_manager).await
+) -> Result<MediaResponse, MediaError> {
+    get_media_variant(media_type, image_id, Some(variant), v, accept, sql, media_manager).await
 }
 
 /// Common handler for media retrieval
+///
+/// `version` is the `v` query of versioned URLs, see `MediaManager::get_media_info`
 async fn get_media_variant(
     media_type: String,
     image_id: String,
     variant: Option<String>,
+    version: Option<String>,
+    accept: Option<&Accept>,
+    sql: &State<Sql>,
     media_manager: &State<MediaManager>,
-) -> Result<(ContentType, Vec<u8>), Status> {
-    // Get image with dynamic format
-    let (bytes, format) = media_manager
-        .get_image(&media_type, &image_id, variant.as_deref())
-        .await
-        .map_err(|_| Status::NotFound)?;
+) -> Result<MediaResponse, MediaError> {
+    // Crops are centered on the focal point of the image if it has one, like on the entity routes.
+    // Only variants taking "$focalX"/"$focalY" need it
+    let uses_focal_point = media_manager.uses_focal_point(&media_type, variant.as_deref())?;
+    let context = if uses_focal_point {
+        match media_sql::get_focal_point(sql, &image_id).await {
+            Ok(focal_point) => RenderContext::default().with_focal_point(focal_point),
+            Err(_) => return Err(Status::InternalServerError.into())
+        }
+    } else {
+        RenderContext::default()
+    };
 
-    // Parse Content-Type based on format
-    let content_type = ContentType::parse_flexible(format.mime_type())
-        .unwrap_or(ContentType::Binary);
+    // Get image with the format negotiated from the Accept header
+    let image = media_manager
+        .get_composite_image(&media_type, &image_id, variant.as_deref(), &context, accept)
+        .await?;
 
-    Ok((content_type, bytes))
+    // The image id is the content hash and the version covers the variant config
+    // and focal point, so a versioned URL always maps to the same bytes.
+    // Unversioned or stale URLs can change with a reload or a focal point change
+    if version.as_deref() == Some(image.version.as_str()) {
+        Ok(MediaResponse::immutable(image))
+    } else {
+        Ok(MediaResponse::revalidate(image))
+    }
 }
 
 /// Get all routes for the media module
@@ -79,7 +276,7 @@ mod tests {
     use tempfile::TempDir;
 
     use crate::media::{
-        EffectRegistry, MediaTypeConfig, FilesystemCache, ImageStorage, MediaManager,
+        EffectRegistry, MediaTypeConfig, FilesystemCache, FilesystemStore, MediaManager,
     };
 
     fn create_test_rocket() -> rocket::Rocket<Build> {
@@ -90,7 +287,7 @@ mod tests {
         // Create components
         let registry = EffectRegistry::new();
         let cache = Arc::new(FilesystemCache::new(cache_dir.path()));
-        let storage = Arc::new(ImageStorage::new(storage_dir.path()));
+        let storage = Arc::new(FilesystemStore::new(storage_dir.path()));
         let media_types = HashMap::new(); // Empty for testing
 
         let manager = MediaManager::new(registry, media_types, cache, storage);
@@ -100,6 +297,105 @@ mod tests {
             .mount("/", routes())
     }
 
+    #[test]
+    fn test_etag_matches() {
+        let etag = "\"abc\"";
+
+        assert!(etag_matches("\"abc\"", etag));
+        assert!(etag_matches("\"old\", W/\"abc\"", etag));
+        assert!(etag_matches("*", etag));
+        assert!(!etag_matches("\"old\"", etag));
+        assert!(!etag_matches("abc", etag));
+    }
+
+    fn image() -> MediaImage {
+        MediaImage {
+            body: MediaBody::Memory(b"0123456789".to_vec()),
+            format: crate::media::ImageFormat::Png,
+            etag: "\"abc\"".to_string(),
+            version: "v1".to_string(),
+        }
+    }
+
+    #[get("/test-image")]
+    fn test_image() -> MediaResponse {
+        MediaResponse::revalidate(image())
+    }
+
+    #[get("/test-immutable")]
+    fn test_immutable() -> MediaResponse {
+        MediaResponse::immutable(image())
+    }
+
+    #[test]
+    fn test_media_response() {
+        use rocket::http::Header;
+
+        let client = Client::tracked(rocket::build().mount("/", routes![test_image, test_immutable])).unwrap();
+
+        let response = client.get("/test-image").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.headers().get_one("ETag"), Some("\"abc\""));
+        assert_eq!(response.headers().get_one("Cache-Control"), Some("public, no-cache"));
+        assert_eq!(response.headers().get_one("Content-Length"), Some("10"));
+        assert_eq!(response.content_type(), Some(ContentType::PNG));
+
+        let response = client.get("/test-immutable").dispatch();
+        assert_eq!(response.headers().get_one("Cache-Control"), Some("public, max-age=31536000, immutable"));
+
+        let response = client.get("/test-image").header(Header::new("If-None-Match", "\"abc\"")).dispatch();
+        assert_eq!(response.status(), Status::NotModified);
+
+        let response = client.get("/test-image").header(Header::new("Range", "bytes=2-5")).dispatch();
+        assert_eq!(response.status(), Status::PartialContent);
+        assert_eq!(response.headers().get_one("Content-Range"), Some("bytes 2-5/10"));
+        assert_eq!(response.into_bytes().unwrap(), b"2345");
+
+        // Stale If-Range gets the whole image
+        let response = client.get("/test-image")
+            .header(Header::new("Range", "bytes=2-5"))
+            .header(Header::new("If-Range", "\"old\""))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+
+        let response = client.get("/test-image").header(Header::new("Range", "bytes=20-")).dispatch();
+        assert_eq!(response.status(), Status::RangeNotSatisfiable);
+        assert_eq!(response.headers().get_one("Content-Range"), Some("bytes */10"));
+    }
+
+    #[get("/test-overloaded")]
+    fn test_overloaded() -> Result<MediaResponse, MediaError> {
+        Err(ManagerError::Overloaded.into())
+    }
+
+    #[test]
+    fn test_overloaded_response() {
+        let client = Client::tracked(rocket::build().mount("/", routes![test_overloaded])).unwrap();
+
+        let response = client.get("/test-overloaded").dispatch();
+        assert_eq!(response.status(), Status::ServiceUnavailable);
+        assert_eq!(response.headers().get_one("Retry-After"), Some("5"));
+    }
+
+    // Same rank as the unlocked card route
+    #[get("/media/unlocked/<_card_unlocked_id>/<_variant>", rank = 1)]
+    fn test_unlocked_variant(_card_unlocked_id: String, _variant: String) -> &'static str {
+        "variant"
+    }
+
+    #[test]
+    fn test_info_route_rank() {
+        let rocket = create_test_rocket().mount("/", routes![test_unlocked_variant]);
+        let client = Client::tracked(rocket).unwrap();
+
+        // "info" isn't taken as a variant of the unlocked card route
+        let response = client.get("/media/unlocked/card/info").dispatch();
+        assert_ne!(response.into_string().unwrap(), "variant");
+
+        let response = client.get("/media/unlocked/card/default").dispatch();
+        assert_eq!(response.into_string().unwrap(), "variant");
+    }
+
     #[test]
     fn test_routes_mount() {
         // Just verify routes can be created and mounted
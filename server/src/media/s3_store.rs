@@ -0,0 +1,237 @@
+use std::time::SystemTime;
+use s3::bucket::Bucket;
+use s3::creds::Credentials;
+use s3::error::S3Error;
+use s3::region::Region;
+use super::storage::{OriginalStore, StoredImage, StorageError, content_id};
+
+/// Connection settings of an `S3Store`
+#[derive(Debug, Clone)]
+pub struct S3Options {
+    /// e.g. "http://localhost:9000" for MinIO or "https://s3.eu-central-1.amazonaws.com"
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub access_key: String,
+    pub secret_key: String,
+    /// Prepended to all object keys, e.g. "originals/"
+    pub prefix: String,
+}
+
+/// Originals in an S3-compatible bucket (AWS S3, MinIO, ...)
+///
+/// Objects are stored as `{prefix}{id}.bin`, buckets don't need sharding.
+/// Requests are path-style, which MinIO expects and AWS still supports.
+pub struct S3Store {
+    bucket: Box<Bucket>,
+    prefix: String,
+}
+
+impl S3Store {
+    /// Create a store, nothing is requested until `init`
+    pub fn new(options: S3Options) -> Result<Self, StorageError> {
+        let bucket_error = |e: S3Error| StorageError::S3Error(options.bucket.clone(), e);
+
+        let credentials = Credentials::new(
+            Some(&options.access_key),
+            Some(&options.secret_key),
+            None,
+            None,
+            None,
+        ).map_err(|e| bucket_error(e.into()))?;
+
+        let region = Region::Custom {
+            region: options.region.clone(),
+            endpoint: options.endpoint.clone(),
+        };
+        let bucket = Bucket::new(&options.bucket, region, credentials)
+            .map_err(bucket_error)?
+            .with_path_style();
+
+        Ok(Self {
+            bucket,
+            prefix: options.prefix,
+        })
+    }
+
+    fn key(&self, image_id: &str) -> String {
+        format!("{}{}.bin", self.prefix, image_id)
+    }
+
+    /// Image id of an object key, None for objects outside the layout
+    fn image_id(&self, key: &str) -> Option<String> {
+        key.strip_prefix(&self.prefix)?
+            .strip_suffix(".bin")
+            .filter(|image_id| !image_id.contains('/'))
+            .map(str::to_string)
+    }
+
+    fn check_status(key: &str, status: u16) -> Result<(), StorageError> {
+        match status {
+            200..=299 => Ok(()),
+            404 => Err(StorageError::NotFound(key.to_string())),
+            status => Err(StorageError::S3Status(key.to_string(), status)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl OriginalStore for S3Store {
+    /// Check that the bucket exists and the credentials work
+    async fn init(&self) -> Result<(), StorageError> {
+        let bucket_name = self.bucket.name();
+
+        match self.bucket.exists().await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(StorageError::NotFound(bucket_name)),
+            Err(e) => Err(StorageError::S3Error(bucket_name, e)),
+        }
+    }
+
+    async fn store(&self, data: &[u8]) -> Result<String, StorageError> {
+        let hash = content_id(data);
+
+        // Deduplication, saves the upload
+        if self.exists(&hash).await? {
+            return Ok(hash);
+        }
+
+        self.store_named(&hash, data).await?;
+
+        Ok(hash)
+    }
+
+    async fn store_named(&self, image_id: &str, data: &[u8]) -> Result<(), StorageError> {
+        let key = self.key(image_id);
+        let response = self.bucket
+            .put_object(&key, data)
+            .await
+            .map_err(|e| StorageError::S3Error(key.clone(), e))?;
+        Self::check_status(&key, response.status_code())
+    }
+
+    async fn retrieve(&self, image_id: &str) -> Result<Vec<u8>, StorageError> {
+        let key = self.key(image_id);
+        let response = self.bucket
+            .get_object(&key)
+            .await
+            .map_err(|e| StorageError::S3Error(key.clone(), e))?;
+
+        match Self::check_status(&key, response.status_code()) {
+            Ok(()) => Ok(response.bytes().to_vec()),
+            Err(StorageError::NotFound(_)) => Err(StorageError::NotFound(image_id.to_string())),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn exists(&self, image_id: &str) -> Result<bool, StorageError> {
+        let key = self.key(image_id);
+        let (_, status) = self.bucket
+            .head_object(&key)
+            .await
+            .map_err(|e| StorageError::S3Error(key.clone(), e))?;
+
+        match Self::check_status(&key, status) {
+            Ok(()) => Ok(true),
+            Err(StorageError::NotFound(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn delete(&self, image_id: &str) -> Result<(), StorageError> {
+        let key = self.key(image_id);
+        let response = self.bucket
+            .delete_object(&key)
+            .await
+            .map_err(|e| StorageError::S3Error(key.clone(), e))?;
+
+        // S3 answers 204 for missing objects too
+        match Self::check_status(&key, response.status_code()) {
+            Err(StorageError::NotFound(_)) => Ok(()),
+            result => result,
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<StoredImage>, StorageError> {
+        let pages = self.bucket
+            .list(self.prefix.clone(), None)
+            .await
+            .map_err(|e| StorageError::S3Error(self.prefix.clone(), e))?;
+
+        let images = pages
+            .into_iter()
+            .flat_map(|page| page.contents)
+            .filter_map(|object| {
+                let image_id = self.image_id(&object.key)?;
+
+                //NOTE: an unparsable date makes the image look new, the GC skips it until its grace period
+                let modified = chrono::DateTime::parse_from_rfc3339(&object.last_modified)
+                    .map(SystemTime::from)
+                    .unwrap_or_else(|_| SystemTime::now());
+
+                Some(StoredImage {
+                    image_id,
+                    len: object.size,
+                    modified,
+                })
+            })
+            .collect();
+
+        Ok(images)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(prefix: &str) -> S3Options {
+        let env = |name: &str, default: &str| std::env::var(name).unwrap_or_else(|_| default.to_string());
+
+        S3Options {
+            endpoint: env("MEDIA_S3_TEST_ENDPOINT", "http://localhost:9000"),
+            region: String::from("us-east-1"),
+            bucket: env("MEDIA_S3_TEST_BUCKET", "media-test"),
+            access_key: env("MEDIA_S3_TEST_ACCESS_KEY", "minioadmin"),
+            secret_key: env("MEDIA_S3_TEST_SECRET_KEY", "minioadmin"),
+            prefix: prefix.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_image_id() {
+        let store = S3Store::new(options("originals/")).unwrap();
+
+        assert_eq!(store.key("abc"), "originals/abc.bin");
+        assert_eq!(store.image_id("originals/abc.bin"), Some(String::from("abc")));
+        assert_eq!(store.image_id("originals/nested/abc.bin"), None);
+        assert_eq!(store.image_id("other/abc.bin"), None);
+        assert_eq!(store.image_id("originals/abc.txt"), None);
+    }
+
+    /// Needs a local MinIO with the bucket "media-test":
+    /// `docker run -p 9000:9000 minio/minio server /data`, then
+    /// `cargo test s3_store -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn test_minio() {
+        let prefix = format!("test-{}/", std::process::id());
+        let store = S3Store::new(options(&prefix)).unwrap();
+        store.init().await.unwrap();
+
+        let image_id = store.store(b"s3 image").await.unwrap();
+        assert_eq!(image_id, content_id(b"s3 image"));
+        assert_eq!(store.store(b"s3 image").await.unwrap(), image_id);
+
+        assert!(store.exists(&image_id).await.unwrap());
+        assert_eq!(store.retrieve(&image_id).await.unwrap(), b"s3 image");
+
+        let listed: Vec<String> = store.list().await.unwrap().into_iter().map(|image| image.image_id).collect();
+        assert_eq!(listed, vec![image_id.clone()]);
+
+        store.delete(&image_id).await.unwrap();
+        assert!(!store.exists(&image_id).await.unwrap());
+        assert!(matches!(store.retrieve(&image_id).await, Err(StorageError::NotFound(_))));
+        store.delete(&image_id).await.unwrap();
+    }
+}
@@ -0,0 +1,151 @@
+use super::effect::ImageFormat;
+
+/// Detect the real format of image data from its magic bytes
+///
+/// File names and Content-Type headers are chosen by the client, the first
+/// bytes are what decoders actually look at
+pub fn sniff_format(data: &[u8]) -> Option<ImageFormat> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(ImageFormat::Jpeg)
+    } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(ImageFormat::Png)
+    } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        Some(ImageFormat::WebP)
+    } else if data.len() >= 12 && &data[4..8] == b"ftyp" && matches!(&data[8..12], b"avif" | b"avis") {
+        Some(ImageFormat::Avif)
+    } else {
+        None
+    }
+}
+
+/// Width and height from the image header, without decoding the image
+///
+/// Used to reject decompression bombs before allocating their pixels.
+/// None if the header is malformed or the format isn't supported.
+pub fn dimensions(format: ImageFormat, data: &[u8]) -> Option<(u32, u32)> {
+    match format {
+        ImageFormat::Png => png_dimensions(data),
+        ImageFormat::Jpeg => jpeg_dimensions(data),
+        ImageFormat::WebP => webp_dimensions(data),
+        ImageFormat::Avif => None,
+    }
+}
+
+fn be_u16(data: &[u8], at: usize) -> Option<u32> {
+    let bytes = data.get(at..at + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]) as u32)
+}
+
+fn be_u32(data: &[u8], at: usize) -> Option<u32> {
+    let bytes = data.get(at..at + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn le_u24(data: &[u8], at: usize) -> Option<u32> {
+    let bytes = data.get(at..at + 3)?;
+    Some(bytes[0] as u32 | (bytes[1] as u32) << 8 | (bytes[2] as u32) << 16)
+}
+
+/// IHDR is always the first chunk
+fn png_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    if data.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    Some((be_u32(data, 16)?, be_u32(data, 20)?))
+}
+
+/// Walk the segments up to the first start of frame
+fn jpeg_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let mut pos = 2;
+
+    loop {
+        // Markers can be padded with any number of 0xFF
+        while *data.get(pos)? == 0xFF && *data.get(pos + 1)? == 0xFF {
+            pos += 1;
+        }
+        if *data.get(pos)? != 0xFF {
+            return None;
+        }
+
+        let marker = *data.get(pos + 1)?;
+        match marker {
+            // SOF0-SOF15, except DHT (C4), JPG (C8) and DAC (CC)
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                let height = be_u16(data, pos + 5)?;
+                let width = be_u16(data, pos + 7)?;
+                return Some((width, height));
+            }
+            // Start of scan or end of image before any frame
+            0xDA | 0xD9 => return None,
+            // Standalone markers without length
+            0x01 | 0xD0..=0xD7 => pos += 2,
+            _ => pos += 2 + be_u16(data, pos + 2)? as usize,
+        }
+    }
+}
+
+/// Lossy (VP8), lossless (VP8L) and extended (VP8X) files
+fn webp_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    match data.get(12..16)? {
+        b"VP8X" => Some((le_u24(data, 24)? + 1, le_u24(data, 27)? + 1)),
+        b"VP8 " => {
+            if data.get(23..26)? != [0x9D, 0x01, 0x2A] {
+                return None;
+            }
+            let width = le_u24(data, 26)? & 0x3FFF;
+            let height = le_u24(data, 28)? & 0x3FFF;
+            Some((width, height))
+        }
+        b"VP8L" => {
+            if *data.get(20)? != 0x2F {
+                return None;
+            }
+            let bits = le_u24(data, 21)? | (*data.get(24)? as u32) << 24;
+            Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{Mat, Scalar, Vector, CV_8UC3};
+    use opencv::imgcodecs::imencode;
+
+    fn encode(extension: &str, width: i32, height: i32, params: &[i32]) -> Vec<u8> {
+        let image = Mat::new_rows_cols_with_default(height, width, CV_8UC3, Scalar::new(40.0, 80.0, 120.0, 0.0)).unwrap();
+        let mut buffer = Vector::<u8>::new();
+        imencode(extension, &image, &mut buffer, &Vector::from_slice(params)).unwrap();
+        buffer.to_vec()
+    }
+
+    #[test]
+    fn test_sniff_and_dimensions() {
+        use opencv::imgcodecs::IMWRITE_WEBP_QUALITY;
+
+        let cases = [
+            (encode(".jpg", 64, 32, &[]), ImageFormat::Jpeg),
+            (encode(".png", 64, 32, &[]), ImageFormat::Png),
+            (encode(".webp", 64, 32, &[IMWRITE_WEBP_QUALITY, 80]), ImageFormat::WebP),
+            // Lossless
+            (encode(".webp", 64, 32, &[IMWRITE_WEBP_QUALITY, 101]), ImageFormat::WebP),
+        ];
+
+        for (data, format) in cases {
+            assert_eq!(sniff_format(&data), Some(format));
+            assert_eq!(dimensions(format, &data), Some((64, 32)));
+        }
+    }
+
+    #[test]
+    fn test_unknown_and_truncated() {
+        assert_eq!(sniff_format(b"GIF89a......"), None);
+        assert_eq!(sniff_format(b"<svg xmlns="), None);
+        assert_eq!(sniff_format(&[]), None);
+
+        let jpeg = encode(".jpg", 64, 32, &[]);
+        assert_eq!(dimensions(ImageFormat::Jpeg, &jpeg[..20]), None);
+        assert_eq!(dimensions(ImageFormat::Png, b"\x89PNG\r\n\x1a\n"), None);
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashSet;
+
+use crate::sql::Sql;
+use crate::shared::Id;
+use super::context::FocalPoint;
+
+/// All image ids referenced by users, collectors and cards
+pub async fn get_referenced_images(sql: &Sql) -> Result<HashSet<String>, sqlx::Error> {
+    let rows: Vec<(String,)> = sqlx::query_as(
+        "SELECT uprofileimage FROM users WHERE uprofileimage IS NOT NULL
+         UNION SELECT coimage FROM collectors WHERE coimage IS NOT NULL
+         UNION SELECT cobanner FROM collectors WHERE cobanner IS NOT NULL
+         UNION SELECT cimage FROM cards WHERE cimage IS NOT NULL;")
+        .fetch_all(sql.pool())
+        .await?;
+
+    Ok(rows.into_iter().map(|(image_id,)| image_id).collect())
+}
+
+pub async fn get_focal_point(sql: &Sql, image_id: &str) -> Result<Option<FocalPoint>, sqlx::Error> {
+    let stmt: Result<(f32, f32), sqlx::Error> = sqlx::query_as(
+        "SELECT ifpx, ifpy
+         FROM imagefocalpoints
+         WHERE ifpimage=?;")
+        .bind(image_id)
+        .fetch_one(sql.pool())
+        .await;
+
+    if let Err(sqlx::Error::RowNotFound) = stmt {
+        return Ok(None);
+    }
+
+    let (x, y) = stmt?;
+    Ok(Some(FocalPoint { x, y }))
+}
+
+pub async fn set_focal_point(sql: &Sql, image_id: &str, focal_point: FocalPoint) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO imagefocalpoints
+         (ifpimage, ifpx, ifpy)
+         VALUES
+         (?, ?, ?)
+         ON DUPLICATE KEY UPDATE ifpx=VALUES(ifpx), ifpy=VALUES(ifpy);")
+        .bind(image_id)
+        .bind(focal_point.x)
+        .bind(focal_point.y)
+        .execute(sql.pool())
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_focal_point(sql: &Sql, image_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM imagefocalpoints
+         WHERE ifpimage=?;")
+        .bind(image_id)
+        .execute(sql.pool())
+        .await?;
+
+    Ok(())
+}
+
+/// Whether the user uploaded the image or moderates a collector using it
+///
+/// Covers the own profile image, collector images and banners of owned or
+/// moderated collectors and card images of own cards or cards in those collectors
+pub async fn can_edit_image(sql: &Sql, image_id: &str, user_id: &Id) -> Result<bool, sqlx::Error> {
+    let (count, ): (i32, ) = sqlx::query_as(
+        "SELECT
+            (SELECT COUNT(*) FROM users WHERE uprofileimage = ? AND uid = ?) +
+            (SELECT COUNT(*) FROM collectors co
+             WHERE (co.coimage = ? OR co.cobanner = ?)
+             AND (co.uid = ? OR co.coid IN (SELECT coid FROM collectormoderators WHERE uid = ?))) +
+            (SELECT COUNT(*) FROM cards c
+             JOIN cardtypes ct ON ct.ctid = c.ctid
+             JOIN collectors co ON co.coid = ct.coid
+             WHERE c.cimage = ?
+             AND (c.uid = ? OR co.uid = ? OR co.coid IN (SELECT coid FROM collectormoderators WHERE uid = ?)))
+        AS count;")
+        .bind(image_id)
+        .bind(user_id)
+        .bind(image_id)
+        .bind(image_id)
+        .bind(user_id)
+        .bind(user_id)
+        .bind(image_id)
+        .bind(user_id)
+        .bind(user_id)
+        .bind(user_id)
+        .fetch_one(sql.pool())
+        .await?;
+
+    Ok(count != 0)
+}
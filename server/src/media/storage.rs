@@ -1,33 +1,277 @@
 use std::path::{Path, PathBuf};
 use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
 use tokio::fs;
+use super::s3_store::{S3Store, S3Options};
+
+/// An original image in a store
+#[derive(Debug, Clone)]
+pub struct StoredImage {
+    pub image_id: String,
+    pub len: u64,
+    pub modified: SystemTime,
+}
+
+/// Backend holding the original (source) images
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// Sharded directory, see `FilesystemStore`
+    Filesystem,
+    /// S3-compatible bucket, see `S3Store`
+    S3,
+}
+
+impl std::str::FromStr for StorageBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "filesystem" => Ok(StorageBackend::Filesystem),
+            "s3" => Ok(StorageBackend::S3),
+            other => Err(format!("Unknown storage backend: {}", other)),
+        }
+    }
+}
+
+/// Create the store of a backend from the config
+pub fn open_store(backend: StorageBackend, config: &crate::config::Config) -> Result<Arc<dyn OriginalStore>, StorageError> {
+    match backend {
+        StorageBackend::Filesystem => Ok(Arc::new(FilesystemStore::new(&config.media_storage_dir))),
+        StorageBackend::S3 => Ok(Arc::new(S3Store::new(S3Options {
+            endpoint: config.media_s3_endpoint.clone(),
+            region: config.media_s3_region.clone(),
+            bucket: config.media_s3_bucket.clone(),
+            access_key: config.media_s3_access_key.clone(),
+            secret_key: config.media_s3_secret_key.clone(),
+            prefix: config.media_s3_prefix.clone(),
+        })?)),
+    }
+}
+
+/// Trait for original image storage implementations
+///
+/// Images are content-addressed, the id is the SHA-256 of the data (see
+/// `content_id`), so storing the same data twice is a no-op.
+#[async_trait::async_trait]
+pub trait OriginalStore: Send + Sync {
+    /// Prepare the store, e.g. create its directory
+    async fn init(&self) -> Result<(), StorageError>;
+
+    /// Store an image and return its hash-based ID
+    async fn store(&self, data: &[u8]) -> Result<String, StorageError>;
+
+    /// Store an image under a fixed id, for named defaults like `card-image-default`
+    async fn store_named(&self, image_id: &str, data: &[u8]) -> Result<(), StorageError>;
+
+    /// Retrieve an image by its hash ID
+    async fn retrieve(&self, image_id: &str) -> Result<Vec<u8>, StorageError>;
+
+    /// Check if an image exists
+    async fn exists(&self, image_id: &str) -> Result<bool, StorageError>;
+
+    /// Delete an image, deleting a missing image is not an error
+    async fn delete(&self, image_id: &str) -> Result<(), StorageError>;
+
+    /// List all stored images
+    async fn list(&self) -> Result<Vec<StoredImage>, StorageError>;
+}
+
+/// SHA-256 hex of image data, the id of an original
+pub fn content_id(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    let result = hasher.finalize();
+    format!("{:x}", result)
+}
+
+/// SHA-256 hex ids as created by `OriginalStore::store`
+pub fn is_content_id(image_id: &str) -> bool {
+    image_id.len() == 64 && image_id.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Result of `copy_originals`
+#[derive(Debug, Clone, Default)]
+pub struct CopyReport {
+    pub copied: usize,
+    /// Already in the target with the right content
+    pub skipped: usize,
+    /// Ids whose data doesn't match their hash (named defaults: the source), in the source or after copying
+    pub mismatched: Vec<String>,
+}
+
+/// Copy all originals from one store to another and verify their hashes
+///
+/// Source data is checked against its id before copying and the copy is
+/// read back and checked again. Mismatches are reported, not copied over.
+/// Images already in the target are verified instead of copied, so an
+/// interrupted copy can be run again. Named defaults like
+/// `card-image-default` have no hash, they are compared byte by byte.
+pub async fn copy_originals(from: &dyn OriginalStore, to: &dyn OriginalStore) -> Result<CopyReport, StorageError> {
+    let mut report = CopyReport::default();
+
+    for image in from.list().await? {
+        let image_id = image.image_id;
+        let named = if is_content_id(&image_id) { None } else { Some(from.retrieve(&image_id).await?) };
+
+        if to.exists(&image_id).await? {
+            if matches_original(&image_id, &to.retrieve(&image_id).await?, named.as_deref()) {
+                report.skipped += 1;
+            } else {
+                report.mismatched.push(image_id);
+            }
+            continue;
+        }
+
+        match &named {
+            Some(data) => to.store_named(&image_id, data).await?,
+            None => {
+                let data = from.retrieve(&image_id).await?;
+                if content_id(&data) != image_id {
+                    report.mismatched.push(image_id);
+                    continue;
+                }
+
+                to.store(&data).await?;
+            }
+        }
+
+        if matches_original(&image_id, &to.retrieve(&image_id).await?, named.as_deref()) {
+            report.copied += 1;
+        } else {
+            report.mismatched.push(image_id);
+        }
+    }
 
-/// Storage for original (source) images using content-addressable hashing
-pub struct ImageStorage {
+    Ok(report)
+}
+
+/// Content ids are checked against their hash, named images against the source data
+fn matches_original(image_id: &str, data: &[u8], named: Option<&[u8]>) -> bool {
+    match named {
+        Some(source) => data == source,
+        None => content_id(data) == image_id,
+    }
+}
+
+/// Makes the temporary files of concurrent writes unique
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Filesystem storage for originals
+///
+/// Layout `{base_path}/{ab}/{cd}/{abcd...}.bin`, sharded by the first two
+/// bytes of the hash so no directory holds more than a few hundred entries.
+/// Named defaults (`card-image-default.bin`, ...) stay in `{base_path}`.
+pub struct FilesystemStore {
     base_path: PathBuf,
 }
 
-impl ImageStorage {
-    /// Create a new image storage
+impl FilesystemStore {
+    /// Create a new filesystem store
     pub fn new<P: Into<PathBuf>>(base_path: P) -> Self {
         Self {
             base_path: base_path.into(),
         }
     }
 
-    /// Initialize storage directory
-    pub async fn init(&self) -> Result<(), StorageError> {
+    /// Get file path for an image ID
+    ///
+    /// Named defaults like `card-image-default` stay in the base directory
+    fn get_path(&self, image_id: &str) -> PathBuf {
+        let file_name = format!("{}.bin", image_id);
+
+        if is_content_id(image_id) {
+            self.base_path.join(&image_id[0..2]).join(&image_id[2..4]).join(file_name)
+        } else {
+            self.base_path.join(file_name)
+        }
+    }
+
+    /// Write through a temporary file, a crash can't leave a truncated original behind its id
+    async fn write(path: PathBuf, data: &[u8]) -> Result<(), StorageError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| StorageError::WriteError(parent.to_path_buf(), e))?;
+        }
+
+        // Concurrent stores of the same image would share a fixed temporary name
+        let temp_path = path.with_extension(format!(
+            "{}.tmp",
+            TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&temp_path, data)
+            .await
+            .map_err(|e| StorageError::WriteError(temp_path.clone(), e))?;
+
+        if let Err(e) = fs::rename(&temp_path, &path).await {
+            let _ = fs::remove_file(&temp_path).await;
+            return Err(StorageError::WriteError(path, e));
+        }
+
+        Ok(())
+    }
+
+    /// Image id of a `{id}.bin` path
+    fn image_id(path: &Path) -> Option<String> {
+        match (path.extension(), path.file_stem()) {
+            (Some(ext), Some(stem)) if ext == "bin" => Some(stem.to_string_lossy().into_owned()),
+            _ => None,
+        }
+    }
+
+    /// Move images of the old flat layout `{base_path}/{id}.bin` into their shard
+    ///
+    /// Named defaults aren't moved, `get_path` keeps them in the base directory
+    async fn migrate_flat_layout(&self) -> Result<usize, StorageError> {
+        let read_error = |e| StorageError::ReadError(self.base_path.clone(), e);
+
+        let mut moved = 0;
+        let mut dir = fs::read_dir(&self.base_path).await.map_err(read_error)?;
+
+        while let Some(entry) = dir.next_entry().await.map_err(read_error)? {
+            let path = entry.path();
+            let image_id = match Self::image_id(&path) {
+                Some(image_id) if is_content_id(&image_id) && entry.file_type().await.map_err(read_error)?.is_file() => image_id,
+                _ => continue,
+            };
+
+            let target = self.get_path(&image_id);
+
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).await.map_err(|e| StorageError::WriteError(parent.to_path_buf(), e))?;
+            }
+            fs::rename(&path, &target).await.map_err(|e| StorageError::WriteError(target, e))?;
+            moved += 1;
+        }
+
+        Ok(moved)
+    }
+}
+
+#[async_trait::async_trait]
+impl OriginalStore for FilesystemStore {
+    /// Create the directory and move images of the old flat layout
+    async fn init(&self) -> Result<(), StorageError> {
         fs::create_dir_all(&self.base_path)
             .await
             .map_err(|e| StorageError::InitError(self.base_path.clone(), e))?;
+
+        let moved = self.migrate_flat_layout().await?;
+        if moved > 0 {
+            println!("- Moved {} originals into the sharded layout", moved);
+        }
+
         Ok(())
     }
 
-    /// Store an image and return its hash-based ID
-    pub async fn store(&self, data: &[u8]) -> Result<String, StorageError> {
+    async fn store(&self, data: &[u8]) -> Result<String, StorageError> {
         // Calculate SHA-256 hash
-        let hash = self.calculate_hash(data);
+        let hash = content_id(data);
 
         // Check if already exists (deduplication)
         let path = self.get_path(&hash);
@@ -35,16 +279,16 @@ impl ImageStorage {
             return Ok(hash);
         }
 
-        // Write file
-        fs::write(&path, data)
-            .await
-            .map_err(|e| StorageError::WriteError(path, e))?;
+        Self::write(path, data).await?;
 
         Ok(hash)
     }
 
-    /// Retrieve an image by its hash ID
-    pub async fn retrieve(&self, image_id: &str) -> Result<Vec<u8>, StorageError> {
+    async fn store_named(&self, image_id: &str, data: &[u8]) -> Result<(), StorageError> {
+        Self::write(self.get_path(image_id), data).await
+    }
+
+    async fn retrieve(&self, image_id: &str) -> Result<Vec<u8>, StorageError> {
         let path = self.get_path(image_id);
 
         fs::read(&path)
@@ -55,14 +299,12 @@ impl ImageStorage {
             })
     }
 
-    /// Check if an image exists
-    pub async fn exists(&self, image_id: &str) -> bool {
+    async fn exists(&self, image_id: &str) -> Result<bool, StorageError> {
         let path = self.get_path(image_id);
-        path.exists()
+        Ok(path.exists())
     }
 
-    /// Delete an image
-    pub async fn delete(&self, image_id: &str) -> Result<(), StorageError> {
+    async fn delete(&self, image_id: &str) -> Result<(), StorageError> {
         let path = self.get_path(image_id);
 
         match fs::remove_file(&path).await {
@@ -72,17 +314,40 @@ impl ImageStorage {
         }
     }
 
-    /// Calculate SHA-256 hash of data
-    fn calculate_hash(&self, data: &[u8]) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(data);
-        let result = hasher.finalize();
-        format!("{:x}", result)
-    }
+    /// Walks the shard directories, files outside the layout are ignored
+    async fn list(&self) -> Result<Vec<StoredImage>, StorageError> {
+        let read_error = |e| StorageError::ReadError(self.base_path.clone(), e);
 
-    /// Get file path for an image ID
-    fn get_path(&self, image_id: &str) -> PathBuf {
-        self.base_path.join(format!("{}.bin", image_id))
+        let mut images = Vec::new();
+        let mut dirs = vec![(self.base_path.clone(), 0)];
+
+        while let Some((dir_path, depth)) = dirs.pop() {
+            let mut dir = fs::read_dir(&dir_path).await.map_err(read_error)?;
+
+            while let Some(entry) = dir.next_entry().await.map_err(read_error)? {
+                let metadata = entry.metadata().await.map_err(read_error)?;
+
+                if metadata.is_dir() {
+                    if depth < 2 {
+                        dirs.push((entry.path(), depth + 1));
+                    }
+                    continue;
+                }
+
+                let image_id = match Self::image_id(&entry.path()) {
+                    Some(image_id) => image_id,
+                    None => continue,
+                };
+
+                images.push(StoredImage {
+                    image_id,
+                    len: metadata.len(),
+                    modified: metadata.modified().map_err(read_error)?,
+                });
+            }
+        }
+
+        Ok(images)
     }
 }
 
@@ -94,6 +359,10 @@ pub enum StorageError {
     ReadError(PathBuf, io::Error),
     DeleteError(PathBuf, io::Error),
     NotFound(String),
+    /// Request to the S3 endpoint failed, with the object key
+    S3Error(String, s3::error::S3Error),
+    /// The S3 endpoint answered with an error status, with the object key
+    S3Status(String, u16),
 }
 
 impl std::fmt::Display for StorageError {
@@ -114,6 +383,12 @@ impl std::fmt::Display for StorageError {
             StorageError::NotFound(id) => {
                 write!(f, "Image not found: {}", id)
             }
+            StorageError::S3Error(key, e) => {
+                write!(f, "S3 request for {} failed: {}", key, e)
+            }
+            StorageError::S3Status(key, status) => {
+                write!(f, "S3 request for {} failed with status {}", key, status)
+            }
         }
     }
 }
@@ -128,7 +403,7 @@ mod tests {
     #[tokio::test]
     async fn test_store_and_retrieve() {
         let temp_dir = TempDir::new().unwrap();
-        let storage = ImageStorage::new(temp_dir.path());
+        let storage = FilesystemStore::new(temp_dir.path());
         storage.init().await.unwrap();
 
         let data = b"test image data";
@@ -142,10 +417,43 @@ mod tests {
         assert_eq!(retrieved, data);
     }
 
+    #[tokio::test]
+    async fn test_sharded_layout() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FilesystemStore::new(temp_dir.path());
+        storage.init().await.unwrap();
+
+        let image_id = storage.store(b"sharded").await.unwrap();
+
+        let expected = temp_dir.path()
+            .join(&image_id[0..2])
+            .join(&image_id[2..4])
+            .join(format!("{}.bin", image_id));
+        assert!(expected.is_file());
+    }
+
+    #[tokio::test]
+    async fn test_migrate_flat_layout() {
+        let temp_dir = TempDir::new().unwrap();
+        let image_id = content_id(b"flat");
+        std::fs::write(temp_dir.path().join(format!("{}.bin", image_id)), b"flat").unwrap();
+        std::fs::write(temp_dir.path().join("card-image-default.bin"), b"default").unwrap();
+
+        let storage = FilesystemStore::new(temp_dir.path());
+        storage.init().await.unwrap();
+
+        assert!(!temp_dir.path().join(format!("{}.bin", image_id)).exists());
+        assert_eq!(storage.retrieve(&image_id).await.unwrap(), b"flat");
+
+        // Named defaults stay where they are
+        assert!(temp_dir.path().join("card-image-default.bin").is_file());
+        assert_eq!(storage.retrieve("card-image-default").await.unwrap(), b"default");
+    }
+
     #[tokio::test]
     async fn test_deduplication() {
         let temp_dir = TempDir::new().unwrap();
-        let storage = ImageStorage::new(temp_dir.path());
+        let storage = FilesystemStore::new(temp_dir.path());
         storage.init().await.unwrap();
 
         let data = b"duplicate test";
@@ -158,32 +466,112 @@ mod tests {
         assert_eq!(id1, id2);
 
         // File should only exist once
-        assert!(storage.exists(&id1).await);
+        assert!(storage.exists(&id1).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Arc::new(FilesystemStore::new(temp_dir.path()));
+        storage.init().await.unwrap();
+
+        let data = b"concurrent test";
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let storage = storage.clone();
+                tokio::spawn(async move { storage.store(data).await })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.await.unwrap().unwrap(), content_id(data));
+        }
+
+        assert_eq!(storage.retrieve(&content_id(data)).await.unwrap(), data);
     }
 
     #[tokio::test]
     async fn test_not_found() {
         let temp_dir = TempDir::new().unwrap();
-        let storage = ImageStorage::new(temp_dir.path());
+        let storage = FilesystemStore::new(temp_dir.path());
         storage.init().await.unwrap();
 
         let result = storage.retrieve("nonexistent").await;
         assert!(matches!(result, Err(StorageError::NotFound(_))));
     }
 
+    #[tokio::test]
+    async fn test_list() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FilesystemStore::new(temp_dir.path());
+        storage.init().await.unwrap();
+
+        let id1 = storage.store(b"first").await.unwrap();
+        let id2 = storage.store(b"second").await.unwrap();
+        std::fs::write(temp_dir.path().join("notes.txt"), b"not an image").unwrap();
+
+        let mut ids: Vec<String> = storage.list().await.unwrap()
+            .into_iter()
+            .map(|image| image.image_id)
+            .collect();
+        ids.sort();
+
+        let mut expected = vec![id1, id2];
+        expected.sort();
+        assert_eq!(ids, expected);
+    }
+
     #[tokio::test]
     async fn test_delete() {
         let temp_dir = TempDir::new().unwrap();
-        let storage = ImageStorage::new(temp_dir.path());
+        let storage = FilesystemStore::new(temp_dir.path());
         storage.init().await.unwrap();
 
         let data = b"delete test";
         let image_id = storage.store(data).await.unwrap();
 
-        assert!(storage.exists(&image_id).await);
+        assert!(storage.exists(&image_id).await.unwrap());
 
         storage.delete(&image_id).await.unwrap();
 
-        assert!(!storage.exists(&image_id).await);
+        assert!(!storage.exists(&image_id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_copy_originals() {
+        let from_dir = TempDir::new().unwrap();
+        let to_dir = TempDir::new().unwrap();
+        let from = FilesystemStore::new(from_dir.path());
+        let to = FilesystemStore::new(to_dir.path());
+        from.init().await.unwrap();
+        to.init().await.unwrap();
+
+        let id1 = from.store(b"first").await.unwrap();
+        let id2 = from.store(b"second").await.unwrap();
+        to.store(b"second").await.unwrap();
+
+        // Corrupted in the source
+        let id3 = from.store(b"third").await.unwrap();
+        std::fs::write(from.get_path(&id3), b"bit rot").unwrap();
+
+        // Named default, not a hash
+        from.store_named("card-image-default", b"default").await.unwrap();
+
+        let report = copy_originals(&from, &to).await.unwrap();
+        assert_eq!(report.copied, 2);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.mismatched, vec![id3.clone()]);
+
+        assert_eq!(to.retrieve(&id1).await.unwrap(), b"first");
+        assert!(to.exists(&id2).await.unwrap());
+        assert!(!to.exists(&id3).await.unwrap());
+        assert_eq!(to.retrieve("card-image-default").await.unwrap(), b"default");
+
+        // Copying again verifies the default by its bytes
+        let report = copy_originals(&from, &to).await.unwrap();
+        assert_eq!(report.copied, 0);
+        assert_eq!(report.skipped, 3);
+        assert_eq!(report.mismatched, vec![id3]);
     }
 }
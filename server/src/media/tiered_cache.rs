@@ -0,0 +1,170 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::io::AsyncReadExt;
+use super::body::MediaBody;
+use super::cache::{ImageCache, CacheKey, CacheError, CacheStats};
+use super::memory_cache::MemoryCache;
+
+/// Memory cache in front of a slower cache (usually `FilesystemCache`)
+///
+/// Writes go through to both tiers, hits on the lower tier are promoted to
+/// memory if they fit its budget. Images too large for memory are streamed
+/// from the lower tier.
+pub struct TieredCache {
+    memory: MemoryCache,
+    lower: Arc<dyn ImageCache>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl TieredCache {
+    pub fn new(memory: MemoryCache, lower: Arc<dyn ImageCache>) -> Self {
+        Self {
+            memory,
+            lower,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn count(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[async_trait::async_trait]
+impl ImageCache for TieredCache {
+    async fn get(&self, key: &CacheKey) -> Result<Option<Vec<u8>>, CacheError> {
+        if let Some(data) = self.memory.get(key).await? {
+            self.count(true);
+            return Ok(Some(data));
+        }
+
+        let data = self.lower.get(key).await?;
+        if let Some(data) = &data {
+            self.memory.set(key, data).await?;
+        }
+
+        self.count(data.is_some());
+        Ok(data)
+    }
+
+    async fn open(&self, key: &CacheKey) -> Result<Option<MediaBody>, CacheError> {
+        if let Some(data) = self.memory.get(key).await? {
+            self.count(true);
+            return Ok(Some(MediaBody::Memory(data)));
+        }
+
+        let body = match self.lower.open(key).await? {
+            // Small enough for memory, read it once and promote
+            Some(MediaBody::File(mut file, len)) if self.memory.accepts(len) => {
+                let mut data = Vec::with_capacity(len as usize);
+                file.read_to_end(&mut data).await?;
+                self.memory.set(key, &data).await?;
+                Some(MediaBody::Memory(data))
+            }
+            Some(MediaBody::Memory(data)) => {
+                self.memory.set(key, &data).await?;
+                Some(MediaBody::Memory(data))
+            }
+            body => body,
+        };
+
+        self.count(body.is_some());
+        Ok(body)
+    }
+
+    async fn set(&self, key: &CacheKey, data: &[u8]) -> Result<(), CacheError> {
+        self.lower.set(key, data).await?;
+        self.memory.set(key, data).await
+    }
+
+    async fn exists(&self, key: &CacheKey) -> Result<bool, CacheError> {
+        if self.memory.exists(key).await? {
+            return Ok(true);
+        }
+        self.lower.exists(key).await
+    }
+
+    async fn delete(&self, key: &CacheKey) -> Result<(), CacheError> {
+        self.memory.delete(key).await?;
+        self.lower.delete(key).await
+    }
+
+    async fn clear(&self) -> Result<(), CacheError> {
+        self.memory.clear().await?;
+        self.lower.clear().await
+    }
+
+    async fn list_images(&self, media_type: &str) -> Result<Vec<String>, CacheError> {
+        // Memory only holds what was written through to the lower tier
+        self.lower.list_images(media_type).await
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            name: "tiered",
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            bytes: None,
+            tiers: vec![self.memory.stats(), self.lower.stats()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::{FilesystemCache, ImageFormat};
+    use tempfile::TempDir;
+
+    fn key(id: &str) -> CacheKey {
+        CacheKey::new(
+            "card".to_string(),
+            id.to_string(),
+            "default".to_string(),
+            ImageFormat::WebP,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_write_through() {
+        let temp_dir = TempDir::new().unwrap();
+        let filesystem = Arc::new(FilesystemCache::new(temp_dir.path()));
+        let cache = TieredCache::new(MemoryCache::new(1024), filesystem.clone());
+
+        cache.set(&key("a"), b"data").await.unwrap();
+        assert!(filesystem.exists(&key("a")).await.unwrap());
+
+        // Served from memory, the filesystem isn't asked
+        assert!(matches!(cache.open(&key("a")).await.unwrap(), Some(MediaBody::Memory(_))));
+        assert_eq!(filesystem.stats().hits, 0);
+    }
+
+    #[tokio::test]
+    async fn test_promotion() {
+        let temp_dir = TempDir::new().unwrap();
+        let filesystem = Arc::new(FilesystemCache::new(temp_dir.path()));
+        filesystem.set(&key("small"), &[0; 16]).await.unwrap();
+        filesystem.set(&key("large"), &[0; 512]).await.unwrap();
+
+        let cache = TieredCache::new(MemoryCache::new(1024), filesystem.clone());
+
+        // Small images are promoted to memory
+        assert!(matches!(cache.open(&key("small")).await.unwrap(), Some(MediaBody::Memory(_))));
+        assert!(matches!(cache.open(&key("small")).await.unwrap(), Some(MediaBody::Memory(_))));
+        assert_eq!(filesystem.stats().hits, 1);
+
+        // Large ones keep streaming from disk
+        assert!(matches!(cache.open(&key("large")).await.unwrap(), Some(MediaBody::File(_, 512))));
+        assert!(matches!(cache.open(&key("large")).await.unwrap(), Some(MediaBody::File(_, 512))));
+
+        assert!(cache.open(&key("missing")).await.unwrap().is_none());
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 4);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.tiers.len(), 2);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::sql::Sql;
+use super::runner::{MigrationRunner, MigrationError};
+
+/// `migrate` subcommand of the server binary
+///
+/// `card_collector migrate status`
+/// `card_collector migrate up`
+/// `card_collector migrate down <version>` reverts everything newer than `version`
+/// `card_collector migrate repair <version>` forgets a dirty migration, undo its
+/// partial changes by hand first, then `up` runs it again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrateCommand {
+    Status,
+    Up,
+    Down(i64),
+    Repair(i64),
+}
+
+impl MigrateCommand {
+    /// Parse the command from the process arguments, None if the server should start normally
+    pub fn from_args(args: &[String]) -> Option<Result<Self, String>> {
+        if args.get(1).map(String::as_str) != Some("migrate") {
+            return None;
+        }
+
+        let command = match args.get(2).map(String::as_str) {
+            None | Some("status") => Ok(MigrateCommand::Status),
+            Some("up") => Ok(MigrateCommand::Up),
+            Some("down") => match args.get(3).map(|v| v.parse::<i64>()) {
+                Some(Ok(version)) => Ok(MigrateCommand::Down(version)),
+                _ => Err(String::from("Usage: migrate down <version>")),
+            },
+            Some("repair") => match args.get(3).map(|v| v.parse::<i64>()) {
+                Some(Ok(version)) => Ok(MigrateCommand::Repair(version)),
+                _ => Err(String::from("Usage: migrate repair <version>")),
+            },
+            Some(other) => Err(format!("Unknown migrate command: {}", other)),
+        };
+
+        Some(command)
+    }
+
+    pub async fn run(&self, sql: &Sql, runner: &MigrationRunner) -> Result<(), MigrationError> {
+        match self {
+            MigrateCommand::Status => {
+                for status in runner.status(sql).await? {
+                    println!("{:>6} {:<40} {:?}", status.version, status.name, status.state);
+                }
+            },
+            MigrateCommand::Up => {
+                for version in runner.apply(sql).await? {
+                    println!("-applied {}", version);
+                }
+            },
+            MigrateCommand::Down(target) => {
+                for version in runner.rollback(sql, *target).await? {
+                    println!("-reverted {}", version);
+                }
+            },
+            MigrateCommand::Repair(version) => {
+                runner.repair(sql, *version).await?;
+                println!("-repaired {}, it is pending again", version);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_from_args() {
+        assert_eq!(MigrateCommand::from_args(&args(&["card_collector"])), None);
+        assert_eq!(MigrateCommand::from_args(&args(&["card_collector", "migrate"])), Some(Ok(MigrateCommand::Status)));
+        assert_eq!(MigrateCommand::from_args(&args(&["card_collector", "migrate", "up"])), Some(Ok(MigrateCommand::Up)));
+        assert_eq!(MigrateCommand::from_args(&args(&["card_collector", "migrate", "down", "1"])), Some(Ok(MigrateCommand::Down(1))));
+        assert!(matches!(MigrateCommand::from_args(&args(&["card_collector", "migrate", "down"])), Some(Err(_))));
+        assert_eq!(MigrateCommand::from_args(&args(&["card_collector", "migrate", "repair", "3"])), Some(Ok(MigrateCommand::Repair(3))));
+        assert!(matches!(MigrateCommand::from_args(&args(&["card_collector", "migrate", "repair", "latest"])), Some(Err(_))));
+        assert!(matches!(MigrateCommand::from_args(&args(&["card_collector", "migrate", "sideways"])), Some(Err(_))));
+    }
+}
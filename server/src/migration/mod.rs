@@ -0,0 +1,9 @@
+pub mod source;
+pub mod splitter;
+pub mod sql;
+pub mod runner;
+pub mod command;
+
+pub use source::load_migrations;
+pub use runner::{MigrationRunner, MigrationMode};
+pub use command::MigrateCommand;
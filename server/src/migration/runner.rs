@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::io;
+use serde::{Serialize, Deserialize};
+
+use crate::sql::Sql;
+use super::source::Migration;
+use super::splitter::split_statements;
+use super::sql;
+
+/// What to do with migrations on startup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationMode {
+    /// Apply pending migrations, refuse to boot on drift
+    Apply,
+    /// Refuse to boot on pending or drifted migrations
+    Verify,
+}
+
+/// State of a single migration compared to the database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the file changed since
+    Drifted,
+    /// Started but never finished, needs manual repair, see `MigrationRunner::repair`
+    Dirty,
+    /// Applied, but no file exists for it anymore
+    Unknown,
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    pub state: MigrationState,
+}
+
+/// Applies and reverts versioned migrations tracked in `schema_migrations`
+pub struct MigrationRunner {
+    migrations: Vec<Migration>,
+}
+
+impl MigrationRunner {
+    /// Create a runner, migrations have to be sorted by version
+    pub fn new(migrations: Vec<Migration>) -> Self {
+        Self {
+            migrations
+        }
+    }
+
+    /// Compare the migrations on disk with the ones recorded in the database
+    pub async fn status(&self, sql: &Sql) -> Result<Vec<MigrationStatus>, MigrationError> {
+        sql::create_migrations_table(sql).await?;
+
+        let mut applied: HashMap<i64, sql::AppliedMigrationDb> = sql::get_applied_migrations(sql)
+            .await?
+            .into_iter()
+            .map(|migration| (migration.version, migration))
+            .collect();
+
+        let mut status: Vec<MigrationStatus> = self.migrations
+            .iter()
+            .map(|migration| {
+                let state = match applied.remove(&migration.version) {
+                    None => MigrationState::Pending,
+                    Some(db) if db.dirty != 0 => MigrationState::Dirty,
+                    Some(db) if db.checksum != migration.checksum => MigrationState::Drifted,
+                    Some(_) => MigrationState::Applied,
+                };
+
+                MigrationStatus {
+                    version: migration.version,
+                    name: migration.name.clone(),
+                    state,
+                }
+            })
+            .collect();
+
+        status.extend(applied.into_iter().map(|(version, db)| MigrationStatus {
+            version,
+            name: db.name,
+            state: if db.dirty != 0 { MigrationState::Dirty } else { MigrationState::Unknown },
+        }));
+
+        status.sort_by_key(|s| s.version);
+
+        Ok(status)
+    }
+
+    /// Fail if any migration is pending, drifted, dirty or unknown
+    pub async fn verify(&self, sql: &Sql) -> Result<(), MigrationError> {
+        let status = self.status(sql).await?;
+
+        check_consistent(&status)?;
+
+        let pending: Vec<i64> = status
+            .iter()
+            .filter(|s| s.state == MigrationState::Pending)
+            .map(|s| s.version)
+            .collect();
+
+        if !pending.is_empty() {
+            return Err(MigrationError::Pending(pending));
+        }
+
+        Ok(())
+    }
+
+    /// Apply all pending migrations in order
+    ///
+    /// Returns the versions that were applied
+    pub async fn apply(&self, sql: &Sql) -> Result<Vec<i64>, MigrationError> {
+        let status = self.status(sql).await?;
+
+        check_consistent(&status)?;
+
+        let pending: Vec<i64> = status
+            .iter()
+            .filter(|s| s.state == MigrationState::Pending)
+            .map(|s| s.version)
+            .collect();
+
+        // Pending migrations older than the newest applied one would run out of order
+        if let (Some(first_pending), Some(last_applied)) = (
+            pending.first(),
+            status.iter().filter(|s| s.state == MigrationState::Applied).map(|s| s.version).max()
+        ) {
+            if *first_pending < last_applied {
+                return Err(MigrationError::OutOfOrder(*first_pending));
+            }
+        }
+
+        for migration in self.migrations.iter().filter(|m| pending.contains(&m.version)) {
+            let statements = split_statements(&migration.up);
+            sql::apply_migration(sql, migration.version, &migration.name, &migration.checksum, &statements).await?;
+        }
+
+        Ok(pending)
+    }
+
+    /// Revert applied migrations newer than `target`, newest first
+    ///
+    /// Returns the versions that were reverted
+    pub async fn rollback(&self, sql: &Sql, target: i64) -> Result<Vec<i64>, MigrationError> {
+        let status = self.status(sql).await?;
+
+        check_consistent(&status)?;
+
+        let mut reverted = Vec::new();
+
+        for migration in self.migrations.iter().rev() {
+            if migration.version <= target {
+                break;
+            }
+
+            let applied = status
+                .iter()
+                .any(|s| s.version == migration.version && s.state == MigrationState::Applied);
+
+            if !applied {
+                continue;
+            }
+
+            let down = migration.down
+                .as_ref()
+                .ok_or(MigrationError::MissingDown(migration.version))?;
+
+            let statements = split_statements(down);
+            sql::revert_migration(sql, migration.version, &statements).await?;
+
+            reverted.push(migration.version);
+        }
+
+        Ok(reverted)
+    }
+
+    /// Forget a dirty migration so it counts as pending again
+    ///
+    /// MySQL can't roll back DDL, so whatever the failed migration changed
+    /// before the error has to be undone by hand before calling this.
+    pub async fn repair(&self, sql: &Sql, version: i64) -> Result<(), MigrationError> {
+        sql::create_migrations_table(sql).await?;
+
+        if !sql::delete_dirty_migration(sql, version).await? {
+            return Err(MigrationError::NotDirty(version));
+        }
+
+        Ok(())
+    }
+}
+
+fn check_consistent(status: &[MigrationStatus]) -> Result<(), MigrationError> {
+    for s in status {
+        match s.state {
+            MigrationState::Dirty => return Err(MigrationError::Dirty(s.version)),
+            MigrationState::Drifted => return Err(MigrationError::Drifted(s.version)),
+            MigrationState::Unknown => return Err(MigrationError::Unknown(s.version)),
+            MigrationState::Applied | MigrationState::Pending => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Errors that can occur when loading or running migrations
+#[derive(Debug)]
+pub enum MigrationError {
+    DirectoryReadError(PathBuf, io::Error),
+    FileReadError(PathBuf, io::Error),
+    InvalidFileName(PathBuf),
+    DuplicateVersion(i64),
+    MissingUp(i64),
+    MissingDown(i64),
+    Pending(Vec<i64>),
+    OutOfOrder(i64),
+    Drifted(i64),
+    Dirty(i64),
+    NotDirty(i64),
+    Unknown(i64),
+    StatementError(i64, String, sqlx::Error),
+    DbError(sqlx::Error),
+}
+
+impl std::fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationError::DirectoryReadError(path, e) => {
+                write!(f, "Failed to read migration directory {}: {}", path.display(), e)
+            }
+            MigrationError::FileReadError(path, e) => {
+                write!(f, "Failed to read migration {}: {}", path.display(), e)
+            }
+            MigrationError::InvalidFileName(path) => {
+                write!(f, "Invalid migration file name {}, expected <version>_<name>.up.sql or <version>_<name>.down.sql", path.display())
+            }
+            MigrationError::DuplicateVersion(version) => write!(f, "Duplicate migration version {}", version),
+            MigrationError::MissingUp(version) => write!(f, "Migration {} has a down but no up file", version),
+            MigrationError::MissingDown(version) => write!(f, "Migration {} has no down file", version),
+            MigrationError::Pending(versions) => write!(f, "Pending migrations: {:?}", versions),
+            MigrationError::OutOfOrder(version) => {
+                write!(f, "Migration {} is older than the newest applied migration", version)
+            }
+            MigrationError::Drifted(version) => {
+                write!(f, "Migration {} was changed after it was applied (checksum mismatch)", version)
+            }
+            MigrationError::Dirty(version) => {
+                write!(f, "Migration {} did not finish, undo its changes manually and run `migrate repair {}`", version, version)
+            }
+            MigrationError::NotDirty(version) => write!(f, "Migration {} is not dirty, nothing to repair", version),
+            MigrationError::Unknown(version) => {
+                write!(f, "Migration {} is applied but missing on disk", version)
+            }
+            MigrationError::StatementError(version, statement, e) => {
+                write!(f, "Migration {} failed: {}\n{}", version, e, statement)
+            }
+            MigrationError::DbError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<sqlx::Error> for MigrationError {
+    fn from(err: sqlx::Error) -> Self {
+        MigrationError::DbError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(version: i64, state: MigrationState) -> MigrationStatus {
+        MigrationStatus {
+            version,
+            name: format!("m{}", version),
+            state,
+        }
+    }
+
+    #[test]
+    fn test_check_consistent() {
+        assert!(check_consistent(&[
+            status(1, MigrationState::Applied),
+            status(2, MigrationState::Pending),
+        ]).is_ok());
+
+        assert!(matches!(
+            check_consistent(&[status(1, MigrationState::Applied), status(2, MigrationState::Drifted)]),
+            Err(MigrationError::Drifted(2))
+        ));
+
+        assert!(matches!(
+            check_consistent(&[status(1, MigrationState::Dirty)]),
+            Err(MigrationError::Dirty(1))
+        ));
+
+        assert!(matches!(
+            check_consistent(&[status(3, MigrationState::Unknown)]),
+            Err(MigrationError::Unknown(3))
+        ));
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::fs;
+use sha2::{Sha256, Digest};
+
+use super::runner::MigrationError;
+
+/// A single versioned schema migration loaded from disk
+///
+/// Files are named `{version}_{name}.up.sql` and optionally `{version}_{name}.down.sql`,
+/// e.g. `0002_add_image_hashes.up.sql`
+#[derive(Debug, Clone)]
+pub struct Migration {
+    pub version: i64,
+    pub name: String,
+    pub up: String,
+    pub down: Option<String>,
+    /// SHA-256 of the up migration, used to detect edits after it was applied
+    pub checksum: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Up,
+    Down,
+}
+
+/// Load all migrations from a directory, sorted by version
+pub fn load_migrations<P: AsRef<Path>>(dir: P) -> Result<Vec<Migration>, MigrationError> {
+    let dir_path = dir.as_ref();
+
+    let mut ups: BTreeMap<i64, (String, String)> = BTreeMap::new();
+    let mut downs: BTreeMap<i64, (String, String)> = BTreeMap::new();
+
+    for entry in fs::read_dir(dir_path)
+        .map_err(|e| MigrationError::DirectoryReadError(dir_path.to_path_buf(), e))?
+    {
+        let entry = entry.map_err(|e| MigrationError::DirectoryReadError(dir_path.to_path_buf(), e))?;
+        let path = entry.path();
+
+        if path.extension().and_then(|s| s.to_str()) != Some("sql") {
+            continue;
+        }
+
+        let (version, name, direction) = parse_file_name(&path)?;
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| MigrationError::FileReadError(path.clone(), e))?;
+
+        let target = match direction {
+            Direction::Up => &mut ups,
+            Direction::Down => &mut downs,
+        };
+
+        if target.insert(version, (name, content)).is_some() {
+            return Err(MigrationError::DuplicateVersion(version));
+        }
+    }
+
+    if let Some(version) = downs.keys().find(|version| !ups.contains_key(version)) {
+        return Err(MigrationError::MissingUp(*version));
+    }
+
+    let migrations = ups
+        .into_iter()
+        .map(|(version, (name, up))| Migration {
+            version,
+            name,
+            checksum: calculate_checksum(&up),
+            down: downs.remove(&version).map(|(_, down)| down),
+            up,
+        })
+        .collect();
+
+    Ok(migrations)
+}
+
+/// Checksum over the migration content, line endings are normalized so checkouts on
+/// different platforms don't count as drift
+pub fn calculate_checksum(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.replace("\r\n", "\n").as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn parse_file_name(path: &PathBuf) -> Result<(i64, String, Direction), MigrationError> {
+    let invalid = || MigrationError::InvalidFileName(path.clone());
+
+    let file_name = path.file_name().and_then(|s| s.to_str()).ok_or_else(invalid)?;
+
+    let (stem, direction) = if let Some(stem) = file_name.strip_suffix(".up.sql") {
+        (stem, Direction::Up)
+    } else if let Some(stem) = file_name.strip_suffix(".down.sql") {
+        (stem, Direction::Down)
+    } else {
+        return Err(invalid());
+    };
+
+    let (version, name) = stem.split_once('_').ok_or_else(invalid)?;
+    let version = version.parse::<i64>().map_err(|_| invalid())?;
+
+    if name.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok((version, name.to_string(), direction))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, name: &str, content: &str) {
+        fs::write(dir.path().join(name), content).unwrap();
+    }
+
+    #[test]
+    fn test_load_sorted_by_version() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "0010_second.up.sql", "SELECT 2;");
+        write(&dir, "0002_first.up.sql", "SELECT 1;");
+        write(&dir, "0002_first.down.sql", "SELECT -1;");
+        write(&dir, "README.md", "ignored");
+
+        let migrations = load_migrations(dir.path()).unwrap();
+
+        assert_eq!(migrations.len(), 2);
+        assert_eq!(migrations[0].version, 2);
+        assert_eq!(migrations[0].name, "first");
+        assert_eq!(migrations[0].down.as_deref(), Some("SELECT -1;"));
+        assert_eq!(migrations[1].version, 10);
+        assert!(migrations[1].down.is_none());
+    }
+
+    #[test]
+    fn test_duplicate_version() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "0001_a.up.sql", "SELECT 1;");
+        write(&dir, "0001_b.up.sql", "SELECT 1;");
+
+        assert!(matches!(load_migrations(dir.path()), Err(MigrationError::DuplicateVersion(1))));
+    }
+
+    #[test]
+    fn test_down_without_up() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "0001_a.down.sql", "SELECT 1;");
+
+        assert!(matches!(load_migrations(dir.path()), Err(MigrationError::MissingUp(1))));
+    }
+
+    #[test]
+    fn test_invalid_file_name() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "tables.sql", "SELECT 1;");
+
+        assert!(matches!(load_migrations(dir.path()), Err(MigrationError::InvalidFileName(_))));
+    }
+
+    #[test]
+    fn test_checksum_ignores_line_endings() {
+        assert_eq!(calculate_checksum("SELECT 1;\r\nSELECT 2;"), calculate_checksum("SELECT 1;\nSELECT 2;"));
+        assert_ne!(calculate_checksum("SELECT 1;"), calculate_checksum("SELECT 2;"));
+    }
+}
@@ -0,0 +1,237 @@
+/// Lexer state while scanning a migration file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Normal,
+    SingleQuote,
+    DoubleQuote,
+    Backtick,
+    LineComment,
+    BlockComment,
+}
+
+/// Split a migration file into single statements
+///
+/// Unlike a plain `split(";")` this keeps delimiters inside string literals,
+/// quoted identifiers and comments intact and understands the mysql client
+/// `DELIMITER` command, so trigger and procedure bodies can be written as:
+///
+/// ```sql
+/// DELIMITER $$
+/// CREATE TRIGGER ... BEGIN ...; ...; END$$
+/// DELIMITER ;
+/// ```
+pub fn split_statements(source: &str) -> Vec<String> {
+    let bytes = source.as_bytes();
+
+    let mut statements = Vec::new();
+    let mut delimiter = String::from(";");
+    let mut state = State::Normal;
+
+    let mut start = 0;
+    let mut has_content = false;
+    let mut line_start = true;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+
+        match state {
+            State::Normal => {
+                // DELIMITER is a client command, only valid at the start of a statement
+                if line_start && !has_content && source.is_char_boundary(i) {
+                    if let Some((new_delimiter, line_len)) = parse_delimiter_command(&source[i..]) {
+                        delimiter = new_delimiter;
+                        i += line_len;
+                        start = i;
+                        continue;
+                    }
+                }
+
+                //NOTE: compared as bytes, `i` can be inside a multi-byte character
+                if bytes[i..].starts_with(delimiter.as_bytes()) {
+                    if has_content {
+                        statements.push(source[start..i].trim().to_string());
+                    }
+
+                    i += delimiter.len();
+                    start = i;
+                    has_content = false;
+                    line_start = false;
+                    continue;
+                }
+
+                match c {
+                    b'\'' => { state = State::SingleQuote; has_content = true; },
+                    b'"' => { state = State::DoubleQuote; has_content = true; },
+                    b'`' => { state = State::Backtick; has_content = true; },
+                    b'#' => state = State::LineComment,
+                    b'-' if is_dash_comment(bytes, i) => state = State::LineComment,
+                    b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                        // Executable comments (/*! ... */) are statements for mysql
+                        if bytes.get(i + 2) == Some(&b'!') {
+                            has_content = true;
+                        }
+                        state = State::BlockComment;
+                        i += 2;
+                        line_start = false;
+                        continue;
+                    },
+                    c if !c.is_ascii_whitespace() => has_content = true,
+                    _ => ()
+                }
+            },
+            State::SingleQuote | State::DoubleQuote | State::Backtick => {
+                let quote = match state {
+                    State::SingleQuote => b'\'',
+                    State::DoubleQuote => b'"',
+                    _ => b'`',
+                };
+
+                if c == b'\\' && state != State::Backtick {
+                    i += 2;
+                    line_start = false;
+                    continue;
+                }
+
+                if c == quote {
+                    // Doubled quotes are an escaped quote, not the end of the literal
+                    if bytes.get(i + 1) == Some(&quote) {
+                        i += 2;
+                        line_start = false;
+                        continue;
+                    }
+                    state = State::Normal;
+                }
+            },
+            State::LineComment => {
+                if c == b'\n' {
+                    state = State::Normal;
+                }
+            },
+            State::BlockComment => {
+                if c == b'*' && bytes.get(i + 1) == Some(&b'/') {
+                    state = State::Normal;
+                    i += 2;
+                    line_start = false;
+                    continue;
+                }
+            },
+        }
+
+        line_start = c == b'\n' || (line_start && (c == b' ' || c == b'\t' || c == b'\r'));
+        i += 1;
+    }
+
+    if has_content {
+        let rest = source[start..].trim();
+        if !rest.is_empty() {
+            statements.push(rest.to_string());
+        }
+    }
+
+    statements
+}
+
+/// `--` only starts a comment in MySQL when followed by whitespace
+fn is_dash_comment(bytes: &[u8], i: usize) -> bool {
+    bytes.get(i + 1) == Some(&b'-')
+        && bytes.get(i + 2).map(|c| c.is_ascii_whitespace()).unwrap_or(true)
+}
+
+/// Parse `DELIMITER <token>` at the start of `rest`
+///
+/// Returns the new delimiter and the length of the line including its newline
+fn parse_delimiter_command(rest: &str) -> Option<(String, usize)> {
+    let line_len = rest.find('\n').map(|pos| pos + 1).unwrap_or(rest.len());
+    let mut tokens = rest[..line_len].split_whitespace();
+
+    let command = tokens.next()?;
+    if !command.eq_ignore_ascii_case("delimiter") {
+        return None;
+    }
+
+    let delimiter = tokens.next()?;
+
+    Some((delimiter.to_string(), line_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_simple() {
+        let statements = split_statements("CREATE TABLE a (id INT);\n\nCREATE TABLE b (id INT);\n");
+        assert_eq!(statements, vec!["CREATE TABLE a (id INT)", "CREATE TABLE b (id INT)"]);
+    }
+
+    #[test]
+    fn test_split_keeps_delimiter_in_strings() {
+        let statements = split_statements(
+            "INSERT INTO t VALUES ('a;b', \"c;d\", 'it''s;', 'esc\\';');\nSELECT `weird;name` FROM t;");
+
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[0], "INSERT INTO t VALUES ('a;b', \"c;d\", 'it''s;', 'esc\\';')");
+        assert_eq!(statements[1], "SELECT `weird;name` FROM t");
+    }
+
+    #[test]
+    fn test_split_ignores_comments() {
+        let statements = split_statements(
+            "-- leading comment; with delimiter\n# hash comment;\n/* block; comment */\nSELECT 1;\n-- trailing;\n");
+
+        assert_eq!(statements.len(), 1);
+        assert!(statements[0].ends_with("SELECT 1"));
+    }
+
+    #[test]
+    fn test_split_double_dash_without_space_is_not_a_comment() {
+        let statements = split_statements("SELECT 1--1;");
+        assert_eq!(statements, vec!["SELECT 1--1"]);
+    }
+
+    #[test]
+    fn test_split_delimiter_command() {
+        let source = "CREATE TABLE a (id INT);\n\
+                      DELIMITER $$\n\
+                      CREATE TRIGGER t BEFORE INSERT ON a FOR EACH ROW\n\
+                      BEGIN\n\
+                      SET NEW.id = NEW.id + 1;\n\
+                      SET NEW.id = NEW.id * 2;\n\
+                      END$$\n\
+                      DELIMITER ;\n\
+                      SELECT 1;";
+
+        let statements = split_statements(source);
+
+        assert_eq!(statements.len(), 3);
+        assert_eq!(statements[0], "CREATE TABLE a (id INT)");
+        assert!(statements[1].starts_with("CREATE TRIGGER t"));
+        assert!(statements[1].contains("SET NEW.id = NEW.id + 1;"));
+        assert!(statements[1].ends_with("END"));
+        assert_eq!(statements[2], "SELECT 1");
+    }
+
+    #[test]
+    fn test_split_non_ascii() {
+        let statements = split_statements(
+            "CREATE TABLE sammelkarten_größe (größe INT);\n-- Kommentar über Größen;\nINSERT INTO sammelkarten_größe VALUES (1); SELECT 'ünïcödé;' AS é;");
+
+        assert_eq!(statements.len(), 3);
+        assert_eq!(statements[0], "CREATE TABLE sammelkarten_größe (größe INT)");
+        assert!(statements[1].ends_with("INSERT INTO sammelkarten_größe VALUES (1)"));
+        assert_eq!(statements[2], "SELECT 'ünïcödé;' AS é");
+    }
+
+    #[test]
+    fn test_split_without_trailing_delimiter() {
+        let statements = split_statements("SELECT 1;\nSELECT 2");
+        assert_eq!(statements, vec!["SELECT 1", "SELECT 2"]);
+    }
+
+    #[test]
+    fn test_split_empty() {
+        assert!(split_statements("").is_empty());
+        assert!(split_statements("  ;\n;\n-- only a comment\n").is_empty());
+    }
+}
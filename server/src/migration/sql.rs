@@ -0,0 +1,124 @@
+use sqlx::FromRow;
+
+use crate::sql::Sql;
+use super::runner::MigrationError;
+
+#[derive(Debug, FromRow)]
+pub struct AppliedMigrationDb {
+    #[sqlx(rename="smversion")]
+    pub version: i64,
+    #[sqlx(rename="smname")]
+    pub name: String,
+    #[sqlx(rename="smchecksum")]
+    pub checksum: String,
+    #[sqlx(rename="smdirty")]
+    pub dirty: i32,
+}
+
+pub async fn create_migrations_table(sql: &Sql) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            smversion BIGINT NOT NULL,
+            smname VARCHAR(255) NOT NULL,
+            smchecksum CHAR(64) NOT NULL,
+            smdirty INT NOT NULL,
+            smtime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (smversion)
+         ) ENGINE = InnoDB;")
+        .execute(sql.pool())
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_applied_migrations(sql: &Sql) -> Result<Vec<AppliedMigrationDb>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT smversion, smname, smchecksum, smdirty
+         FROM schema_migrations
+         ORDER BY smversion;")
+        .fetch_all(sql.pool())
+        .await
+}
+
+/// Runs the statements of an up migration and records it
+///
+/// The row is written as dirty before anything runs. MySQL commits implicitly on DDL,
+/// so if a statement fails after such a commit the migration stays marked dirty and
+/// has to be repaired by hand (then `migrate repair`) instead of being silently half applied.
+pub async fn apply_migration(sql: &Sql, version: i64, name: &str, checksum: &str, statements: &[String]) -> Result<(), MigrationError> {
+    sqlx::query(
+        "INSERT INTO schema_migrations
+         (smversion, smname, smchecksum, smdirty)
+         VALUES
+         (?, ?, ?, 1);")
+        .bind(version)
+        .bind(name)
+        .bind(checksum)
+        .execute(sql.pool())
+        .await?;
+
+    let mut transaction = sql.pool().begin().await?;
+
+    for statement in statements {
+        sqlx::raw_sql(statement)
+            .execute(&mut *transaction)
+            .await
+            .map_err(|e| MigrationError::StatementError(version, statement.clone(), e))?;
+    }
+
+    sqlx::query(
+        "UPDATE schema_migrations
+         SET smdirty=0, smtime=NOW()
+         WHERE smversion=?;")
+        .bind(version)
+        .execute(&mut *transaction)
+        .await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+/// Removes the record of a dirty migration, false if there is none
+pub async fn delete_dirty_migration(sql: &Sql, version: i64) -> Result<bool, sqlx::Error> {
+    let deleted = sqlx::query(
+        "DELETE FROM schema_migrations
+         WHERE smversion=? AND smdirty=1;")
+        .bind(version)
+        .execute(sql.pool())
+        .await?
+        .rows_affected();
+
+    Ok(deleted > 0)
+}
+
+/// Runs the statements of a down migration and removes its record
+pub async fn revert_migration(sql: &Sql, version: i64, statements: &[String]) -> Result<(), MigrationError> {
+    sqlx::query(
+        "UPDATE schema_migrations
+         SET smdirty=1
+         WHERE smversion=?;")
+        .bind(version)
+        .execute(sql.pool())
+        .await?;
+
+    let mut transaction = sql.pool().begin().await?;
+
+    for statement in statements {
+        sqlx::raw_sql(statement)
+            .execute(&mut *transaction)
+            .await
+            .map_err(|e| MigrationError::StatementError(version, statement.clone(), e))?;
+    }
+
+    sqlx::query(
+        "DELETE FROM schema_migrations
+         WHERE smversion=?;")
+        .bind(version)
+        .execute(&mut *transaction)
+        .await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
@@ -2,21 +2,55 @@ use serde::Serialize;
 use sqlx::FromRow;
 use chrono::{DateTime, Utc};
 
-use crate::shared::{Id};
 use crate::shared::card::data::UnlockedCard;
 
-#[derive(Debug, Serialize, FromRow)]
-#[sqlx(rename_all="camelCase")]
-pub struct CardCreateDataDb {
-    pub card_id: Id,
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct PackOpenResponse {
+    pub cards: Vec<UnlockedCard>,
+    pub pity: PityProgress
 }
 
 #[derive(Debug, Serialize)]
-pub struct PackOpenResponse {
-    pub cards: Vec<UnlockedCard>
+#[serde(rename_all="camelCase")]
+pub struct PityProgress {
+    ///Packs opened in a row without a pity card
+    pub count: u32,
+    ///0 when pity is disabled
+    pub threshold: u32,
+    ///This pack was guaranteed a pity card
+    pub guaranteed: bool
 }
 
 pub enum CanOpenPack {
     Yes,
     No(DateTime<Utc>)
 }
+
+///Chances in per mille, level two is rolled first
+pub struct LevelChances {
+    pub level_one: u32,
+    pub level_two: u32
+}
+
+///A card counts for pity if it reaches `level` or its rarity weight is at most `rarity_weight`,
+///0 disables the respective check
+pub struct PityRules {
+    pub threshold: u32,
+    pub level: u32,
+    pub rarity_weight: u32
+}
+
+#[derive(Debug, FromRow)]
+pub struct PackTimeDb {
+    pub ptlastopened: Option<DateTime<Utc>>,
+    pub ptpity: i32
+}
+
+#[derive(Debug, FromRow)]
+pub struct PackOpenDb {
+    pub poid: i32,
+    pub popitycount: i32,
+    pub popitythreshold: i32,
+    pub poguaranteed: i32
+}
@@ -5,9 +5,8 @@ use chrono::{DateTime, Utc, Duration};
 use rand::Rng;
 use std::ops::RangeInclusive;
 
-use super::data::{PackOpenResponse, CanOpenPack};
+use super::data::{PackOpenResponse, CanOpenPack, LevelChances, PityRules, PityProgress};
 use super::sql;
-use super::super::shared;
 use crate::shared::crypto::JwtToken;
 use crate::sql::Sql;
 use crate::config::Config;
@@ -16,41 +15,110 @@ use crate::shared::Id;
 use crate::{verify_user, verify_collector};
 use crate::shared::card::packstats::sql::add_pack_stats;
 use crate::shared::collector::{get_collector_setting, CollectorSetting};
+use crate::shared::rarity::{CardPool, CardPoolCache};
+use crate::shared::idempotency::IdempotencyKey;
 
 #[post("/pack/<collector_id>/open")]
-pub async fn pack_open_route(collector_id: Id, sql: &State<Sql>, token: JwtToken, config: &State<Config>) -> ApiResponseErr<PackOpenResponse> {
+pub async fn pack_open_route(collector_id: Id, sql: &State<Sql>, token: JwtToken, config: &State<Config>, card_pool_cache: &State<CardPoolCache>, idempotency_key: IdempotencyKey) -> ApiResponseErr<PackOpenResponse> {
     let user_id = token.id;
 
     verify_user!(sql, &user_id, true);
     verify_collector!(sql, &collector_id);
 
+    let idempotency_since = Utc::now() - Duration::seconds(config.pack_idempotency_key_duration as i64);
+
     let pack_amount = rjtry!(get_collector_setting(sql, &collector_id, CollectorSetting::PackAmount, config.pack_amount).await);
+    let pack_cooldown = rjtry!(get_collector_setting(sql, &collector_id, CollectorSetting::PackCooldown, config.pack_cooldown).await);
     let pack_quality_min = rjtry!(get_collector_setting(sql, &collector_id, CollectorSetting::PackQualityMin, config.pack_quality_min).await);
     let pack_quality_max = rjtry!(get_collector_setting(sql, &collector_id, CollectorSetting::PackQualityMax, config.pack_quality_max).await);
+    let pack_level_one_chance = rjtry!(get_collector_setting(sql, &collector_id, CollectorSetting::PackLevelOneChance, config.pack_level_one_chance).await);
+    let pack_level_two_chance = rjtry!(get_collector_setting(sql, &collector_id, CollectorSetting::PackLevelTwoChance, config.pack_level_two_chance).await);
+    let pity_rules = PityRules {
+        threshold: rjtry!(get_collector_setting(sql, &collector_id, CollectorSetting::PackPityThreshold, config.pack_pity_threshold).await),
+        level: rjtry!(get_collector_setting(sql, &collector_id, CollectorSetting::PackPityLevel, config.pack_pity_level).await),
+        rarity_weight: rjtry!(get_collector_setting(sql, &collector_id, CollectorSetting::PackPityRarityWeight, config.pack_pity_rarity_weight).await),
+    };
+
+    let card_pool = rjtry!(card_pool_cache.get(sql, &collector_id).await);
+
+    rjtry!(sql::ensure_pack_time(sql, &user_id, &collector_id).await);
+
+    let mut transaction = rjtry!(sql.pool().begin().await);
 
-    let last_opened = rjtry!(shared::sql::get_pack_time(sql, &user_id, &collector_id).await);
+    //Held until commit, a concurrent request for the same user and collector waits here
+    let pack_time = rjtry!(sql::lock_pack_time(&mut transaction, &user_id, &collector_id).await);
 
-    if let CanOpenPack::No(next_time) = can_open_pack(last_opened, rjtry!(get_collector_setting(sql, &collector_id, CollectorSetting::PackCooldown, config.pack_cooldown).await)) {
+    //Checked after the lock so a retry racing the original request still gets its pack
+    if let Some(ref key) = idempotency_key.0 {
+        if let Some(pack_open) = rjtry!(sql::get_pack_open(&mut transaction, &user_id, &collector_id, key, idempotency_since).await) {
+            rjtry!(transaction.rollback().await);
+
+            let cards_uuids = rjtry!(sql::get_pack_open_cards(sql, pack_open.poid).await);
+            let cards = rjtry!(card::sql::get_unlocked_cards(sql, cards_uuids, None).await);
+
+            return ApiResponseErr::ok(Status::Ok, PackOpenResponse {
+                cards,
+                pity: PityProgress {
+                    count: pack_open.popitycount.max(0) as u32,
+                    threshold: pack_open.popitythreshold.max(0) as u32,
+                    guaranteed: pack_open.poguaranteed != 0
+                }
+            });
+        }
+    }
+
+    if let CanOpenPack::No(next_time) = can_open_pack(pack_time.ptlastopened, pack_cooldown) {
         return ApiResponseErr::api_err(Status::Conflict, format!("Wait until: {}", next_time));
     }
 
-    let cards_create_data = rjtry!(get_random_cards(sql, pack_amount, pack_quality_min..=pack_quality_max, &collector_id).await);
+    let pity_count = pack_time.ptpity.max(0) as u32;
+
+    let mut cards_create_data = get_random_cards(&card_pool, pack_amount, pack_quality_min..=pack_quality_max, LevelChances {
+        level_one: pack_level_one_chance,
+        level_two: pack_level_two_chance
+    });
+
+    let guaranteed = pity_rules.threshold != 0 && pity_count >= pity_rules.threshold;
+    if guaranteed {
+        apply_pity(&mut rand::rng(), &mut cards_create_data, &card_pool, &pity_rules);
+    }
+
+    let pity_count = if cards_create_data.iter().any(|card| is_pity_card(card, &card_pool, &pity_rules)) {
+        0
+    } else {
+        pity_count + 1
+    };
+
+    let pity = PityProgress {
+        count: pity_count,
+        threshold: pity_rules.threshold,
+        guaranteed
+    };
+
+    let now = Utc::now();
 
     let mut inserted_cards_uuids = Vec::new();
     for card_create_data in cards_create_data.iter() {
         let inserted_card_uuid = Id::new(config.id_length);
-        rjtry!(card::sql::add_card(&sql, &user_id, &inserted_card_uuid, &collector_id, card_create_data).await);
+        rjtry!(card::sql::add_card_transaction(&mut transaction, &user_id, &inserted_card_uuid, &collector_id, card_create_data).await);
         inserted_cards_uuids.push(inserted_card_uuid);
     }
 
-    rjtry!(sql::set_pack_time(&sql, &user_id, &collector_id, Utc::now()).await);
+    rjtry!(sql::set_pack_time(&mut transaction, &user_id, &collector_id, now, pity_count).await);
 
-    let cards = rjtry!(card::sql::get_unlocked_cards(&sql, inserted_cards_uuids, None).await);
+    rjtry!(add_pack_stats(&mut transaction, &user_id, &collector_id, pack_amount as i32, &now).await);
+
+    if let Some(ref key) = idempotency_key.0 {
+        rjtry!(sql::add_pack_open(&mut transaction, &user_id, &collector_id, key, idempotency_since, &inserted_cards_uuids, &pity).await);
+    }
+
+    rjtry!(transaction.commit().await);
 
-    rjtry!(add_pack_stats(sql, &user_id, &collector_id, pack_amount as i32, &Utc::now()).await);
+    let cards = rjtry!(card::sql::get_unlocked_cards(&sql, inserted_cards_uuids, None).await);
 
     ApiResponseErr::ok(Status::Ok, PackOpenResponse {
-        cards
+        cards,
+        pity
     })
 }
 
@@ -69,38 +137,129 @@ fn can_open_pack(last_opened: Option<DateTime<Utc>>, pack_cooldown: u32) -> CanO
     }
 }
 
-async fn get_random_cards(sql: &Sql, amount: u32, quality_range: RangeInclusive<i32>, collector_id: &Id) -> Result<Vec<UnlockedCardCreateData>, sqlx::Error> {
-    let new_cards_create = sql::get_random_card_data(sql, amount, collector_id).await?;
+fn get_random_cards(card_pool: &CardPool, amount: u32, quality_range: RangeInclusive<i32>, level_chances: LevelChances) -> Vec<UnlockedCardCreateData> {
+    let mut rng = rand::rng();
 
-	//NOTE: maybe this could be smth for the future:
-    // (highest-level)^3 * 0.5
+    let card_ids = card_pool.choose(&mut rng, amount as usize);
 
-    let mut rng = rand::rng();
+    card_ids
+        .into_iter()
+        .map(|card_id| UnlockedCardCreateData {
+            card_id,
+            level: roll_level(&mut rng, &level_chances),
+            frame_id: None,
+            quality: rng.random_range(quality_range.clone())
+        })
+        .collect()
+}
+
+//NOTE: maybe this could be smth for the future:
+// (highest-level)^3 * 0.5
+fn roll_level<R: Rng + ?Sized>(rng: &mut R, level_chances: &LevelChances) -> i32 {
+    let level_random: u32 = rng.random_range(0..1000);
 
-    let mut cards_create_data = Vec::new();
+    if level_random < level_chances.level_two {
+        2
+    } else if level_random < level_chances.level_two.saturating_add(level_chances.level_one) {
+        1
+    } else {
+        0
+    }
+}
 
-    for card in new_cards_create.iter() {
-        let quality: i32 = rng.random_range(quality_range.clone());
+fn is_pity_card(card: &UnlockedCardCreateData, card_pool: &CardPool, pity_rules: &PityRules) -> bool {
+    let level_hit = pity_rules.level != 0 && card.level >= pity_rules.level as i32;
+    let rarity_hit = pity_rules.rarity_weight != 0 && card_pool
+        .weight(&card.card_id)
+        .map(|weight| weight <= pity_rules.rarity_weight)
+        .unwrap_or(false);
 
-        //TODO: improve the way to pick level (not algorythm but code)
-        let mut level = 0;
-        let level_random: i32 = rng.random_range(0..=1000);
-        if level_random <= 50 {
-            level = 1;
-            if level_random <= 5 {
-                level = 2;
-            }
+    level_hit || rarity_hit
+}
+
+///Make sure the pack contains at least one pity card, prefers a rare card and
+///falls back to raising the level when the collector has no card that is rare enough
+fn apply_pity<R: Rng + ?Sized>(rng: &mut R, cards: &mut Vec<UnlockedCardCreateData>, card_pool: &CardPool, pity_rules: &PityRules) {
+    if cards.iter().any(|card| is_pity_card(card, card_pool, pity_rules)) {
+        return;
+    }
+
+    let card = match cards.first_mut() {
+        Some(card) => card,
+        None => return
+    };
+
+    if pity_rules.rarity_weight != 0 {
+        if let Some(card_id) = card_pool.choose_rare(rng, pity_rules.rarity_weight) {
+            card.card_id = card_id;
+            return;
         }
+    }
+
+    if pity_rules.level != 0 {
+        card.level = pity_rules.level as i32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
 
-        let card_create_data = UnlockedCardCreateData {
-            card_id: card.card_id.clone(),
+    #[test]
+    fn test_roll_level() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let never = LevelChances { level_one: 0, level_two: 0 };
+        assert!((0..1000).all(|_| roll_level(&mut rng, &never) == 0));
+
+        let always_two = LevelChances { level_one: 0, level_two: 1000 };
+        assert!((0..1000).all(|_| roll_level(&mut rng, &always_two) == 2));
+
+        let always_one = LevelChances { level_one: 1000, level_two: 0 };
+        assert!((0..1000).all(|_| roll_level(&mut rng, &always_one) == 1));
+    }
+
+    fn card(card_id: &str, level: i32) -> UnlockedCardCreateData {
+        UnlockedCardCreateData {
+            card_id: Id::from(card_id),
             level,
             frame_id: None,
-            quality
-        };
-
-        cards_create_data.push(card_create_data);
+            quality: 1
+        }
     }
 
-    Ok(cards_create_data)
+    #[test]
+    fn test_apply_pity() {
+        let card_pool = CardPool::new(vec![
+            (Id::from("common"), Some(100)),
+            (Id::from("rare"), Some(5)),
+        ], 100);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let level_rules = PityRules { threshold: 1, level: 2, rarity_weight: 0 };
+        let mut cards = vec![card("common", 0)];
+        apply_pity(&mut rng, &mut cards, &card_pool, &level_rules);
+        assert_eq!(cards[0].level, 2);
+
+        let rarity_rules = PityRules { threshold: 1, level: 1, rarity_weight: 10 };
+        let mut cards = vec![card("common", 0)];
+        apply_pity(&mut rng, &mut cards, &card_pool, &rarity_rules);
+        assert_eq!(cards[0].card_id, Id::from("rare"));
+        assert_eq!(cards[0].level, 0);
+
+        //already contains a pity card, nothing changes
+        let mut cards = vec![card("common", 0), card("common", 1)];
+        apply_pity(&mut rng, &mut cards, &card_pool, &rarity_rules);
+        assert_eq!(cards[0].level, 0);
+        assert_eq!(cards[0].card_id, Id::from("common"));
+
+        //no card is rare enough, falls back to the level
+        let strict_rules = PityRules { threshold: 1, level: 1, rarity_weight: 1 };
+        let mut cards = vec![card("common", 0)];
+        apply_pity(&mut rng, &mut cards, &card_pool, &strict_rules);
+        assert_eq!(cards[0].card_id, Id::from("common"));
+        assert_eq!(cards[0].level, 1);
+    }
 }
@@ -1,44 +1,120 @@
 use chrono::{DateTime, Utc};
+use sqlx::MySqlConnection;
 
 use crate::sql::Sql;
 use crate::shared::Id;
-use crate::shared::card::data::CardState;
-use super::data::CardCreateDataDb;
+use super::data::{PackTimeDb, PackOpenDb, PityProgress};
 
-pub async fn set_pack_time(sql: &Sql, user_id: &Id, collector_id: &Id, last_opened: DateTime<Utc>) -> Result<(), sqlx::Error> {
+///Make sure the packtimes row exists so it can be locked with `lock_pack_time`
+pub async fn ensure_pack_time(sql: &Sql, user_id: &Id, collector_id: &Id) -> Result<(), sqlx::Error> {
     sqlx::query(
-        "INSERT INTO packtimes (uid, coid, ptlastopened)
-         VALUES (?, ?, ?)
-         ON DUPLICATE KEY UPDATE ptlastopened = VALUES(ptlastopened);"
-    )
+        "INSERT IGNORE INTO packtimes (uid, coid, ptlastopened, ptpity)
+         VALUES (?, ?, NULL, 0);")
+        .bind(user_id)
+        .bind(collector_id)
+        .execute(sql.pool())
+        .await?;
+
+    Ok(())
+}
+
+///Locks the packtimes row until the transaction ends, concurrent pack openings of the same
+///user and collector wait here and see the updated cooldown afterwards
+pub async fn lock_pack_time(transaction: &mut MySqlConnection, user_id: &Id, collector_id: &Id) -> Result<PackTimeDb, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT ptlastopened, ptpity
+         FROM packtimes
+         WHERE uid=?
+         AND coid=?
+         FOR UPDATE;")
+        .bind(user_id)
+        .bind(collector_id)
+        .fetch_one(transaction)
+        .await
+}
+
+pub async fn set_pack_time(transaction: &mut MySqlConnection, user_id: &Id, collector_id: &Id, last_opened: DateTime<Utc>, pity: u32) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE packtimes
+         SET ptlastopened=?, ptpity=?
+         WHERE uid=?
+         AND coid=?;")
+    .bind(last_opened)
+    .bind(pity)
     .bind(user_id)
     .bind(collector_id)
-    .bind(last_opened)
-    .execute(sql.pool())
+    .execute(transaction)
     .await?;
 
     Ok(())
 }
 
-pub async fn get_random_card_data(sql: &Sql, card_amount: u32, collector_id: &Id) -> Result<Vec<CardCreateDataDb>, sqlx::Error> {
-    let cards: Vec<CardCreateDataDb> = sqlx::query_as(
-        "SELECT
-         cards.cid AS cardId,
-         cards.uid AS cardUserId
-         FROM
-         cards, cardtypes
-         WHERE 
-         cardtypes.ctid = cards.ctid
-         AND cardtypes.coid=?
-         AND cards.cstate=?
-         ORDER BY
-         RAND()
-         LIMIT ?;")
+pub async fn get_pack_open(transaction: &mut MySqlConnection, user_id: &Id, collector_id: &Id, idempotency_key: &str, since: DateTime<Utc>) -> Result<Option<PackOpenDb>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT poid, popitycount, popitythreshold, poguaranteed
+         FROM packopens
+         WHERE uid=?
+         AND coid=?
+         AND pokey=?
+         AND potime>=?;")
+        .bind(user_id)
         .bind(collector_id)
-        .bind(CardState::Created as u32)
-        .bind(card_amount)
+        .bind(idempotency_key)
+        .bind(since)
+        .fetch_optional(transaction)
+        .await
+}
+
+pub async fn get_pack_open_cards(sql: &Sql, pack_open_id: i32) -> Result<Vec<Id>, sqlx::Error> {
+    let cards: Vec<(Id, )> = sqlx::query_as(
+        "SELECT cuid
+         FROM packopencards
+         WHERE poid=?;")
+        .bind(pack_open_id)
         .fetch_all(sql.pool())
         .await?;
 
-    Ok(cards)
+    Ok(cards.into_iter().map(|(card_unlocked_id, )| card_unlocked_id).collect())
+}
+
+///Remember the pack for the idempotency key, expired keys of the user are dropped
+pub async fn add_pack_open(transaction: &mut MySqlConnection, user_id: &Id, collector_id: &Id, idempotency_key: &str, since: DateTime<Utc>, card_unlocked_ids: &[Id], pity: &PityProgress) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM packopens
+         WHERE uid=?
+         AND potime<?;")
+        .bind(user_id)
+        .bind(since)
+        .execute(&mut *transaction)
+        .await?;
+
+    let result = sqlx::query(
+        "INSERT INTO packopens
+         (uid, coid, pokey, popitycount, popitythreshold, poguaranteed, potime)
+         VALUES
+         (?, ?, ?, ?, ?, ?, NOW());")
+        .bind(user_id)
+        .bind(collector_id)
+        .bind(idempotency_key)
+        .bind(pity.count)
+        .bind(pity.threshold)
+        .bind(pity.guaranteed as i32)
+        .execute(&mut *transaction)
+        .await?;
+
+    let pack_open_id = result.last_insert_id();
+
+    for card_unlocked_id in card_unlocked_ids {
+        sqlx::query(
+            "INSERT INTO packopencards
+             (poid, cuid)
+             VALUES
+             (?, ?);")
+            .bind(pack_open_id)
+            .bind(card_unlocked_id)
+            .execute(&mut *transaction)
+            .await?;
+    }
+
+    Ok(())
 }
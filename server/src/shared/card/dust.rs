@@ -0,0 +1,75 @@
+use crate::sql::Sql;
+use crate::config::Config;
+use crate::shared::Id;
+use crate::shared::collector::{get_collector_setting, CollectorSetting};
+
+/// Dust rates of a collector, dust is the collector's currency
+///
+/// Dismantling a card yields `base + level * level_dust + quality * quality_dust`,
+/// negative qualities add nothing
+#[derive(Debug, Clone)]
+pub struct DustRules {
+    pub base: u32,
+    pub level_dust: u32,
+    pub quality_dust: u32,
+    /// Dust needed to craft a card
+    pub craft_cost: u32,
+}
+
+impl DustRules {
+    pub async fn load(sql: &Sql, collector_id: &Id, config: &Config) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            base: get_collector_setting(sql, collector_id, CollectorSetting::DismantleBaseDust, config.dismantle_base_dust).await?,
+            level_dust: get_collector_setting(sql, collector_id, CollectorSetting::DismantleLevelDust, config.dismantle_level_dust).await?,
+            quality_dust: get_collector_setting(sql, collector_id, CollectorSetting::DismantleQualityDust, config.dismantle_quality_dust).await?,
+            craft_cost: get_collector_setting(sql, collector_id, CollectorSetting::CraftCost, config.craft_cost).await?,
+        })
+    }
+
+    //computed in u64 so it can't get capped below the largest balance
+    pub fn dismantle_value(&self, level: i32, quality: i32) -> u64 {
+        (self.base as u64)
+            .saturating_add((level.max(0) as u64).saturating_mul(self.level_dust as u64))
+            .saturating_add((quality.max(0) as u64).saturating_mul(self.quality_dust as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> DustRules {
+        DustRules {
+            base: 5,
+            level_dust: 20,
+            quality_dust: 2,
+            craft_cost: 100,
+        }
+    }
+
+    #[test]
+    fn test_dismantle_value() {
+        let rules = rules();
+
+        assert_eq!(rules.dismantle_value(0, 1), 7);
+        assert_eq!(rules.dismantle_value(2, 5), 55);
+    }
+
+    #[test]
+    fn test_dismantle_value_negative_quality() {
+        let rules = rules();
+
+        assert_eq!(rules.dismantle_value(0, -3), 5);
+        assert_eq!(rules.dismantle_value(-1, 0), 5);
+    }
+
+    #[test]
+    fn test_dismantle_value_large() {
+        let rules = DustRules {
+            level_dust: u32::MAX,
+            ..rules()
+        };
+
+        assert_eq!(rules.dismantle_value(2, 0), 5 + 2 * u32::MAX as u64);
+    }
+}
@@ -1,3 +1,5 @@
 pub mod data;
 pub mod sql;
 pub mod packstats;
+pub mod upgrade;
+pub mod dust;
@@ -1,12 +1,10 @@
-use sqlx::Acquire;
+use sqlx::MySqlConnection;
 use chrono::{DateTime, Utc};
 
 use crate::sql::Sql;
 use crate::shared::Id;
 
-pub async fn add_pack_stats(sql: &Sql, user_id: &Id, collector_id: &Id, amount: i32, time: &DateTime<Utc>) -> Result<(), sqlx::Error> {
-    let mut transaction = sql.pool().begin().await?;
-
+pub async fn add_pack_stats(transaction: &mut MySqlConnection, user_id: &Id, collector_id: &Id, amount: i32, time: &DateTime<Utc>) -> Result<(), sqlx::Error> {
     for _ in 0..amount {
         sqlx::query(
             "INSERT INTO packstats
@@ -20,8 +18,6 @@ pub async fn add_pack_stats(sql: &Sql, user_id: &Id, collector_id: &Id, amount:
             .await?;
     }
 
-    transaction.commit().await?;
-
     Ok(())
 }
 
@@ -1,4 +1,4 @@
-use sqlx::mysql::MySqlQueryResult;
+use sqlx::mysql::MySqlConnection;
 use validator::ValidateRequired;
 use std::collections::{HashSet, HashMap};
 
@@ -60,7 +60,14 @@ pub async fn card_exists(sql: &Sql, card_id: &Id) -> Result<bool, sqlx::Error> {
 }
 
 //TODO: Think if should also safe collector_id
-pub async fn add_card(sql: &Sql, user_id: &Id, card_unlocked_id: &Id, _collector_id: &Id, card: &UnlockedCardCreateData) -> Result<(), sqlx::Error> {
+pub async fn add_card(sql: &Sql, user_id: &Id, card_unlocked_id: &Id, collector_id: &Id, card: &UnlockedCardCreateData) -> Result<(), sqlx::Error> {
+    let mut connection = sql.pool().acquire().await?;
+
+    add_card_transaction(&mut connection, user_id, card_unlocked_id, collector_id, card).await
+}
+
+///Same as `add_card`, for callers that need the insert to be part of a transaction
+pub async fn add_card_transaction(transaction: &mut MySqlConnection, user_id: &Id, card_unlocked_id: &Id, _collector_id: &Id, card: &UnlockedCardCreateData) -> Result<(), sqlx::Error> {
     sqlx::query(
         "INSERT INTO cardunlocks
          (cuid, uid, cid, cuquality, culevel, cfid, cutime)
@@ -72,7 +79,7 @@ pub async fn add_card(sql: &Sql, user_id: &Id, card_unlocked_id: &Id, _collector
         .bind(card.quality)
         .bind(card.level)
         .bind(card.frame_id)
-        .execute(sql.pool())
+        .execute(transaction)
         .await?;
     Ok(())
 }
@@ -153,17 +160,6 @@ pub async fn get_unlocked_cards(sql: &Sql, card_unlocked_ids: Vec<Id>, user_id:
     Ok(cards_db.into_iter().map(UnlockedCard::from).collect())
 }
 
-pub async fn delete_card(sql: &Sql, card_unlocked_id: &Id) -> Result<u64, sqlx::Error> {
-    let result: MySqlQueryResult = sqlx::query(
-        "DELETE FROM cardunlocks
-         WHERE cuid=?;")
-        .bind(card_unlocked_id)
-        .execute(sql.pool())
-        .await?;
-
-    Ok(result.rows_affected())
-}
-
 pub async fn user_owns_card(sql: &Sql, user_id: &Id, card_unlocked_id: &Id, collector_id: Option<&Id>) -> Result<bool, sqlx::Error> {
     let query = match collector_id {
         None =>
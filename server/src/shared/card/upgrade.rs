@@ -0,0 +1,139 @@
+use rand::Rng;
+
+use crate::sql::Sql;
+use crate::config::Config;
+use crate::shared::Id;
+use crate::shared::collector::{get_collector_setting, CollectorSetting};
+
+/// Upgrade formula of a collector
+///
+/// The success chance in percent is
+/// `base_chance + (quality_one + quality_two) * quality_multiplier - level * level_decay`,
+/// clamped to 0..=100
+#[derive(Debug, Clone)]
+pub struct UpgradeRules {
+    pub base_chance: u32,
+    pub quality_multiplier: u32,
+    pub level_decay: u32,
+    /// Range of the quality of the new card on success
+    pub success_quality_min: i32,
+    pub success_quality_max: i32,
+    /// Added to the average quality of both cards on failure
+    pub failure_quality_bonus: i32,
+    /// The new card's quality is always clamped to the pack quality range
+    pub quality_min: i32,
+    pub quality_max: i32,
+}
+
+impl UpgradeRules {
+    pub async fn load(sql: &Sql, collector_id: &Id, config: &Config) -> Result<Self, sqlx::Error> {
+        let quality_min = get_collector_setting(sql, collector_id, CollectorSetting::PackQualityMin, config.pack_quality_min).await?;
+        let quality_max = get_collector_setting(sql, collector_id, CollectorSetting::PackQualityMax, config.pack_quality_max).await?;
+
+        Ok(Self {
+            base_chance: get_collector_setting(sql, collector_id, CollectorSetting::UpgradeBaseChance, config.upgrade_base_chance).await?,
+            quality_multiplier: get_collector_setting(sql, collector_id, CollectorSetting::UpgradeQualityMultiplier, config.upgrade_quality_multiplier).await?,
+            level_decay: get_collector_setting(sql, collector_id, CollectorSetting::UpgradeLevelDecay, config.upgrade_level_decay).await?,
+            //Defaults to a fresh roll like opening a pack
+            success_quality_min: get_collector_setting(sql, collector_id, CollectorSetting::UpgradeSuccessQualityMin, quality_min).await?,
+            success_quality_max: get_collector_setting(sql, collector_id, CollectorSetting::UpgradeSuccessQualityMax, quality_max).await?,
+            failure_quality_bonus: get_collector_setting(sql, collector_id, CollectorSetting::UpgradeFailureQualityBonus, config.upgrade_failure_quality_bonus).await?,
+            quality_min,
+            quality_max,
+        })
+    }
+
+    /// Success chance in percent
+    pub fn chance(&self, level: i32, quality_one: i32, quality_two: i32) -> f32 {
+        let chance = self.base_chance as f32
+            + ((quality_one + quality_two) as f32 * self.quality_multiplier as f32)
+            - (level.max(0) as f32 * self.level_decay as f32);
+
+        chance.clamp(0f32, 100f32)
+    }
+
+    pub fn success_quality<R: Rng + ?Sized>(&self, rng: &mut R) -> i32 {
+        let min = self.success_quality_min.min(self.success_quality_max);
+        let max = self.success_quality_min.max(self.success_quality_max);
+
+        rng.random_range(min..=max).clamp(self.quality_min, self.quality_max.max(self.quality_min))
+    }
+
+    pub fn failure_quality(&self, quality_one: i32, quality_two: i32) -> i32 {
+        let average = ((quality_one as f32 + quality_two as f32) / 2f32).round() as i32;
+
+        (average + self.failure_quality_bonus).clamp(self.quality_min, self.quality_max.max(self.quality_min))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn rules() -> UpgradeRules {
+        UpgradeRules {
+            base_chance: 0,
+            quality_multiplier: 10,
+            level_decay: 0,
+            success_quality_min: 1,
+            success_quality_max: 5,
+            failure_quality_bonus: 1,
+            quality_min: 1,
+            quality_max: 5,
+        }
+    }
+
+    #[test]
+    fn test_default_chance() {
+        let rules = rules();
+
+        assert_eq!(rules.chance(0, 1, 1), 20f32);
+        assert_eq!(rules.chance(3, 2, 3), 50f32);
+        assert_eq!(rules.chance(0, 5, 10), 100f32);
+    }
+
+    #[test]
+    fn test_chance_with_decay() {
+        let rules = UpgradeRules {
+            base_chance: 30,
+            level_decay: 15,
+            ..rules()
+        };
+
+        assert_eq!(rules.chance(0, 1, 1), 50f32);
+        assert_eq!(rules.chance(2, 1, 1), 20f32);
+        assert_eq!(rules.chance(10, 1, 1), 0f32);
+    }
+
+    #[test]
+    fn test_quality_outcome() {
+        let rules = rules();
+
+        assert_eq!(rules.failure_quality(2, 3), 4);
+        assert_eq!(rules.failure_quality(5, 5), 5);
+
+        let rules = UpgradeRules {
+            success_quality_min: 4,
+            success_quality_max: 8,
+            ..rules
+        };
+        let mut rng = StdRng::seed_from_u64(2);
+        assert!((0..100).map(|_| rules.success_quality(&mut rng)).all(|quality| (4..=5).contains(&quality)));
+    }
+
+    #[test]
+    fn test_quality_range_inverted() {
+        //a collector can set PackQualityMin above PackQualityMax, the min wins
+        let rules = UpgradeRules {
+            quality_min: 4,
+            quality_max: 2,
+            ..rules()
+        };
+
+        let mut rng = StdRng::seed_from_u64(2);
+        assert!((0..100).map(|_| rules.success_quality(&mut rng)).all(|quality| quality == 4));
+        assert_eq!(rules.failure_quality(1, 1), 4);
+    }
+}
@@ -50,6 +50,22 @@ pub enum CollectorSetting {
     PackAmount,
     PackQualityMin,
     PackQualityMax,
+    PackLevelOneChance,
+    PackLevelTwoChance,
+    PackPityThreshold,
+    PackPityLevel,
+    PackPityRarityWeight,
+    UpgradeBaseChance,
+    UpgradeQualityMultiplier,
+    UpgradeLevelDecay,
+    UpgradeSuccessQualityMin,
+    UpgradeSuccessQualityMax,
+    UpgradeFailureQualityBonus,
+    MarketDailyReward,
+    DismantleBaseDust,
+    DismantleLevelDust,
+    DismantleQualityDust,
+    CraftCost,
     /* TradeCooldown,
     TradeCardLimit */
 }
@@ -61,6 +77,22 @@ impl std::string::ToString for CollectorSetting {
             CollectorSetting::PackAmount => "pack_amount",
             CollectorSetting::PackQualityMin => "pack_quality_min",
             CollectorSetting::PackQualityMax => "pack_quality_max",
+            CollectorSetting::PackLevelOneChance => "pack_level_one_chance",
+            CollectorSetting::PackLevelTwoChance => "pack_level_two_chance",
+            CollectorSetting::PackPityThreshold => "pack_pity_threshold",
+            CollectorSetting::PackPityLevel => "pack_pity_level",
+            CollectorSetting::PackPityRarityWeight => "pack_pity_rarity_weight",
+            CollectorSetting::UpgradeBaseChance => "upgrade_base_chance",
+            CollectorSetting::UpgradeQualityMultiplier => "upgrade_quality_multiplier",
+            CollectorSetting::UpgradeLevelDecay => "upgrade_level_decay",
+            CollectorSetting::UpgradeSuccessQualityMin => "upgrade_success_quality_min",
+            CollectorSetting::UpgradeSuccessQualityMax => "upgrade_success_quality_max",
+            CollectorSetting::UpgradeFailureQualityBonus => "upgrade_failure_quality_bonus",
+            CollectorSetting::MarketDailyReward => "market_daily_reward",
+            CollectorSetting::DismantleBaseDust => "dismantle_base_dust",
+            CollectorSetting::DismantleLevelDust => "dismantle_level_dust",
+            CollectorSetting::DismantleQualityDust => "dismantle_quality_dust",
+            CollectorSetting::CraftCost => "craft_cost",
             /* CollectorSetting::TradeCooldown => "trade_cooldown",
             CollectorSetting::TradeCardLimit => "trade_card_limit" */
         })
@@ -0,0 +1,33 @@
+use rocket::request::{self, FromRequest, Request};
+use rocket::http::Status;
+use rocketjson::error::JsonBodyError;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+const IDEMPOTENCY_KEY_LEN_MAX: usize = 255;
+
+/// Optional `Idempotency-Key` header, lets clients safely retry requests that must not run twice
+pub struct IdempotencyKey(pub Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IdempotencyKey {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let key = match req.headers().get_one(IDEMPOTENCY_KEY_HEADER) {
+            Some(key) => key.trim(),
+            None => return request::Outcome::Success(IdempotencyKey(None))
+        };
+
+        if key.is_empty() || key.len() > IDEMPOTENCY_KEY_LEN_MAX {
+            req.local_cache(|| JsonBodyError::CustomError(format!("Idempotency key has to be between 1 and {} characters", IDEMPOTENCY_KEY_LEN_MAX)));
+            return request::Outcome::Error((Status::BadRequest, ()));
+        }
+
+        if !key.chars().all(|c| c.is_ascii_graphic()) {
+            req.local_cache(|| JsonBodyError::CustomError(String::from("Idempotency key can only contain visible ascii characters")));
+            return request::Outcome::Error((Status::BadRequest, ()));
+        }
+
+        request::Outcome::Success(IdempotencyKey(Some(key.to_string())))
+    }
+}
@@ -1,34 +1,108 @@
 use rocket::fs::TempFile;
+use rocket::http::Status;
 use rocket::State;
 use std::fs;
 use std::path::PathBuf;
+use opencv::core::Vector;
+use opencv::prelude::*;
+use opencv::imgcodecs::{imdecode, imencode, IMREAD_COLOR, IMREAD_UNCHANGED, IMWRITE_JPEG_QUALITY, IMWRITE_WEBP_QUALITY};
 
-use crate::media::MediaManager;
+use crate::config::Config;
+use crate::media::{ImageFormat, MediaManager};
+use crate::media::sniff;
 
 /// Common image upload handler that uses MediaManager for hash-based storage
 ///
 /// This function:
-/// 1. Reads the uploaded file bytes
-/// 2. Uploads to MediaManager (which returns a content-hash ID)
-/// 3. Returns the image hash for database storage
+/// 1. Rejects files over `image_upload_max_bytes`
+/// 2. Reads the uploaded file bytes and sanitizes them (see `sanitize_image`)
+/// 3. Uploads to MediaManager (which returns a content-hash ID)
+/// 4. Queues generation of all variants of `media_type`
+/// 5. Returns the image hash for database storage
 pub async fn upload_image_with_media_manager(
     file: &mut TempFile<'_>,
+    media_type: &str,
+    config: &State<Config>,
     media_manager: &State<MediaManager>,
 ) -> Result<String, UploadError> {
+    let max_bytes = config.image_upload_max_bytes as u64;
+    if file.len() > max_bytes {
+        return Err(UploadError::TooLarge(max_bytes));
+    }
+
     // Read file bytes
     let bytes = tokio::fs::read(file.path().ok_or(UploadError::NoFilePath)?)
         .await
         .map_err(|_| UploadError::ReadError)?;
 
+    // Decoding and encoding is CPU-heavy, keep it off the async workers
+    let max_pixels = config.image_upload_max_pixels as u64;
+    let bytes = tokio::task::spawn_blocking(move || sanitize_image(&bytes, max_bytes, max_pixels))
+        .await
+        .map_err(|_| UploadError::EncodeError)??;
+
     // Upload to MediaManager (returns hash-based ID)
     let image_id = media_manager
-        .upload_image(&bytes)
+        .upload_image(&bytes, Some(media_type))
         .await
         .map_err(|_| UploadError::MediaManagerError)?;
 
     Ok(image_id)
 }
 
+/// Validate uploaded image data and re-encode it without metadata
+///
+/// The format is sniffed from the magic bytes and the pixel count read from
+/// the header, so decompression bombs are rejected before decoding. Decoding
+/// applies the EXIF orientation of JPEGs and keeps the alpha channel of PNG
+/// and WebP, re-encoding drops EXIF, GPS and all other metadata. The result
+/// is what gets hashed and stored.
+pub fn sanitize_image(data: &[u8], max_bytes: u64, max_pixels: u64) -> Result<Vec<u8>, UploadError> {
+    if data.len() as u64 > max_bytes {
+        return Err(UploadError::TooLarge(max_bytes));
+    }
+
+    //NOTE: the OpenCV build can't decode AVIF, so it isn't accepted as upload
+    let format = match sniff::sniff_format(data) {
+        Some(format) if format != ImageFormat::Avif => format,
+        _ => return Err(UploadError::UnsupportedFormat),
+    };
+
+    let (width, height) = sniff::dimensions(format, data).ok_or(UploadError::Undecodable)?;
+    if width == 0 || height == 0 {
+        return Err(UploadError::Undecodable);
+    }
+    if width as u64 * height as u64 > max_pixels {
+        return Err(UploadError::TooManyPixels(width, height));
+    }
+
+    // PNG and WebP keep their alpha channel, JPEG has none but needs the EXIF
+    // orientation, which only IMREAD_COLOR applies
+    let flags = match format {
+        ImageFormat::Jpeg => IMREAD_COLOR,
+        _ => IMREAD_UNCHANGED,
+    };
+    let image = imdecode(&Vector::<u8>::from_slice(data), flags)
+        .map_err(|_| UploadError::Undecodable)?;
+    if image.empty() {
+        return Err(UploadError::Undecodable);
+    }
+
+    // Keep the uploaded format, lossless where it was
+    let (extension, params) = match format {
+        ImageFormat::Jpeg => (".jpg", vec![IMWRITE_JPEG_QUALITY, 95]),
+        ImageFormat::Png => (".png", vec![]),
+        // Quality above 100 is lossless
+        _ => (".webp", vec![IMWRITE_WEBP_QUALITY, 101]),
+    };
+
+    let mut buffer = Vector::<u8>::new();
+    match imencode(extension, &image, &mut buffer, &Vector::from_slice(&params)) {
+        Ok(true) => Ok(buffer.to_vec()),
+        _ => Err(UploadError::EncodeError),
+    }
+}
+
 /// Legacy common upload handler for filesystem-based storage
 ///
 /// This function handles the common logic for uploading images to the filesystem:
@@ -68,6 +142,27 @@ pub enum UploadError {
     CreateDirError,
     CopyError,
     MediaManagerError,
+    /// Larger than the maximum in bytes
+    TooLarge(u64),
+    /// Not a JPEG, PNG or WebP image
+    UnsupportedFormat,
+    /// Width and height exceed the pixel limit
+    TooManyPixels(u32, u32),
+    /// Malformed header or image data
+    Undecodable,
+    EncodeError,
+}
+
+impl UploadError {
+    /// Response status, rejected images are client errors
+    pub fn status(&self) -> Status {
+        match self {
+            UploadError::TooLarge(_) => Status::PayloadTooLarge,
+            UploadError::UnsupportedFormat => Status::UnsupportedMediaType,
+            UploadError::TooManyPixels(_, _) | UploadError::Undecodable => Status::UnprocessableEntity,
+            _ => Status::InternalServerError,
+        }
+    }
 }
 
 impl std::fmt::Display for UploadError {
@@ -78,8 +173,85 @@ impl std::fmt::Display for UploadError {
             UploadError::CreateDirError => write!(f, "Failed to create directory"),
             UploadError::CopyError => write!(f, "Failed to copy file"),
             UploadError::MediaManagerError => write!(f, "Media manager error"),
+            UploadError::TooLarge(max) => write!(f, "Image is larger than {} bytes", max),
+            UploadError::UnsupportedFormat => write!(f, "Unsupported image format, use JPEG, PNG or WebP"),
+            UploadError::TooManyPixels(width, height) => write!(f, "Image has too many pixels ({}x{})", width, height),
+            UploadError::Undecodable => write!(f, "File is not a valid image"),
+            UploadError::EncodeError => write!(f, "Failed to process image"),
         }
     }
 }
 
 impl std::error::Error for UploadError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{Mat, Scalar, CV_8UC3, CV_8UC4};
+
+    const MAX_BYTES: u64 = 1024 * 1024;
+    const MAX_PIXELS: u64 = 1_000_000;
+
+    fn jpeg(width: i32, height: i32) -> Vec<u8> {
+        let image = Mat::new_rows_cols_with_default(height, width, CV_8UC3, Scalar::new(40.0, 80.0, 120.0, 0.0)).unwrap();
+        let mut buffer = Vector::<u8>::new();
+        imencode(".jpg", &image, &mut buffer, &Vector::new()).unwrap();
+        buffer.to_vec()
+    }
+
+    /// APP1 segment with only an orientation tag (6: rotate 90° clockwise)
+    fn with_exif_orientation(data: &[u8]) -> Vec<u8> {
+        let mut exif = vec![0xFF, 0xE1, 0x00, 34];
+        exif.extend_from_slice(b"Exif\0\0");
+        exif.extend_from_slice(b"MM\0*\0\0\0\x08");
+        exif.extend_from_slice(&[0x00, 0x01]);
+        exif.extend_from_slice(&[0x01, 0x12, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00, 0x06, 0x00, 0x00]);
+        exif.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+
+        let mut result = data[..2].to_vec();
+        result.extend(exif);
+        result.extend_from_slice(&data[2..]);
+        result
+    }
+
+    #[test]
+    fn test_sanitize_valid() {
+        let data = sanitize_image(&jpeg(64, 32), MAX_BYTES, MAX_PIXELS).unwrap();
+
+        assert_eq!(sniff::sniff_format(&data), Some(ImageFormat::Jpeg));
+        assert_eq!(sniff::dimensions(ImageFormat::Jpeg, &data), Some((64, 32)));
+    }
+
+    #[test]
+    fn test_sanitize_strips_exif() {
+        let data = with_exif_orientation(&jpeg(64, 32));
+        assert!(data.windows(4).any(|window| window == b"Exif"));
+
+        let data = sanitize_image(&data, MAX_BYTES, MAX_PIXELS).unwrap();
+
+        assert!(!data.windows(4).any(|window| window == b"Exif"));
+        // Orientation is applied to the pixels
+        assert_eq!(sniff::dimensions(ImageFormat::Jpeg, &data), Some((32, 64)));
+    }
+
+    #[test]
+    fn test_sanitize_keeps_alpha() {
+        let image = Mat::new_rows_cols_with_default(32, 64, CV_8UC4, Scalar::new(40.0, 80.0, 120.0, 128.0)).unwrap();
+        let mut buffer = Vector::<u8>::new();
+        imencode(".png", &image, &mut buffer, &Vector::new()).unwrap();
+
+        let data = sanitize_image(&buffer.to_vec(), MAX_BYTES, MAX_PIXELS).unwrap();
+
+        assert_eq!(sniff::sniff_format(&data), Some(ImageFormat::Png));
+        let decoded = imdecode(&Vector::<u8>::from_slice(&data), IMREAD_UNCHANGED).unwrap();
+        assert_eq!(decoded.channels(), 4);
+    }
+
+    #[test]
+    fn test_sanitize_rejects() {
+        assert!(matches!(sanitize_image(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>", MAX_BYTES, MAX_PIXELS), Err(UploadError::UnsupportedFormat)));
+        assert!(matches!(sanitize_image(&jpeg(64, 32)[..20], MAX_BYTES, MAX_PIXELS), Err(UploadError::Undecodable)));
+        assert!(matches!(sanitize_image(&jpeg(64, 32), MAX_BYTES, 1000), Err(UploadError::TooManyPixels(64, 32))));
+        assert!(matches!(sanitize_image(&jpeg(64, 32), 100, MAX_PIXELS), Err(UploadError::TooLarge(100))));
+    }
+}
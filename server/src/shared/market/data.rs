@@ -0,0 +1,18 @@
+use serde_repr::{Serialize_repr, Deserialize_repr};
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize_repr, Deserialize_repr)]
+#[repr(i32)]
+pub enum ListingStatus {
+    //NOTE: the value is part of the mlopencuid column in the 0010 migration
+    Open = 0,
+    Sold = 1,
+    Cancelled = 2
+}
+
+#[derive(Debug, FromRow)]
+pub struct BalanceDb {
+    pub bamount: i32,
+    pub bdaily: Option<DateTime<Utc>>
+}
@@ -0,0 +1,79 @@
+use sqlx::mysql::MySqlConnection;
+
+use crate::sql::Sql;
+use crate::shared::Id;
+use super::data::{BalanceDb, ListingStatus};
+
+//has to be called before locking the balance in a transaction
+pub async fn ensure_balance(sql: &Sql, user_id: &Id, collector_id: &Id) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT IGNORE INTO balances
+         (uid, coid, bamount)
+         VALUES
+         (?, ?, 0);")
+        .bind(user_id)
+        .bind(collector_id)
+        .execute(sql.pool())
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_balance(sql: &Sql, user_id: &Id, collector_id: &Id) -> Result<BalanceDb, sqlx::Error> {
+    let balance: Option<BalanceDb> = sqlx::query_as(
+        "SELECT bamount, bdaily
+         FROM balances
+         WHERE uid=? AND coid=?;")
+        .bind(user_id)
+        .bind(collector_id)
+        .fetch_optional(sql.pool())
+        .await?;
+
+    Ok(balance.unwrap_or(BalanceDb {
+        bamount: 0,
+        bdaily: None
+    }))
+}
+
+pub async fn lock_balance(transaction: &mut MySqlConnection, user_id: &Id, collector_id: &Id) -> Result<BalanceDb, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT bamount, bdaily
+         FROM balances
+         WHERE uid=? AND coid=?
+         FOR UPDATE;")
+        .bind(user_id)
+        .bind(collector_id)
+        .fetch_one(transaction)
+        .await
+}
+
+//amount can be negative, callers have to check the balance before
+pub async fn add_balance(transaction: &mut MySqlConnection, user_id: &Id, collector_id: &Id, amount: i32) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO balances
+         (uid, coid, bamount)
+         VALUES
+         (?, ?, ?)
+         ON DUPLICATE KEY UPDATE bamount=bamount+?;")
+        .bind(user_id)
+        .bind(collector_id)
+        .bind(amount)
+        .bind(amount)
+        .execute(transaction)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn card_listed(sql: &Sql, card_unlocked_id: &Id) -> Result<bool, sqlx::Error> {
+    let (count, ): (i64, ) = sqlx::query_as(
+        "SELECT COUNT(*)
+         FROM marketlistings
+         WHERE cuid=? AND mlstatus=?;")
+        .bind(card_unlocked_id)
+        .bind(ListingStatus::Open as i32)
+        .fetch_one(sql.pool())
+        .await?;
+
+    Ok(count != 0)
+}
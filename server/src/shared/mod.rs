@@ -13,6 +13,9 @@ pub mod collector;
 pub mod id;
 pub mod image;
 pub mod image_upload;
+pub mod rarity;
+pub mod idempotency;
+pub mod market;
 
 pub use id::Id;
 
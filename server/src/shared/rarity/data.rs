@@ -0,0 +1,22 @@
+use serde::Serialize;
+use sqlx::FromRow;
+
+use crate::shared::Id;
+
+#[derive(Debug, Serialize, FromRow)]
+#[serde(rename_all="camelCase")]
+pub struct Rarity {
+    #[sqlx(rename="raid")]
+    pub id: Id,
+    #[sqlx(rename="raname")]
+    pub name: String,
+    #[sqlx(rename="raweight")]
+    pub weight: i32,
+}
+
+#[derive(Debug, FromRow)]
+#[sqlx(rename_all="camelCase")]
+pub struct PoolCardDb {
+    pub card_id: Id,
+    pub weight: Option<i32>,
+}
@@ -0,0 +1,6 @@
+pub mod data;
+pub mod sql;
+pub mod pool;
+
+pub use data::Rarity;
+pub use pool::{CardPool, CardPoolCache};
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use dashmap::DashMap;
+use rand::Rng;
+use rand::seq::IndexedRandom;
+
+use crate::sql::Sql;
+use crate::shared::Id;
+use super::sql;
+
+/// Cards that can drop from a pack of a collector together with their drop weight
+#[derive(Debug, Clone)]
+pub struct CardPool {
+    cards: Vec<(Id, u32)>,
+}
+
+impl CardPool {
+    /// Cards without a rarity drop with `default_weight`
+    pub fn new(cards: Vec<(Id, Option<i32>)>, default_weight: u32) -> Self {
+        Self {
+            cards: cards
+                .into_iter()
+                .map(|(card_id, weight)| {
+                    let weight = weight.map(|w| w.max(0) as u32).unwrap_or(default_weight);
+                    (card_id, weight)
+                })
+                .filter(|(_, weight)| *weight > 0)
+                .collect()
+        }
+    }
+
+    pub fn weight(&self, card_id: &Id) -> Option<u32> {
+        self.cards
+            .iter()
+            .find(|(id, _)| id == card_id)
+            .map(|(_, weight)| *weight)
+    }
+
+    /// Weighted pick among the cards with a weight of at most `max_weight`
+    pub fn choose_rare<R: Rng + ?Sized>(&self, rng: &mut R, max_weight: u32) -> Option<Id> {
+        let rare: Vec<&(Id, u32)> = self.cards
+            .iter()
+            .filter(|(_, weight)| *weight <= max_weight)
+            .collect();
+
+        rare.choose_weighted(rng, |(_, weight)| *weight as f64)
+            .ok()
+            .map(|(card_id, _)| card_id.clone())
+    }
+
+    /// Pick up to `amount` distinct cards, each draw weighted by the card's rarity
+    pub fn choose<R: Rng + ?Sized>(&self, rng: &mut R, amount: usize) -> Vec<Id> {
+        match self.cards.choose_multiple_weighted(rng, amount, |(_, weight)| *weight as f64) {
+            Ok(chosen) => chosen.map(|(card_id, _)| card_id.clone()).collect(),
+            //Only happens on invalid weights, fall back to a uniform pick
+            Err(_) => self.cards.choose_multiple(rng, amount).map(|(card_id, _)| card_id.clone()).collect()
+        }
+    }
+}
+
+/// Per collector cache of card pools so opening a pack does not have to query all cards
+///
+/// Pools are rebuilt after `ttl` or when invalidated, e.g. when a rarity or card changes
+pub struct CardPoolCache {
+    pools: DashMap<Id, (Instant, Arc<CardPool>)>,
+    ttl: Duration,
+    default_weight: u32,
+}
+
+impl CardPoolCache {
+    pub fn new(ttl: Duration, default_weight: u32) -> Self {
+        Self {
+            pools: DashMap::new(),
+            ttl,
+            default_weight,
+        }
+    }
+
+    pub async fn get(&self, sql: &Sql, collector_id: &Id) -> Result<Arc<CardPool>, sqlx::Error> {
+        if let Some(entry) = self.pools.get(collector_id) {
+            let (created, pool) = entry.value();
+            if created.elapsed() < self.ttl {
+                return Ok(Arc::clone(pool));
+            }
+        }
+
+        let cards = sql::get_card_pool(sql, collector_id)
+            .await?
+            .into_iter()
+            .map(|card| (card.card_id, card.weight))
+            .collect();
+
+        let pool = Arc::new(CardPool::new(cards, self.default_weight));
+        self.pools.insert(collector_id.clone(), (Instant::now(), Arc::clone(&pool)));
+
+        Ok(pool)
+    }
+
+    pub fn invalidate(&self, collector_id: &Id) {
+        self.pools.remove(collector_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_choose_distinct_cards() {
+        let pool = CardPool::new(vec![
+            (Id::from("a"), Some(10)),
+            (Id::from("b"), None),
+            (Id::from("c"), Some(1)),
+        ], 5);
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let chosen = pool.choose(&mut rng, 5);
+
+        assert_eq!(chosen.len(), 3);
+        assert_eq!(chosen.iter().collect::<HashSet<_>>().len(), 3);
+    }
+
+    #[test]
+    fn test_choose_respects_weights() {
+        let pool = CardPool::new(vec![
+            (Id::from("common"), Some(1000)),
+            (Id::from("rare"), Some(1)),
+        ], 1);
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let rare = (0..1000)
+            .filter(|_| pool.choose(&mut rng, 1)[0] == Id::from("rare"))
+            .count();
+
+        assert!(rare < 20, "rare card dropped {} times", rare);
+    }
+
+    #[test]
+    fn test_choose_rare() {
+        let pool = CardPool::new(vec![
+            (Id::from("common"), Some(1000)),
+            (Id::from("rare"), Some(10)),
+        ], 1);
+
+        let mut rng = StdRng::seed_from_u64(5);
+        assert!((0..100).all(|_| pool.choose_rare(&mut rng, 10) == Some(Id::from("rare"))));
+        assert_eq!(pool.choose_rare(&mut rng, 5), None);
+        assert_eq!(pool.weight(&Id::from("common")), Some(1000));
+    }
+
+    #[test]
+    fn test_zero_weight_never_drops() {
+        let pool = CardPool::new(vec![
+            (Id::from("a"), Some(0)),
+            (Id::from("b"), Some(1)),
+        ], 1);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        assert_eq!(pool.choose(&mut rng, 2), vec![Id::from("b")]);
+    }
+}
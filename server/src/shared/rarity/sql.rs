@@ -0,0 +1,48 @@
+use crate::sql::Sql;
+use crate::shared::Id;
+use crate::shared::card::data::CardState;
+use super::data::{Rarity, PoolCardDb};
+
+pub async fn get_rarities(sql: &Sql, collector_id: &Id) -> Result<Vec<Rarity>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT raid, raname, raweight
+         FROM rarities
+         WHERE coid=?
+         ORDER BY raweight DESC;")
+        .bind(collector_id)
+        .fetch_all(sql.pool())
+        .await
+}
+
+pub async fn rarity_exists(sql: &Sql, collector_id: &Id, rarity_id: &Id) -> Result<bool, sqlx::Error> {
+    let (count, ): (i64, ) = sqlx::query_as(
+        "SELECT COUNT(*)
+         FROM rarities
+         WHERE coid=?
+         AND raid=?;")
+        .bind(collector_id)
+        .bind(rarity_id)
+        .fetch_one(sql.pool())
+        .await?;
+
+    Ok(count != 0)
+}
+
+///Created cards of a collector with the weight of their rarity,
+///the rarity of the card takes precedence over the one of its card type
+pub async fn get_card_pool(sql: &Sql, collector_id: &Id) -> Result<Vec<PoolCardDb>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT
+         cards.cid AS cardId,
+         COALESCE(cardrarities.raweight, typerarities.raweight) AS weight
+         FROM cards
+         INNER JOIN cardtypes ON cardtypes.ctid = cards.ctid
+         LEFT JOIN rarities AS cardrarities ON cardrarities.raid = cards.raid
+         LEFT JOIN rarities AS typerarities ON typerarities.raid = cardtypes.raid
+         WHERE cardtypes.coid=?
+         AND cards.cstate=?;")
+        .bind(collector_id)
+        .bind(CardState::Created as u32)
+        .fetch_all(sql.pool())
+        .await
+}
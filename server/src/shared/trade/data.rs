@@ -1,7 +1,10 @@
+use serde::Serialize;
 use serde_repr::{Serialize_repr, Deserialize_repr};
 use sqlx::FromRow;
 use chrono::{DateTime, Utc};
 
+use crate::shared::Id;
+
 #[derive(Debug, Clone, Copy, Serialize_repr, Deserialize_repr)]
 #[repr(i32)]
 pub enum TradeStatus {
@@ -30,3 +33,70 @@ pub struct TradeDb {
     pub friend_status: i32
 }
 
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize_repr, Deserialize_repr)]
+#[repr(i32)]
+pub enum TradeOfferStatus {
+    Open = 0,
+    Accepted = 1,
+    Declined = 2,
+    Cancelled = 3,
+    //replaced by a counter-offer
+    Superseded = 4,
+    Expired = 5
+}
+
+impl TradeOfferStatus {
+    pub fn from_int(status: i32) -> Result<Self, ()> {
+        match status {
+            0 => Ok(TradeOfferStatus::Open),
+            1 => Ok(TradeOfferStatus::Accepted),
+            2 => Ok(TradeOfferStatus::Declined),
+            3 => Ok(TradeOfferStatus::Cancelled),
+            4 => Ok(TradeOfferStatus::Superseded),
+            5 => Ok(TradeOfferStatus::Expired),
+            _ => Err(())
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct TradeOfferDb {
+    pub toid: Id,
+    pub tid: Id,
+    pub coid: Id,
+    pub uidfrom: Id,
+    pub uidto: Id,
+    pub toparent: Option<Id>,
+    pub tostatus: i32,
+    pub tocreated: DateTime<Utc>,
+    pub toexpires: DateTime<Utc>
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct TradeOffer {
+    pub id: Id,
+    pub user_id: Id,
+    pub user_friend_id: Id,
+    pub parent_id: Option<Id>,
+    pub status: TradeOfferStatus,
+    pub created: DateTime<Utc>,
+    pub expires: DateTime<Utc>
+}
+
+impl TryFrom<TradeOfferDb> for TradeOffer {
+    type Error = ();
+
+    fn try_from(offer: TradeOfferDb) -> Result<Self, Self::Error> {
+        Ok(Self {
+            status: TradeOfferStatus::from_int(offer.tostatus)?,
+            id: offer.toid,
+            user_id: offer.uidfrom,
+            user_friend_id: offer.uidto,
+            parent_id: offer.toparent,
+            created: offer.tocreated,
+            expires: offer.toexpires
+        })
+    }
+}
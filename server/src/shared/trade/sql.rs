@@ -1,8 +1,9 @@
 use sqlx::Acquire;
+use chrono::Utc;
 
 use crate::sql::Sql;
 use crate::shared::Id;
-use super::data::{TradeStatus, TradeDb};
+use super::data::{TradeStatus, TradeDb, TradeOfferStatus, TradeOfferDb};
 
 pub async fn set_trade_status(sql: &Sql, trade_id: &Id, status: TradeStatus) -> Result<(), sqlx::Error> {
     let mut transaction = sql.pool().begin().await?;
@@ -153,3 +154,94 @@ pub async fn get_trade(sql: &Sql, user_id: &Id, trade_id: &Id) -> Result<TradeDb
         .fetch_one(sql.pool())
         .await
 }
+
+//offers are expired lazily whenever a trade is looked at
+pub async fn expire_trade_offers(sql: &Sql, trade_id: &Id) -> Result<(), sqlx::Error> {
+    let mut transaction = sql.pool().begin().await?;
+
+    let expired = sqlx::query(
+        "UPDATE tradeoffers
+         SET tostatus=?, toresolved=?
+         WHERE tid=? AND tostatus=? AND toexpires<=?;")
+        .bind(TradeOfferStatus::Expired as i32)
+        .bind(Utc::now())
+        .bind(trade_id)
+        .bind(TradeOfferStatus::Open as i32)
+        .bind(Utc::now())
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+
+    if expired != 0 {
+        sqlx::query(
+            "UPDATE trades
+             SET tstatusone=?, tstatustwo=?
+             WHERE tid=?;")
+            .bind(TradeStatus::UnConfirmed as i32)
+            .bind(TradeStatus::UnConfirmed as i32)
+            .bind(trade_id)
+            .execute(&mut *transaction)
+            .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+pub async fn get_open_trade_offer(sql: &Sql, trade_id: &Id) -> Result<Option<TradeOfferDb>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT toid, tid, coid, uidfrom, uidto, toparent, tostatus, tocreated, toexpires
+         FROM tradeoffers
+         WHERE tid=? AND tostatus=? AND toexpires>?;")
+        .bind(trade_id)
+        .bind(TradeOfferStatus::Open as i32)
+        .bind(Utc::now())
+        .fetch_optional(sql.pool())
+        .await
+}
+
+pub async fn get_trade_offer(sql: &Sql, offer_id: &Id) -> Result<Option<TradeOfferDb>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT toid, tid, coid, uidfrom, uidto, toparent, tostatus, tocreated, toexpires
+         FROM tradeoffers
+         WHERE toid=?;")
+        .bind(offer_id)
+        .fetch_optional(sql.pool())
+        .await
+}
+
+//closes an open offer and resets the confirmations, false if the offer was not open anymore
+pub async fn close_trade_offer(sql: &Sql, offer_id: &Id, trade_id: &Id, status: TradeOfferStatus) -> Result<bool, sqlx::Error> {
+    let mut transaction = sql.pool().begin().await?;
+
+    let closed = sqlx::query(
+        "UPDATE tradeoffers
+         SET tostatus=?, toresolved=?
+         WHERE toid=? AND tostatus=?;")
+        .bind(status as i32)
+        .bind(Utc::now())
+        .bind(offer_id)
+        .bind(TradeOfferStatus::Open as i32)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+
+    if closed == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        "UPDATE trades
+         SET tstatusone=?, tstatustwo=?
+         WHERE tid=?;")
+        .bind(TradeStatus::UnConfirmed as i32)
+        .bind(TradeStatus::UnConfirmed as i32)
+        .bind(trade_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    transaction.commit().await?;
+
+    Ok(true)
+}
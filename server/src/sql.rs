@@ -1,5 +1,4 @@
 use sqlx::{Pool, MySql};
-use std::fs;
 
 #[derive(Debug, Clone)]
 pub struct Sql(pub Pool<MySql>);
@@ -10,26 +9,97 @@ impl Sql {
     }
 }
 
-//TODO: find a better solution
-pub async fn setup_db(sql: &Sql, file: &str) -> Result<(), sqlx::Error> {
+#[cfg(test)]
+pub mod test {
+    use std::str::FromStr;
+    use sqlx::{Connection, MySqlConnection};
+    use sqlx::mysql::{MySqlConnectOptions, MySqlPoolOptions};
 
-    let file_sql = fs::read_to_string(file)
-        .expect("Failed reading file");
+    use super::Sql;
+    use crate::migration::{load_migrations, MigrationRunner};
+    use crate::shared::crypto::random_string::generate_random_string;
 
-    let queries = file_sql.split(";");
+    /// Throwaway database with all migrations applied, dropped again with the value
+    ///
+    /// `TEST_DATABASE_URL` has to point to a MySQL server, e.g. `mysql://root@localhost`.
+    /// Tests using it are `#[ignore]`d, run them with
+    /// `TEST_DATABASE_URL=mysql://root@localhost cargo test -- --ignored`
+    pub struct TestDb {
+        pub sql: Sql,
+        url: String,
+        name: String,
+    }
+
+    impl TestDb {
+        pub async fn create() -> Self {
+            let url = std::env::var("TEST_DATABASE_URL")
+                .expect("TEST_DATABASE_URL has to be set for database tests");
+
+            let mut server = MySqlConnection::connect(&url)
+                .await
+                .expect("Connecting to the test database server failed");
+
+            let name = format!("cardcollector_test_{}", generate_random_string(10));
+            sqlx::query(&format!("CREATE DATABASE `{}`;", name))
+                .execute(&mut server)
+                .await
+                .expect("Creating the test database failed");
+            let _ = server.close().await;
+
+            let options = MySqlConnectOptions::from_str(&url)
+                .expect("Invalid TEST_DATABASE_URL")
+                .database(&name);
+            let sql = Sql(MySqlPoolOptions::new()
+                .max_connections(5)
+                .connect_with(options)
+                .await
+                .expect("Connecting to the test database failed"));
 
-    for query in queries {
-        let prepared_query = String::from(query.trim()) + ";";
-        if prepared_query.len() == 1 { continue; }
+            // From here on the database is dropped even if migrating fails
+            let db = Self {
+                sql,
+                url,
+                name,
+            };
 
-        let result = sqlx::query(&prepared_query)
-            .execute(sql.pool())
-            .await;
+            let migrations = load_migrations(concat!(env!("CARGO_MANIFEST_DIR"), "/sqlfiles/migrations"))
+                .expect("Loading migrations failed");
+            MigrationRunner::new(migrations)
+                .apply(&db.sql)
+                .await
+                .expect("Migrating the test database failed");
 
-        if result.is_ok() {
-            println!("{}", prepared_query);
+            db
         }
     }
 
-    Ok(())
+    //NOTE: also runs when an assertion panics. The pool belongs to the runtime of the test,
+    //which is blocked here, so the database is dropped over a new connection on its own runtime.
+    impl Drop for TestDb {
+        fn drop(&mut self) {
+            let url = self.url.clone();
+            let name = self.name.clone();
+
+            let dropped = std::thread::spawn(move || {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(|e| e.to_string())?;
+
+                runtime.block_on(async {
+                    let mut connection = MySqlConnection::connect(&url).await?;
+                    // Don't hang on locks of transactions the panicking test left open
+                    sqlx::query("SET SESSION lock_wait_timeout=10;").execute(&mut connection).await?;
+                    sqlx::query(&format!("DROP DATABASE `{}`;", name)).execute(&mut connection).await?;
+                    connection.close().await
+                }).map_err(|e| e.to_string())
+            }).join();
+
+            match dropped {
+                Ok(Ok(())) => {},
+                Ok(Err(e)) => eprintln!("Dropping the test database {} failed: {}", self.name, e),
+                Err(_) => eprintln!("Dropping the test database {} failed", self.name),
+            }
+        }
+    }
 }
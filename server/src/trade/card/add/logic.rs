@@ -7,7 +7,7 @@ use super::sql;
 use crate::shared::crypto::JwtToken;
 use crate::sql::Sql;
 use crate::config::Config;
-use crate::shared::{friend, card, trade, notification};
+use crate::shared::{friend, card, trade, notification, market};
 use crate::shared::Id;
 use crate::{verify_user, verify_collector};
 
@@ -43,6 +43,10 @@ pub async fn trade_card_add_route(card_unlocked_id: Id, user_friend_id: Id, coll
         return ApiResponseErr::api_err(Status::Conflict, format!("Card with id {} is already in a trade", &card_unlocked_id));
     }
 
+    if rjtry!(market::sql::card_listed(sql, &card_unlocked_id).await) {
+        return ApiResponseErr::api_err(Status::Conflict, format!("Card with id {} is listed on the market", &card_unlocked_id));
+    }
+
     rjtry!(trade::sql::trade_add_card(sql, &trade_id, &card_unlocked_id).await);
 
     if !rjtry!(trade::sql::suggestion_in_trade(sql, &trade_id, &card_unlocked_id).await) {
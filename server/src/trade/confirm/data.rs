@@ -1,12 +1,47 @@
 use serde::Serialize;
+use sqlx::FromRow;
 use chrono::{DateTime, Utc};
 
+use crate::shared::Id;
+
 #[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
 pub struct TradeConfirmReponse {
-    pub message: String
+    pub message: String,
+    pub offer_id: Id
 }
 
 pub enum TradeTimeOver {
     Yes,
     No(DateTime<Utc>),
 }
+
+pub struct TradeOfferCreateData<'a> {
+    pub offer_id: &'a Id,
+    pub trade_id: &'a Id,
+    pub collector_id: &'a Id,
+    pub user_id: &'a Id,
+    pub user_friend_id: &'a Id,
+    //open offer that gets superseded by this one
+    pub parent_id: Option<&'a Id>,
+    pub expires: DateTime<Utc>
+}
+
+pub enum TradeCompleteResult {
+    Completed,
+    OfferClosed,
+    //the card was removed from the trade, upgraded or is not owned by the offering user anymore
+    CardChanged(Id),
+    //cards were added to the trade since the offer
+    TradeChanged,
+    //the card got an open market listing since it was added to the trade
+    CardListed(Id)
+}
+
+#[derive(Debug, FromRow)]
+pub struct OfferCardDb {
+    pub cuid: Id,
+    pub uidfrom: Id,
+    pub uidowner: Option<Id>,
+    pub tid: Option<Id>
+}
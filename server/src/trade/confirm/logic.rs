@@ -3,7 +3,7 @@ use rocket::http::Status;
 use rocket::State;
 use chrono::{DateTime, Utc, Duration};
 
-use super::data::{TradeConfirmReponse, TradeTimeOver};
+use super::data::{TradeConfirmReponse, TradeTimeOver, TradeOfferCreateData, TradeCompleteResult};
 use super::sql;
 use crate::shared::Id; use crate::shared::{friend, trade, notification};
 use crate::shared::crypto::JwtToken;
@@ -13,7 +13,7 @@ use crate::{verify_user, verify_collector};
 
 #[post("/trade/<user_friend_id>/<collector_id>/confirm")]
 pub async fn trade_confirm_route(user_friend_id: Id, collector_id: Id, sql: &State<Sql>, config: &State<Config>, token: JwtToken) -> ApiResponseErr<TradeConfirmReponse> {
-    let user_id = token.id;
+    let JwtToken { id: user_id, username } = token;
 
     verify_user!(sql, &user_id, true);
     //NOTE: could be removed if not for the username
@@ -27,13 +27,9 @@ pub async fn trade_confirm_route(user_friend_id: Id, collector_id: Id, sql: &Sta
 
     let trade_id = rjtry!(trade::sql::create_trade(sql, &Id::new(config.id_length), &user_id, &user_friend_id, &collector_id).await);
 
-    let trade_db = rjtry!(trade::sql::get_trade(sql, &user_id, &trade_id).await);
+    rjtry!(trade::sql::expire_trade_offers(sql, &trade_id).await);
 
-    let friend_status = if let Ok(friend_status) = trade::data::TradeStatus::from_int(trade_db.friend_status) {
-            friend_status
-    } else {
-        return ApiResponseErr::api_err(Status::InternalServerError, String::from("Internal Server Error"));
-    };
+    let trade_db = rjtry!(trade::sql::get_trade(sql, &user_id, &trade_id).await);
 
     if let TradeTimeOver::No(next_time) = trade_time_over(trade_db.last_trade, config.trade_cooldown) {
         return ApiResponseErr::api_err(Status::Conflict, format!("Wait until: {}", next_time));
@@ -43,34 +39,68 @@ pub async fn trade_confirm_route(user_friend_id: Id, collector_id: Id, sql: &Sta
         return ApiResponseErr::api_err(Status::Conflict, format!("Can not confirm with open suggestions."));
     }
 
+    let open_offer = rjtry!(trade::sql::get_open_trade_offer(sql, &trade_id).await);
+
+    //confirming an unchanged offer of the friend accepts it
+    if let Some(offer) = open_offer.as_ref().filter(|offer| offer.uidto == user_id) {
+        let offer_cards = rjtry!(sql::get_trade_offer_card_ids(sql, &offer.toid).await);
+        let trade_cards = rjtry!(sql::get_trade_card_ids(sql, &trade_id).await);
+
+        if offer_cards == trade_cards {
+            match rjtry!(sql::complete_trade(sql, &trade_id, &offer.toid).await) {
+                TradeCompleteResult::Completed => (),
+                TradeCompleteResult::OfferClosed =>
+                    return ApiResponseErr::api_err(Status::Conflict, format!("The trade offer {} is not open anymore", &offer.toid)),
+                TradeCompleteResult::CardChanged(card_unlocked_id) =>
+                    return ApiResponseErr::api_err(Status::Conflict, format!("The card {} changed since the offer was made, the trade was aborted", &card_unlocked_id)),
+                TradeCompleteResult::TradeChanged =>
+                    return ApiResponseErr::api_err(Status::Conflict, format!("The trade changed since the offer was made, the trade was aborted")),
+                TradeCompleteResult::CardListed(card_unlocked_id) =>
+                    return ApiResponseErr::api_err(Status::Conflict, format!("The card {} is listed on the market, the trade was aborted", &card_unlocked_id)),
+            }
 
-    if let trade::data::TradeStatus::Confirmed = friend_status {
-        rjtry!(notification::sql::add_notification(sql, &user_friend_id, Some(&collector_id), &notification::data::NotificationCreateData {
-            title: String::from("Trade Completed"),
-            message: format!("Completed trade with {}", user_friend_username),
-            time: Utc::now(),
-            url: format!("user/{}/trade/{}", &user_id, &collector_id),
-        }).await);
-
-        rjtry!(sql::complete_trade(sql, &trade_id).await);
-        rjtry!(trade::sql::set_trade_status(sql, &trade_id, trade::data::TradeStatus::UnConfirmed).await);
-
-        return ApiResponseErr::ok(Status::Ok, TradeConfirmReponse {
-            message: format!("Trade with user {} completed", user_friend_username)
-        })
+            rjtry!(notification::sql::add_notification(sql, &user_friend_id, Some(&collector_id), &notification::data::NotificationCreateData {
+                title: String::from("Trade Completed"),
+                message: format!("Completed trade with {}", username),
+                time: Utc::now(),
+                url: format!("user/{}/trade/{}", &user_id, &collector_id),
+            }).await);
+
+            return ApiResponseErr::ok(Status::Ok, TradeConfirmReponse {
+                message: format!("Trade with user {} completed", user_friend_username),
+                offer_id: offer.toid.clone()
+            })
+        }
     }
 
-    rjtry!(trade::sql::set_trade_status_one(sql, &user_id, &user_friend_id, trade::data::TradeStatus::Confirmed).await);
+    //otherwise this is a new offer or a counter-offer
+    let offer_id = Id::new(config.id_length);
+    rjtry!(sql::create_trade_offer(sql, &TradeOfferCreateData {
+        offer_id: &offer_id,
+        trade_id: &trade_id,
+        collector_id: &collector_id,
+        user_id: &user_id,
+        user_friend_id: &user_friend_id,
+        parent_id: open_offer.as_ref().map(|offer| &offer.toid),
+        expires: Utc::now() + Duration::seconds(config.trade_offer_duration as i64)
+    }).await);
+
+    let (title, message) = if open_offer.is_some() {
+        (String::from("Trade Counter-Offer"), format!("{} made a counter-offer", &username))
+    } else {
+        (String::from("Trade Confirmed"), format!("{} confirmed the trade", &username))
+    };
 
     rjtry!(notification::sql::add_notification(sql, &user_friend_id, Some(&collector_id), &notification::data::NotificationCreateData {
-        title: String::from("Trade Confirmed"),
-        message: format!("{} confirmed the trade", &user_friend_username),
+        title,
+        message,
         time: Utc::now(),
         url: format!("user/{}/trade/{}", &user_id, &collector_id),
     }).await);
 
     ApiResponseErr::ok(Status::Ok, TradeConfirmReponse {
-        message: format!("Confirmed trade with user {}", &user_friend_username)
+        message: format!("Confirmed trade with user {}", &user_friend_username),
+        offer_id
     })
 }
 
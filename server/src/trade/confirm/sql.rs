@@ -1,37 +1,207 @@
 use chrono::Utc;
+use std::collections::HashSet;
 
 use crate::sql::Sql;
 use crate::shared::Id;
+use crate::shared::trade::data::{TradeStatus, TradeOfferStatus};
+use crate::shared::market::data::ListingStatus;
+use super::data::{TradeOfferCreateData, TradeCompleteResult, OfferCardDb};
 
-//transfers the cards, clears the trade and sets the cooldown
-pub async fn complete_trade(sql: &Sql, trade_id: &Id) -> Result<u64, sqlx::Error> {
+pub async fn get_trade_card_ids(sql: &Sql, trade_id: &Id) -> Result<HashSet<Id>, sqlx::Error> {
+    let cards: Vec<(Id, )> = sqlx::query_as(
+        "SELECT cuid
+         FROM tradecards
+         WHERE tid=?;")
+        .bind(trade_id)
+        .fetch_all(sql.pool())
+        .await?;
+
+    Ok(cards.into_iter().map(|(card_unlocked_id, )| card_unlocked_id).collect())
+}
+
+pub async fn get_trade_offer_card_ids(sql: &Sql, offer_id: &Id) -> Result<HashSet<Id>, sqlx::Error> {
+    let cards: Vec<(Id, )> = sqlx::query_as(
+        "SELECT cuid
+         FROM tradeoffercards
+         WHERE toid=?;")
+        .bind(offer_id)
+        .fetch_all(sql.pool())
+        .await?;
+
+    Ok(cards.into_iter().map(|(card_unlocked_id, )| card_unlocked_id).collect())
+}
+
+//snapshots the current trade cards as an offer, superseding the open offer if there is one
+pub async fn create_trade_offer(sql: &Sql, data: &TradeOfferCreateData<'_>) -> Result<(), sqlx::Error> {
     let mut transaction = sql.pool().begin().await?;
 
-    let mut transfered_card_count = 0;
+    if let Some(parent_id) = data.parent_id {
+        sqlx::query(
+            "UPDATE tradeoffers
+             SET tostatus=?, toresolved=?
+             WHERE toid=? AND tostatus=?;")
+            .bind(TradeOfferStatus::Superseded as i32)
+            .bind(Utc::now())
+            .bind(parent_id)
+            .bind(TradeOfferStatus::Open as i32)
+            .execute(&mut *transaction)
+            .await?;
+    }
 
-    transfered_card_count += sqlx::query(
-            "UPDATE cardunlocks, trades, tradecards
-             SET cardunlocks.uid=trades.uidone
-             WHERE cardunlocks.cuid = tradecards.cuid
-             AND tradecards.tid = trades.tid
-             AND trades.tid=?
-             AND cardunlocks.uid<>trades.uidone;")
-        .bind(trade_id)
+    sqlx::query(
+        "INSERT INTO tradeoffers
+         (toid, tid, coid, uidfrom, uidto, toparent, tostatus, tocreated, toexpires)
+         VALUES
+         (?, ?, ?, ?, ?, ?, ?, ?, ?);")
+        .bind(data.offer_id)
+        .bind(data.trade_id)
+        .bind(data.collector_id)
+        .bind(data.user_id)
+        .bind(data.user_friend_id)
+        .bind(data.parent_id)
+        .bind(TradeOfferStatus::Open as i32)
+        .bind(Utc::now())
+        .bind(data.expires)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO tradeoffercards
+         (toid, cuid, uidfrom)
+         SELECT ?, tradecards.cuid, cardunlocks.uid
+         FROM tradecards, cardunlocks
+         WHERE tradecards.cuid = cardunlocks.cuid
+         AND tradecards.tid=?;")
+        .bind(data.offer_id)
+        .bind(data.trade_id)
         .execute(&mut *transaction)
-        .await?
-        .rows_affected();
+        .await?;
+
+    //only the user who made the open offer is confirmed
+    sqlx::query(
+        "UPDATE trades
+         SET tstatusone=IF(uidone=?, ?, ?), tstatustwo=IF(uidtwo=?, ?, ?)
+         WHERE tid=?;")
+        .bind(data.user_id)
+        .bind(TradeStatus::Confirmed as i32)
+        .bind(TradeStatus::UnConfirmed as i32)
+        .bind(data.user_id)
+        .bind(TradeStatus::Confirmed as i32)
+        .bind(TradeStatus::UnConfirmed as i32)
+        .bind(data.trade_id)
+        .execute(&mut *transaction)
+        .await?;
 
-    transfered_card_count += sqlx::query(
-            "UPDATE cardunlocks, trades, tradecards
-             SET cardunlocks.uid=trades.uidone
-             WHERE cardunlocks.cuid = tradecards.cuid
-             AND tradecards.tid = trades.tid
-             AND trades.tid=?
-             AND cardunlocks.uid<>trades.uidtwo;")
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+//accepts the offer, moves every card to the other user, records them in the history, clears the trade and sets the cooldown
+//nothing is changed unless every card of the offer is still in the trade and owned by the user who offered it
+pub async fn complete_trade(sql: &Sql, trade_id: &Id, offer_id: &Id) -> Result<TradeCompleteResult, sqlx::Error> {
+    let mut transaction = sql.pool().begin().await?;
+
+    let offer: Option<(Id, Id, i32)> = sqlx::query_as(
+        "SELECT uidfrom, uidto, tostatus
+         FROM tradeoffers
+         WHERE toid=? AND tid=?
+         FOR UPDATE;")
+        .bind(offer_id)
+        .bind(trade_id)
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+    let (user_id, user_friend_id) = match offer {
+        Some((user_id, user_friend_id, status)) if status == TradeOfferStatus::Open as i32 => (user_id, user_friend_id),
+        _ => return Ok(TradeCompleteResult::OfferClosed)
+    };
+
+    //locks the cards so they can not be upgraded or traded while transfering
+    let cards: Vec<OfferCardDb> = sqlx::query_as(
+        "SELECT tradeoffercards.cuid, tradeoffercards.uidfrom, cardunlocks.uid AS uidowner, tradecards.tid
+         FROM tradeoffercards
+         LEFT JOIN cardunlocks ON cardunlocks.cuid = tradeoffercards.cuid
+         LEFT JOIN tradecards ON tradecards.cuid = tradeoffercards.cuid AND tradecards.tid=?
+         WHERE tradeoffercards.toid=?
+         FOR UPDATE;")
+        .bind(trade_id)
+        .bind(offer_id)
+        .fetch_all(&mut *transaction)
+        .await?;
+
+    if let Some(card) = cards.iter().find(|card| !card_transferable(card, &user_id, &user_friend_id)) {
+        return Ok(TradeCompleteResult::CardChanged(card.cuid.clone()));
+    }
+
+    let (trade_card_count, ): (i64, ) = sqlx::query_as(
+        "SELECT COUNT(*)
+         FROM tradecards
+         WHERE tid=?;")
         .bind(trade_id)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+    if trade_card_count as usize != cards.len() {
+        return Ok(TradeCompleteResult::TradeChanged);
+    }
+
+    //the market checks of the trade routes run without a card lock, a listing
+    //created at the same time would be left open for the old owner
+    let listed: Option<(Id, )> = sqlx::query_as(
+        "SELECT cuid
+         FROM marketlistings
+         WHERE mlstatus=?
+         AND cuid IN (SELECT cuid FROM tradeoffercards WHERE toid=?)
+         LIMIT 1
+         FOR UPDATE;")
+        .bind(ListingStatus::Open as i32)
+        .bind(offer_id)
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+    if let Some((card_unlocked_id, )) = listed {
+        return Ok(TradeCompleteResult::CardListed(card_unlocked_id));
+    }
+
+    sqlx::query(
+        "UPDATE tradeoffers
+         SET tostatus=?, toresolved=?
+         WHERE toid=?;")
+        .bind(TradeOfferStatus::Accepted as i32)
+        .bind(Utc::now())
+        .bind(offer_id)
         .execute(&mut *transaction)
-        .await?
-        .rows_affected();
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO tradehistory
+         (toid, coid, cuid, cid, uidfrom, uidto, thlevel, thquality, thtime)
+         SELECT tradeoffers.toid, tradeoffers.coid, cardunlocks.cuid, cardunlocks.cid,
+                tradeoffercards.uidfrom,
+                IF(tradeoffercards.uidfrom=tradeoffers.uidfrom, tradeoffers.uidto, tradeoffers.uidfrom),
+                cardunlocks.culevel, cardunlocks.cuquality, ?
+         FROM tradeoffers, tradeoffercards, cardunlocks
+         WHERE tradeoffers.toid = tradeoffercards.toid
+         AND tradeoffercards.cuid = cardunlocks.cuid
+         AND tradeoffers.toid=?;")
+        .bind(Utc::now())
+        .bind(offer_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    //ownership was checked above, so every card goes to the user that does not own it
+    sqlx::query(
+            "UPDATE cardunlocks, tradeoffercards
+             SET cardunlocks.uid=IF(cardunlocks.uid=?, ?, ?)
+             WHERE cardunlocks.cuid = tradeoffercards.cuid
+             AND tradeoffercards.toid=?;")
+        .bind(&user_id)
+        .bind(&user_friend_id)
+        .bind(&user_id)
+        .bind(offer_id)
+        .execute(&mut *transaction)
+        .await?;
 
     sqlx::query(
         "DELETE FROM tradecards
@@ -49,14 +219,191 @@ pub async fn complete_trade(sql: &Sql, trade_id: &Id) -> Result<u64, sqlx::Error
 
     sqlx::query(
         "UPDATE trades
-         SET tlasttrade=?
+         SET tlasttrade=?, tstatusone=?, tstatustwo=?
          WHERE tid=?")
         .bind(Utc::now())
+        .bind(TradeStatus::UnConfirmed as i32)
+        .bind(TradeStatus::UnConfirmed as i32)
         .bind(trade_id)
         .execute(&mut *transaction)
         .await?;
 
     transaction.commit().await?;
 
-    Ok(transfered_card_count)
+    Ok(TradeCompleteResult::Completed)
+}
+
+fn card_transferable(card: &OfferCardDb, user_id: &Id, user_friend_id: &Id) -> bool {
+    (&card.uidfrom == user_id || &card.uidfrom == user_friend_id)
+        && card.uidowner.as_ref() == Some(&card.uidfrom)
+        && card.tid.is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use crate::sql::test::TestDb;
+
+    //NOTE: the tests need a MySQL server, see `TestDb`:
+    //`TEST_DATABASE_URL=mysql://root@localhost cargo test trade::confirm -- --ignored`
+
+    const TRADE: &str = "trade";
+
+    async fn execute(sql: &Sql, query: &str) {
+        sqlx::query(query).execute(sql.pool()).await.expect(query);
+    }
+
+    //user one owns card one, user two owns card two, both cards are in the trade
+    async fn seed(sql: &Sql) {
+        execute(sql, "INSERT INTO users (uid, uusername, upassword, uranking, uemail, uverified)
+                      VALUES ('one', 'one', '', 0, 'one@test', 1), ('two', 'two', '', 0, 'two@test', 1), ('three', 'three', '', 0, 'three@test', 1);").await;
+        execute(sql, "INSERT INTO collectors (coid, uid, coname, codescription) VALUES ('collector', 'one', 'collector', '');").await;
+        execute(sql, "INSERT INTO cardtypes (ctid, coid, uid, ctname, ctstate) VALUES ('cardtype', 'collector', 'one', 'cardtype', 1);").await;
+        execute(sql, "INSERT INTO cards (cid, cname, ctid, uid, cstate) VALUES ('card', 'card', 'cardtype', 'one', 1);").await;
+        execute(sql, "INSERT INTO cardunlocks (cuid, uid, cid, cuquality, culevel)
+                      VALUES ('cardone', 'one', 'card', 3, 0), ('cardtwo', 'two', 'card', 4, 1);").await;
+        execute(sql, "INSERT INTO trades (tid, coid, uidone, uidtwo, tstatusone, tstatustwo) VALUES ('trade', 'collector', 'one', 'two', 0, 0);").await;
+        execute(sql, "INSERT INTO tradecards (tid, cuid) VALUES ('trade', 'cardone'), ('trade', 'cardtwo');").await;
+    }
+
+    async fn offer(sql: &Sql, offer_id: &str, user_id: &str, user_friend_id: &str) -> Id {
+        let offer_id = Id::from(offer_id);
+
+        create_trade_offer(sql, &TradeOfferCreateData {
+            offer_id: &offer_id,
+            trade_id: &Id::from(TRADE),
+            collector_id: &Id::from("collector"),
+            user_id: &Id::from(user_id),
+            user_friend_id: &Id::from(user_friend_id),
+            parent_id: None,
+            expires: Utc::now() + Duration::hours(1)
+        }).await.unwrap();
+
+        offer_id
+    }
+
+    async fn owner(sql: &Sql, card_unlocked_id: &str) -> String {
+        let (owner, ): (String, ) = sqlx::query_as("SELECT uid FROM cardunlocks WHERE cuid=?;")
+            .bind(card_unlocked_id)
+            .fetch_one(sql.pool())
+            .await
+            .unwrap();
+
+        owner
+    }
+
+    async fn history(sql: &Sql) -> Vec<(String, String, String)> {
+        sqlx::query_as("SELECT cuid, uidfrom, uidto FROM tradehistory ORDER BY cuid;")
+            .fetch_all(sql.pool())
+            .await
+            .unwrap()
+    }
+
+    async fn assert_transfers(offer_from: &str, offer_to: &str) {
+        let db = TestDb::create().await;
+        let sql = &db.sql;
+        seed(sql).await;
+
+        let offer_id = offer(sql, "offer", offer_from, offer_to).await;
+        assert!(matches!(complete_trade(sql, &Id::from(TRADE), &offer_id).await.unwrap(), TradeCompleteResult::Completed));
+
+        assert_eq!(owner(sql, "cardone").await, "two");
+        assert_eq!(owner(sql, "cardtwo").await, "one");
+        assert_eq!(history(sql).await, vec![
+            (String::from("cardone"), String::from("one"), String::from("two")),
+            (String::from("cardtwo"), String::from("two"), String::from("one")),
+        ]);
+        assert!(get_trade_card_ids(sql, &Id::from(TRADE)).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_complete_trade_offered_by_user_one() {
+        assert_transfers("one", "two").await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_complete_trade_offered_by_user_two() {
+        assert_transfers("two", "one").await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_complete_trade_aborts_on_changed_owner() {
+        let db = TestDb::create().await;
+        let sql = &db.sql;
+        seed(sql).await;
+
+        let offer_id = offer(sql, "offer", "one", "two").await;
+        execute(sql, "UPDATE cardunlocks SET uid='three' WHERE cuid='cardone';").await;
+
+        match complete_trade(sql, &Id::from(TRADE), &offer_id).await.unwrap() {
+            TradeCompleteResult::CardChanged(card_unlocked_id) => assert_eq!(card_unlocked_id, Id::from("cardone")),
+            _ => panic!("trade was not aborted")
+        }
+        assert_eq!(owner(sql, "cardtwo").await, "two");
+        assert!(history(sql).await.is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_complete_trade_aborts_on_upgraded_card() {
+        let db = TestDb::create().await;
+        let sql = &db.sql;
+        seed(sql).await;
+
+        let offer_id = offer(sql, "offer", "two", "one").await;
+        execute(sql, "DELETE FROM cardunlocks WHERE cuid='cardtwo';").await;
+
+        assert!(matches!(complete_trade(sql, &Id::from(TRADE), &offer_id).await.unwrap(), TradeCompleteResult::CardChanged(_)));
+        assert_eq!(owner(sql, "cardone").await, "one");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_complete_trade_aborts_on_added_card() {
+        let db = TestDb::create().await;
+        let sql = &db.sql;
+        seed(sql).await;
+
+        let offer_id = offer(sql, "offer", "one", "two").await;
+        execute(sql, "INSERT INTO cardunlocks (cuid, uid, cid, cuquality, culevel) VALUES ('cardthree', 'two', 'card', 1, 0);").await;
+        execute(sql, "INSERT INTO tradecards (tid, cuid) VALUES ('trade', 'cardthree');").await;
+
+        assert!(matches!(complete_trade(sql, &Id::from(TRADE), &offer_id).await.unwrap(), TradeCompleteResult::TradeChanged));
+        assert_eq!(owner(sql, "cardone").await, "one");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_complete_trade_aborts_on_listed_card() {
+        let db = TestDb::create().await;
+        let sql = &db.sql;
+        seed(sql).await;
+
+        let offer_id = offer(sql, "offer", "one", "two").await;
+        execute(sql, "INSERT INTO marketlistings (mlid, cuid, coid, uid, mlprice, mlstatus) VALUES ('listing', 'cardtwo', 'collector', 'two', 10, 0);").await;
+
+        match complete_trade(sql, &Id::from(TRADE), &offer_id).await.unwrap() {
+            TradeCompleteResult::CardListed(card_unlocked_id) => assert_eq!(card_unlocked_id, Id::from("cardtwo")),
+            _ => panic!("trade was not aborted")
+        }
+        assert_eq!(owner(sql, "cardtwo").await, "two");
+        assert!(history(sql).await.is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_complete_trade_only_once() {
+        let db = TestDb::create().await;
+        let sql = &db.sql;
+        seed(sql).await;
+
+        let offer_id = offer(sql, "offer", "one", "two").await;
+        assert!(matches!(complete_trade(sql, &Id::from(TRADE), &offer_id).await.unwrap(), TradeCompleteResult::Completed));
+        assert!(matches!(complete_trade(sql, &Id::from(TRADE), &offer_id).await.unwrap(), TradeCompleteResult::OfferClosed));
+        assert_eq!(owner(sql, "cardone").await, "two");
+    }
 }
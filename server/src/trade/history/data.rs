@@ -0,0 +1,67 @@
+use serde::Serialize;
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+
+use crate::shared::Id;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct TradeHistoryResponse {
+    pub page_size: u32,
+    pub page: u32,
+    pub trade_count: u32,
+    pub trades: Vec<TradeHistoryEntry>
+}
+
+//friend is None if the user got deleted
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct TradeHistoryEntry {
+    pub offer_id: Id,
+    pub user_friend_id: Option<Id>,
+    pub friend_username: Option<String>,
+    pub time: DateTime<Utc>,
+    pub sent_cards: Vec<TradeHistoryCard>,
+    pub received_cards: Vec<TradeHistoryCard>
+}
+
+//level and quality at the time of the trade, the card could be deleted by now
+#[derive(Debug, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct TradeHistoryCard {
+    pub card_unlocked_id: Id,
+    pub card_id: Option<Id>,
+    pub card_name: Option<String>,
+    pub level: i32,
+    pub quality: i32
+}
+
+#[derive(Debug, FromRow)]
+pub struct TradeHistoryEntryDb {
+    pub toid: Id,
+    pub thtime: DateTime<Utc>,
+    pub uidfriend: Option<Id>,
+    pub uusername: Option<String>
+}
+
+#[derive(Debug, FromRow)]
+pub struct TradeHistoryCardDb {
+    pub cuid: Id,
+    pub cid: Option<Id>,
+    pub cname: Option<String>,
+    pub uidfrom: Option<Id>,
+    pub thlevel: i32,
+    pub thquality: i32
+}
+
+impl From<TradeHistoryCardDb> for TradeHistoryCard {
+    fn from(card: TradeHistoryCardDb) -> Self {
+        Self {
+            card_unlocked_id: card.cuid,
+            card_id: card.cid,
+            card_name: card.cname,
+            level: card.thlevel,
+            quality: card.thquality
+        }
+    }
+}
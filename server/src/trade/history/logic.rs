@@ -0,0 +1,48 @@
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+use rocket::http::Status;
+use rocket::State;
+
+use super::data::{TradeHistoryResponse, TradeHistoryEntry, TradeHistoryCard};
+use super::sql;
+use crate::sql::Sql;
+use crate::config::Config;
+use crate::shared::Id;
+use crate::shared::crypto::JwtToken;
+use crate::{verify_user, verify_collector};
+
+//NOTE: the query makes this rank before /trade/<user_friend_id>/<collector_id>
+#[get("/trade/history/<collector_id>?<page>")]
+pub async fn trade_history_route(collector_id: Id, page: Option<u32>, sql: &State<Sql>, config: &State<Config>, token: JwtToken) -> ApiResponseErr<TradeHistoryResponse> {
+    let user_id = token.id;
+
+    verify_user!(sql, &user_id, true);
+    verify_collector!(sql, &collector_id);
+
+    let page = page.unwrap_or(0);
+
+    let history = rjtry!(sql::get_trade_history(sql, &user_id, &collector_id, config.trade_history_page_amount, config.trade_history_page_amount * page).await);
+    let trade_count = rjtry!(sql::get_trade_history_count(sql, &user_id, &collector_id).await);
+
+    let mut trades = Vec::new();
+    for entry in history {
+        let (sent_cards, received_cards): (Vec<_>, Vec<_>) = rjtry!(sql::get_trade_history_cards(sql, &entry.toid).await)
+            .into_iter()
+            .partition(|card| card.uidfrom.as_ref() == Some(&user_id));
+
+        trades.push(TradeHistoryEntry {
+            offer_id: entry.toid,
+            user_friend_id: entry.uidfriend,
+            friend_username: entry.uusername,
+            time: entry.thtime,
+            sent_cards: sent_cards.into_iter().map(TradeHistoryCard::from).collect(),
+            received_cards: received_cards.into_iter().map(TradeHistoryCard::from).collect()
+        });
+    }
+
+    ApiResponseErr::ok(Status::Ok, TradeHistoryResponse {
+        page_size: config.trade_history_page_amount,
+        page,
+        trade_count,
+        trades
+    })
+}
@@ -0,0 +1,51 @@
+use crate::sql::Sql;
+use crate::shared::Id;
+use super::data::{TradeHistoryEntryDb, TradeHistoryCardDb};
+
+pub async fn get_trade_history(sql: &Sql, user_id: &Id, collector_id: &Id, amount: u32, offset: u32) -> Result<Vec<TradeHistoryEntryDb>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT history.toid, history.thtime, history.uidfriend, users.uusername
+         FROM (
+            SELECT toid, MAX(thtime) AS thtime, MAX(IF(uidfrom=?, uidto, uidfrom)) AS uidfriend
+            FROM tradehistory
+            WHERE coid=? AND (uidfrom=? OR uidto=?)
+            GROUP BY toid
+         ) AS history
+         LEFT JOIN users ON users.uid = history.uidfriend
+         ORDER BY history.thtime DESC
+         LIMIT ? OFFSET ?;")
+        .bind(user_id)
+        .bind(collector_id)
+        .bind(user_id)
+        .bind(user_id)
+        .bind(amount)
+        .bind(offset)
+        .fetch_all(sql.pool())
+        .await
+}
+
+pub async fn get_trade_history_count(sql: &Sql, user_id: &Id, collector_id: &Id) -> Result<u32, sqlx::Error> {
+    let (count, ): (i64, ) = sqlx::query_as(
+        "SELECT COUNT(DISTINCT toid)
+         FROM tradehistory
+         WHERE coid=? AND (uidfrom=? OR uidto=?);")
+        .bind(collector_id)
+        .bind(user_id)
+        .bind(user_id)
+        .fetch_one(sql.pool())
+        .await?;
+
+    Ok(count as u32)
+}
+
+pub async fn get_trade_history_cards(sql: &Sql, offer_id: &Id) -> Result<Vec<TradeHistoryCardDb>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT tradehistory.cuid, tradehistory.cid, cards.cname, tradehistory.uidfrom, tradehistory.thlevel, tradehistory.thquality
+         FROM tradehistory
+         LEFT JOIN cards ON cards.cid = tradehistory.cid
+         WHERE tradehistory.toid=?
+         ORDER BY tradehistory.thid;")
+        .bind(offer_id)
+        .fetch_all(sql.pool())
+        .await
+}
@@ -2,7 +2,7 @@ use serde::Serialize;
 use chrono::{DateTime, Utc};
 
 use crate::shared::card::data::UnlockedCard;
-use crate::shared::trade::data::TradeStatus;
+use crate::shared::trade::data::{TradeStatus, TradeOffer};
 
 //TODO: maybe split into 2 structs
 #[derive(Debug, Serialize)]
@@ -15,6 +15,7 @@ pub struct TradeResponse {
     pub friend_username: String,
     pub self_status: TradeStatus,
     pub friend_status: TradeStatus,
+    pub offer: Option<TradeOffer>,
     pub trade_card_limit: u32,
     pub trade_time: DateTime<Utc>
 }
@@ -25,6 +25,8 @@ pub async fn trade_route(user_friend_id: Id, collector_id: Id, sql: &State<Sql>,
     }
     let trade_id = rjtry!(trade::sql::create_trade(sql, &Id::new(config.id_length), &user_id, &user_friend_id, &collector_id).await);
 
+    rjtry!(trade::sql::expire_trade_offers(sql, &trade_id).await);
+
     let self_cards = rjtry!(sql::trade_cards(sql, &user_id, &trade_id).await);
     let friend_cards = rjtry!(sql::trade_cards(sql, &user_friend_id, &trade_id).await);
 
@@ -40,6 +42,12 @@ pub async fn trade_route(user_friend_id: Id, collector_id: Id, sql: &State<Sql>,
         return ApiResponseErr::api_err(Status::InternalServerError, String::from("Internal Server Error"));
     };
 
+    let offer = match rjtry!(trade::sql::get_open_trade_offer(sql, &trade_id).await).map(trade::data::TradeOffer::try_from) {
+        Some(Ok(offer)) => Some(offer),
+        None => None,
+        Some(Err(_)) => return ApiResponseErr::api_err(Status::InternalServerError, String::from("Internal Server Error"))
+    };
+
     let trade_time = util::time_from_db(trade_db.last_trade, config.trade_cooldown);
 
     ApiResponseErr::ok(Status::Ok, TradeResponse {
@@ -50,6 +58,7 @@ pub async fn trade_route(user_friend_id: Id, collector_id: Id, sql: &State<Sql>,
         friend_username,
         self_status,
         friend_status,
+        offer,
         trade_time,
         trade_card_limit: config.trade_card_limit
     })
@@ -3,3 +3,5 @@ pub mod suggestion;
 pub mod info;
 pub mod confirm;
 pub mod time;
+pub mod offer;
+pub mod history;
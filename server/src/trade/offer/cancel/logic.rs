@@ -0,0 +1,41 @@
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+use rocket::http::Status;
+use rocket::State;
+use chrono::Utc;
+
+use super::data::TradeOfferCancelResponse;
+use crate::shared::Id;
+use crate::shared::{trade, notification};
+use crate::shared::crypto::JwtToken;
+use crate::sql::Sql;
+use crate::verify_user;
+
+#[post("/trade/offer/<offer_id>/cancel")]
+pub async fn trade_offer_cancel_route(offer_id: Id, sql: &State<Sql>, token: JwtToken) -> ApiResponseErr<TradeOfferCancelResponse> {
+    let JwtToken { id: user_id, username } = token;
+
+    verify_user!(sql, &user_id, true);
+
+    //only the user who made the offer can cancel it
+    let offer = match rjtry!(trade::sql::get_trade_offer(sql, &offer_id).await) {
+        Some(offer) if offer.uidfrom == user_id => offer,
+        _ => return ApiResponseErr::api_err(Status::NotFound, format!("No trade offer with id {} found", &offer_id))
+    };
+
+    rjtry!(trade::sql::expire_trade_offers(sql, &offer.tid).await);
+
+    if !rjtry!(trade::sql::close_trade_offer(sql, &offer.toid, &offer.tid, trade::data::TradeOfferStatus::Cancelled).await) {
+        return ApiResponseErr::api_err(Status::Conflict, format!("The trade offer {} is not open anymore", &offer_id));
+    }
+
+    rjtry!(notification::sql::add_notification(sql, &offer.uidto, Some(&offer.coid), &notification::data::NotificationCreateData {
+        title: String::from("Trade Offer Cancelled"),
+        message: format!("{} cancelled their trade offer", &username),
+        time: Utc::now(),
+        url: format!("user/{}/trade/{}", &user_id, &offer.coid),
+    }).await);
+
+    ApiResponseErr::ok(Status::Ok, TradeOfferCancelResponse {
+        message: format!("Cancelled trade offer {}", &offer_id)
+    })
+}
@@ -0,0 +1,41 @@
+use rocketjson::{ApiResponseErr, rjtry, error::ApiErrorsCreate};
+use rocket::http::Status;
+use rocket::State;
+use chrono::Utc;
+
+use super::data::TradeOfferDeclineResponse;
+use crate::shared::Id;
+use crate::shared::{trade, notification};
+use crate::shared::crypto::JwtToken;
+use crate::sql::Sql;
+use crate::verify_user;
+
+#[post("/trade/offer/<offer_id>/decline")]
+pub async fn trade_offer_decline_route(offer_id: Id, sql: &State<Sql>, token: JwtToken) -> ApiResponseErr<TradeOfferDeclineResponse> {
+    let JwtToken { id: user_id, username } = token;
+
+    verify_user!(sql, &user_id, true);
+
+    //only the receiver can decline an offer
+    let offer = match rjtry!(trade::sql::get_trade_offer(sql, &offer_id).await) {
+        Some(offer) if offer.uidto == user_id => offer,
+        _ => return ApiResponseErr::api_err(Status::NotFound, format!("No trade offer with id {} found", &offer_id))
+    };
+
+    rjtry!(trade::sql::expire_trade_offers(sql, &offer.tid).await);
+
+    if !rjtry!(trade::sql::close_trade_offer(sql, &offer.toid, &offer.tid, trade::data::TradeOfferStatus::Declined).await) {
+        return ApiResponseErr::api_err(Status::Conflict, format!("The trade offer {} is not open anymore", &offer_id));
+    }
+
+    rjtry!(notification::sql::add_notification(sql, &offer.uidfrom, Some(&offer.coid), &notification::data::NotificationCreateData {
+        title: String::from("Trade Offer Declined"),
+        message: format!("{} declined your trade offer", &username),
+        time: Utc::now(),
+        url: format!("user/{}/trade/{}", &user_id, &offer.coid),
+    }).await);
+
+    ApiResponseErr::ok(Status::Ok, TradeOfferDeclineResponse {
+        message: format!("Declined trade offer {}", &offer_id)
+    })
+}
@@ -0,0 +1,4 @@
+mod logic;
+mod data;
+
+pub use logic::trade_offer_decline_route;
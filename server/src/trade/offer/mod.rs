@@ -0,0 +1,2 @@
+pub mod decline;
+pub mod cancel;
@@ -5,7 +5,7 @@ use chrono::Utc;
 
 use crate::sql::Sql;
 use crate::shared::Id;
-use crate::shared::{friend, card, trade, notification};
+use crate::shared::{friend, card, trade, notification, market};
 use crate::shared::crypto::JwtToken;
 use crate::{verify_user, verify_collector};
 use crate::config::Config;
@@ -39,6 +39,10 @@ pub async fn trade_suggestion_add_route(user_friend_id: Id, card_unlocked_id: Id
         return ApiResponseErr::api_err(Status::Conflict, format!("Card with id {} is already in a trade", &card_unlocked_id));
     }
 
+    if rjtry!(market::sql::card_listed(sql, &card_unlocked_id).await) {
+        return ApiResponseErr::api_err(Status::Conflict, format!("Card with id {} is listed on the market", &card_unlocked_id));
+    }
+
     if rjtry!(trade::sql::suggestion_in_trade(sql, &trade_id, &card_unlocked_id).await) {
         return ApiResponseErr::api_err(Status::Conflict, format!("Card with id {} is already suggested in the trade with {}", &card_unlocked_id, &user_friend_username));
     }
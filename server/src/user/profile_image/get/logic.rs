@@ -1,8 +1,9 @@
 use rocket::State;
-use rocket::http::{Status, ContentType};
+use rocket::http::Status;
 
 use crate::sql::Sql;
-use crate::media::MediaManager;
+use crate::media::{MediaManager, MediaResponse, MediaError, RenderContext};
+use crate::media::sql as media_sql;
 use crate::shared::Id;
 use crate::shared::user::sql as user_sql;
 
@@ -11,12 +12,12 @@ pub async fn profile_image_get_route(
     user_id: Id,
     sql: &State<Sql>,
     media_manager: &State<MediaManager>
-) -> Result<(ContentType, Vec<u8>), Status> {
+) -> Result<MediaResponse, MediaError> {
     // Check if user exists
     match user_sql::username_from_user_id(sql, &user_id).await {
         Ok(Some(_)) => (),
-        Ok(None) => return Err(Status::NotFound),
-        Err(_) => return Err(Status::InternalServerError)
+        Ok(None) => return Err(Status::NotFound.into()),
+        Err(_) => return Err(Status::InternalServerError.into())
     }
 
     // Get image hash from database
@@ -25,18 +26,21 @@ pub async fn profile_image_get_route(
         Ok(None) => {
             String::from("profile-image-default")
         },
-        Err(_) => return Err(Status::InternalServerError)
+        Err(_) => return Err(Status::InternalServerError.into())
     };
-    
-    // Get image through MediaManager with "profile" media type and "default" variant
-    let (bytes, format) = media_manager
-        .get_image("profile", &image_hash, None)
-        .await
-        .map_err(|_| Status::NotFound)?;
 
-    // Parse Content-Type based on format
-    let content_type = ContentType::parse_flexible(format.mime_type())
-        .unwrap_or(ContentType::Binary);
+    // Crops are centered on the focal point of the image if it has one
+    let focal_point = match media_sql::get_focal_point(sql, &image_hash).await {
+        Ok(focal_point) => focal_point,
+        Err(_) => return Err(Status::InternalServerError.into())
+    };
+    let context = RenderContext::default().with_focal_point(focal_point);
+
+    // Get image through MediaManager with "profile" media type and "default" variant
+    let image = media_manager
+        .get_composite_image("profile", &image_hash, None, &context, None)
+        .await?;
 
-    Ok((content_type, bytes))
+    // The URL stays the same when the image changes, clients revalidate with the ETag
+    Ok(MediaResponse::revalidate(image))
 }
@@ -4,6 +4,7 @@ use rocket::State;
 use rocket::form::Form;
 
 use crate::sql::Sql;
+use crate::config::Config;
 use crate::media::MediaManager;
 use crate::shared::crypto::JwtToken;
 use crate::shared::image_upload::upload_image_with_media_manager;
@@ -15,6 +16,7 @@ use super::data::{ProfileImageSetResponse, ProfileImageSetRequest};
 pub async fn profile_image_set_route(
     mut data: Form<ProfileImageSetRequest<'_>>,
     sql: &State<Sql>,
+    config: &State<Config>,
     media_manager: &State<MediaManager>,
     token: JwtToken
 ) -> ApiResponseErr<ProfileImageSetResponse> {
@@ -22,8 +24,9 @@ pub async fn profile_image_set_route(
     verify_user!(sql, &user_id, true);
 
     // Upload image to MediaManager (returns hash-based ID)
-    let image_hash = match upload_image_with_media_manager(&mut data.file, media_manager).await {
+    let image_hash = match upload_image_with_media_manager(&mut data.file, "profile", config, media_manager).await {
         Ok(hash) => hash,
+        Err(err) if err.status().class().is_client_error() => return ApiResponseErr::api_err(err.status(), err.to_string()),
         Err(_) => return ApiResponseErr::api_err(Status::InternalServerError, String::from("Error uploading image"))
     };
 